@@ -0,0 +1,96 @@
+use crate::audit::AuditAction;
+use crate::hooks::HookScript;
+use crate::power::PowerAction;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// 计时、规则和日程执行的操作，前后端共用
+// 电源操作直接序列化为字符串（如 "shutdown"），与旧的配置文件兼容
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    KillProcesses {
+        names: Vec<String>,
+        // 同时结束这些进程启动的所有子进程，如启动器拉起的游戏和覆盖层
+        #[serde(default)]
+        tree: bool,
+    },
+    RunScript { script: HookScript },
+    // 如计划睡眠前切换到节能方案
+    SetPowerPlan { guid: String },
+    // 如睡眠前的警告期间调暗屏幕
+    SetBrightness { percent: u8 },
+    // 依次尝试，前一步失败时改用下一步，如睡眠 → 休眠 → 关机
+    Fallback { steps: Vec<FallbackStep> },
+    #[serde(untagged)]
+    Power(PowerAction),
+}
+
+// 回退链中的一步；wake_within_secs 大于 0 时，睡眠或休眠后在该时间内被唤醒也视为失败
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FallbackStep {
+    pub action: PowerAction,
+    #[serde(default)]
+    pub wake_within_secs: u64,
+}
+
+impl Action {
+    pub fn power(&self) -> Option<PowerAction> {
+        match self {
+            Action::Power(action) => Some(*action),
+            _ => None,
+        }
+    }
+
+    // 同时到期时用于裁决，非电源操作不影响电脑状态，排在最后
+    pub fn severity(&self) -> u8 {
+        match self {
+            Action::Fallback { steps } => steps.iter().map(|s| s.action.severity()).max().unwrap_or(0),
+            other => other.power().map_or(0, PowerAction::severity),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Action::Fallback { steps } if steps.is_empty() => Err("Add at least one action to try".into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn audit(&self) -> AuditAction {
+        match self {
+            Action::Power(action) => AuditAction::Power { action: *action },
+            Action::KillProcesses { names, tree } => AuditAction::KillProcesses { names: names.clone(), tree: *tree },
+            Action::RunScript { script } => AuditAction::RunScript { script: script.clone() },
+            Action::SetPowerPlan { guid } => AuditAction::SetPowerPlan { guid: guid.clone() },
+            Action::SetBrightness { percent } => AuditAction::SetBrightness { percent: *percent },
+            // 每一步执行时各自记录，这里只用于取消等整体记录，按第一步显示
+            Action::Fallback { steps } => AuditAction::Power {
+                action: steps.first().map_or(PowerAction::Shutdown, |s| s.action),
+            },
+        }
+    }
+}
+
+impl From<PowerAction> for Action {
+    fn from(action: PowerAction) -> Self {
+        Action::Power(action)
+    }
+}
+
+// 用于通知和日志中的可读描述
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Power(action) => write!(f, "{:?}", action),
+            Action::KillProcesses { names, .. } => write!(f, "Close {}", names.join(", ")),
+            Action::RunScript { script } => write!(f, "Run {}", script.path),
+            Action::SetPowerPlan { guid } => write!(f, "Switch power plan to {}", guid),
+            Action::SetBrightness { percent } => write!(f, "Set brightness to {}%", percent),
+            Action::Fallback { steps } => {
+                let steps: Vec<String> = steps.iter().map(|s| format!("{:?}", s.action)).collect();
+                write!(f, "{}", steps.join(", else "))
+            }
+        }
+    }
+}
@@ -0,0 +1,520 @@
+use crate::actions::Action;
+use crate::audit::{ActionSource, AuditAction};
+use crate::executor;
+use crate::peers::{self, PairRequest, PeerStore};
+use crate::power::PowerAction;
+use crate::settings::SettingsStore;
+use crate::timers::{ArmedTimer, TimerSpec, TimerStatus, TimerStore};
+use crate::ws;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(200);
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_BODY: usize = 64 * 1024;
+// 请求行和所有头部合计的上限
+const MAX_HEAD: u64 = 16 * 1024;
+// 同时处理的连接数，超出时直接关闭新连接
+const MAX_CONNECTIONS: usize = 16;
+// 取消令牌的有效期，覆盖最早的倒计时提醒
+const CANCEL_TOKEN_TTL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct ApiSettings {
+    pub enabled: bool,
+    // 默认只监听本机，改为 0.0.0.0 可供局域网内的设备访问
+    pub bind: String,
+    pub port: u16,
+    // 请求需带上 Authorization: Bearer <token>，启用时为空则自动生成
+    pub token: String,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        ApiSettings {
+            enabled: false,
+            bind: "127.0.0.1".into(),
+            port: 47800,
+            token: String::new(),
+        }
+    }
+}
+
+// 设置会写入日志，不输出令牌
+impl fmt::Debug for ApiSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiSettings")
+            .field("enabled", &self.enabled)
+            .field("bind", &self.bind)
+            .field("port", &self.port)
+            .finish_non_exhaustive()
+    }
+}
+
+struct Running {
+    config: ApiSettings,
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+#[derive(Default)]
+pub struct ApiServer(Mutex<Option<Running>>);
+
+// 发给 ntfy 等外部服务的一次性令牌，只能取消对应的一个计时，用后或过期即失效
+#[derive(Default)]
+pub struct CancelTokens(Mutex<HashMap<String, (String, Instant)>>);
+
+impl CancelTokens {
+    fn redeem(&self, token: &str) -> Option<String> {
+        let mut tokens = self.0.lock().unwrap();
+        tokens.retain(|_, (_, expires)| *expires > Instant::now());
+        tokens.remove(token).map(|(timer_id, _)| timer_id)
+    }
+}
+
+pub fn mint_cancel_token(app: &AppHandle, timer_id: &str) -> String {
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let store = app.state::<CancelTokens>();
+    let mut tokens = store.0.lock().unwrap();
+    tokens.retain(|_, (_, expires)| *expires > Instant::now());
+    tokens.insert(token.clone(), (timer_id.to_string(), Instant::now() + CANCEL_TOKEN_TTL));
+    token
+}
+
+impl ApiServer {
+    pub fn is_running(&self) -> bool {
+        self.0.lock().unwrap().is_some()
+    }
+}
+
+// 按设置启动、重启或停止服务，配置未变时不做任何事
+pub fn apply(app: &AppHandle, config: &ApiSettings) -> Result<(), String> {
+    let server = app.state::<ApiServer>();
+    let mut running = server.0.lock().unwrap();
+    if running.as_ref().is_some_and(|r| &r.config == config) {
+        return Ok(());
+    }
+    // 先等旧的监听线程退出，释放端口后再重新绑定
+    if let Some(old) = running.take() {
+        old.stop.store(true, Ordering::SeqCst);
+        let _ = old.handle.join();
+        peers::withdraw();
+        tracing::info!("http api stopped");
+    }
+    if !config.enabled {
+        return Ok(());
+    }
+    if config.token.is_empty() {
+        return Err("HTTP API needs a token".into());
+    }
+
+    let listener = TcpListener::bind((config.bind.as_str(), config.port))
+        .map_err(|e| format!("Failed to listen on {}:{}: {}", config.bind, config.port, e))?;
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let handle = {
+        let app = app.clone();
+        let stop = stop.clone();
+        let token = config.token.clone();
+        thread::spawn(move || accept_loop(app, listener, token, stop))
+    };
+    tracing::info!(bind = %config.bind, port = config.port, "http api listening");
+    peers::advertise(config);
+    *running = Some(Running {
+        config: config.clone(),
+        stop,
+        handle,
+    });
+    Ok(())
+}
+
+pub fn start_server(app: AppHandle) {
+    let config = app.state::<SettingsStore>().get().api;
+    if let Err(e) = apply(&app, &config) {
+        tracing::error!(error = %e, "failed to start http api");
+    }
+}
+
+// 连接处理线程结束（包括崩溃）时释放占用的名额
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn accept_loop(app: AppHandle, listener: TcpListener, token: String, stop: Arc<AtomicBool>) {
+    let active = Arc::new(AtomicUsize::new(0));
+    while !stop.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    tracing::debug!(%peer, "http api connection rejected: too many connections");
+                    continue;
+                }
+                let slot = ConnectionSlot(active.clone());
+                let app = app.clone();
+                let token = token.clone();
+                thread::spawn(move || {
+                    if let Err(e) = handle_connection(&app, stream, &token, slot) {
+                        tracing::debug!(%peer, error = %e, "http api connection failed");
+                    }
+                });
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => thread::sleep(ACCEPT_INTERVAL),
+            Err(e) => {
+                tracing::warn!(error = %e, "http api accept failed");
+                thread::sleep(ACCEPT_INTERVAL);
+            }
+        }
+    }
+}
+
+pub struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn query(&self, name: &str) -> Option<&str> {
+        let (_, query) = self.path.split_once('?')?;
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v)
+    }
+}
+
+// 只支持本服务需要的 HTTP/1.1 子集：一个请求一个连接，请求体按 Content-Length 读取
+// 超出头部上限或连接提前关闭时读不到换行
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> Result<(), String> {
+    line.clear();
+    reader.read_line(line).map_err(|e| e.to_string())?;
+    if !line.ends_with('\n') {
+        return Err("Request header too large or incomplete".into());
+    }
+    Ok(())
+}
+
+fn read_request(reader: &mut impl BufRead) -> Result<Request, String> {
+    let mut head = reader.by_ref().take(MAX_HEAD);
+    let mut line = String::new();
+    read_line(&mut head, &mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err("Malformed request line".into());
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut headers = Vec::new();
+    loop {
+        read_line(&mut head, &mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let mut request = Request {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+    let length: usize = request.header("Content-Length").and_then(|v| v.parse().ok()).unwrap_or(0);
+    if length > MAX_BODY {
+        return Err("Request body too large".into());
+    }
+    request.body.resize(length, 0);
+    reader.read_exact(&mut request.body).map_err(|e| e.to_string())?;
+    Ok(request)
+}
+
+fn handle_connection(app: &AppHandle, stream: TcpStream, token: &str, slot: ConnectionSlot) -> Result<(), String> {
+    stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+    stream.set_read_timeout(Some(READ_TIMEOUT)).map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    let request = read_request(&mut reader)?;
+
+    // 配对请求凭配对码而不是令牌验证
+    if request.method == "POST" && request.path == "/pair" {
+        let (status, body) = pair(app, &request, stream.peer_addr().map_err(|e| e.to_string())?.ip());
+        return write_response(stream, status, &body);
+    }
+    // 一次性取消令牌只在这个路由上有效
+    if let Some(cancel_token) = request.path.strip_prefix("/cancel/").filter(|_| request.method == "POST") {
+        let (status, body) = match app.state::<CancelTokens>().redeem(cancel_token) {
+            Some(id) => match app.state::<TimerStore>().cancel(&id) {
+                Some(_) => (200, json!({ "cancelled": 1 })),
+                None => error(404, "Timer not found"),
+            },
+            None => (401, json!({ "error": "Missing or invalid token" })),
+        };
+        tracing::debug!(status, "http api cancel token request");
+        return write_response(stream, status, &body);
+    }
+
+    // 浏览器的 WebSocket 无法设置请求头，升级请求也接受 ?token= 参数
+    let upgrade = ws::is_upgrade(&request);
+    let given = request
+        .header("Authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .or_else(|| upgrade.then(|| request.query("token")).flatten());
+    let authorized = given.is_some_and(|t| token_matches(t, token));
+    if authorized && upgrade {
+        // WebSocket 连接长期保持，由 ws 模块单独限制数量
+        drop(slot);
+        return ws::serve(app, stream, &request);
+    }
+    let (status, body) = if authorized {
+        route(app, &request)
+    } else if given.is_some_and(|t| app.state::<PeerStore>().granted_to(t).is_some()) {
+        if peer_allowed(&request) {
+            route(app, &request)
+        } else {
+            error(403, "Not allowed for paired instances")
+        }
+    } else {
+        (401, json!({ "error": "Missing or invalid token" }))
+    };
+    tracing::debug!(method = %request.method, path = %request.path, status, "http api request");
+    write_response(stream, status, &body)
+}
+
+// 逐字节比较全部内容，耗时不随首个不同字节的位置变化
+pub(crate) fn token_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len() && given.bytes().zip(expected.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+pub(crate) fn write_response(mut stream: TcpStream, status: u16, body: &Value) -> Result<(), String> {
+    let reason = match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    let body = if status == 204 { String::new() } else { body.to_string() };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason,
+        body.len()
+    );
+    stream
+        .write_all(head.as_bytes())
+        .and_then(|_| stream.write_all(body.as_bytes()))
+        .map_err(|e| e.to_string())
+}
+
+fn to_json(value: impl Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+// 接口只用于远程关机和取消：只接受电源操作，不接受脚本、结束进程和执行前的准备步骤，
+// 持有令牌的一方不能借此以当前用户身份运行程序
+fn remote_spec(body: &[u8]) -> Result<TimerSpec, String> {
+    let spec: TimerSpec = serde_json::from_slice(body).map_err(|e| format!("Invalid timer: {}", e))?;
+    if !matches!(spec.action, Action::Power(_) | Action::Fallback { .. }) {
+        return Err("Only power actions can be scheduled through the API".into());
+    }
+    if !spec.pre_action.is_empty() {
+        return Err("Pre-action steps cannot be set through the API".into());
+    }
+    Ok(spec)
+}
+
+fn error(status: u16, message: impl Into<String>) -> (u16, Value) {
+    (status, json!({ "error": message.into() }))
+}
+
+fn timer_statuses(app: &AppHandle) -> Vec<TimerStatus> {
+    app.state::<TimerStore>().all().iter().map(ArmedTimer::status).collect()
+}
+
+fn pair(app: &AppHandle, request: &Request, address: std::net::IpAddr) -> (u16, Value) {
+    let pairing: PairRequest = match serde_json::from_slice(&request.body) {
+        Ok(pairing) => pairing,
+        Err(e) => return error(400, format!("Invalid pairing request: {}", e)),
+    };
+    match peers::accept_pairing(app, pairing, address) {
+        Ok(response) => (200, to_json(response)),
+        Err(e) => error(401, e),
+    }
+}
+
+// 配对实例的令牌只能读取状态、执行电源操作和取消计时
+fn peer_allowed(request: &Request) -> bool {
+    let path = request.path.split('?').next().unwrap_or_default().trim_end_matches('/');
+    match request.method.as_str() {
+        "GET" => path == "/status",
+        "POST" => path == "/cancel" || path.strip_prefix("/power/").is_some_and(|action| !action.contains('/')),
+        _ => false,
+    }
+}
+
+fn route(app: &AppHandle, request: &Request) -> (u16, Value) {
+    let path = request.path.split('?').next().unwrap_or_default().trim_end_matches('/');
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["status"]) => (
+            200,
+            json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "simulate": app.state::<SettingsStore>().get().simulate,
+                "timers": timer_statuses(app),
+            }),
+        ),
+        ("GET", ["timers"]) => (200, to_json(timer_statuses(app))),
+        ("POST", ["timers"]) => {
+            let spec = match remote_spec(&request.body) {
+                Ok(spec) => spec,
+                Err(e) => return error(400, e),
+            };
+            match app.state::<TimerStore>().arm(None, spec) {
+                Ok(timer) => (200, to_json(timer.status())),
+                Err(e) => error(400, e),
+            }
+        }
+        ("POST", ["cancel"]) => (200, json!({ "cancelled": app.state::<TimerStore>().cancel_all() })),
+        ("DELETE", ["timers", id]) => match app.state::<TimerStore>().cancel(id) {
+            Some(_) => (204, Value::Null),
+            None => error(404, "Timer not found"),
+        },
+        ("GET", ["processes"]) => (200, to_json(crate::get_running_apps(app.state(), app.state(), None))),
+        ("POST", ["power", action]) => {
+            let Ok(action) = serde_json::from_value::<PowerAction>(Value::String(action.to_string())) else {
+                return error(404, "Unknown power action");
+            };
+            match executor::perform(app, AuditAction::Power { action }, ActionSource::Api) {
+                Ok(()) => (200, json!({ "action": action })),
+                Err(e) => error(500, e.to_string()),
+            }
+        }
+        _ => error(404, "Not found"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(extra: Value) -> Result<TimerSpec, String> {
+        let mut body = json!({ "action": "shutdown", "trigger": { "kind": "after", "seconds": 60 } });
+        body.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        remote_spec(body.to_string().as_bytes())
+    }
+
+    #[test]
+    fn accepts_power_actions() {
+        assert!(spec(json!({})).is_ok());
+        assert!(spec(json!({ "action": "sleep" })).is_ok());
+        assert!(spec(json!({ "action": { "fallback": { "steps": [{ "action": "sleep" }, { "action": "shutdown" }] } } }))
+            .is_ok());
+    }
+
+    #[test]
+    fn rejects_scripts_and_kills() {
+        assert!(spec(json!({ "action": { "run_script": { "script": { "path": "C:\\evil.bat" } } } })).is_err());
+        assert!(spec(json!({ "action": { "kill_processes": { "names": ["explorer.exe"] } } })).is_err());
+        assert!(spec(json!({ "pre_action": { "hooks": [{ "path": "C:\\evil.bat" }] } })).is_err());
+        assert!(spec(json!({ "pre_action": { "shutdown_wsl": true } })).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_bodies() {
+        assert!(remote_spec(b"").is_err());
+        assert!(remote_spec(b"{\"action\": \"shutdown\"}").is_err());
+    }
+
+    fn request(method: &str, path: &str) -> Request {
+        Request {
+            method: method.into(),
+            path: path.into(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn peer_tokens_are_limited_to_power_and_cancel() {
+        assert!(peer_allowed(&request("POST", "/power/shutdown")));
+        assert!(peer_allowed(&request("POST", "/cancel")));
+        assert!(peer_allowed(&request("GET", "/status")));
+        assert!(!peer_allowed(&request("POST", "/timers")));
+        assert!(!peer_allowed(&request("DELETE", "/timers/1")));
+        assert!(!peer_allowed(&request("GET", "/processes")));
+        assert!(!peer_allowed(&request("GET", "/power/shutdown")));
+        assert!(!peer_allowed(&request("POST", "/power/shutdown/extra")));
+    }
+
+    fn parse(raw: &[u8]) -> Result<Request, String> {
+        read_request(&mut BufReader::new(raw))
+    }
+
+    #[test]
+    fn parses_request_with_body() {
+        let request = parse(b"POST /timers?x=1 HTTP/1.1\r\nAuthorization: Bearer abc\r\ncontent-length: 4\r\n\r\n{}{}extra").unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/timers?x=1");
+        assert_eq!(request.header("Authorization"), Some("Bearer abc"));
+        assert_eq!(request.header("Content-Length"), Some("4"));
+        assert_eq!(request.query("x"), Some("1"));
+        assert_eq!(request.body, b"{}{}");
+    }
+
+    #[test]
+    fn rejects_malformed_requests() {
+        assert!(parse(b"").is_err());
+        assert!(parse(b"GET\r\n\r\n").is_err());
+        // 头部没有以空行结束
+        assert!(parse(b"GET /status HTTP/1.1\r\nHost: x").is_err());
+        // 请求体比声明的短
+        assert!(parse(b"POST /timers HTTP/1.1\r\nContent-Length: 10\r\n\r\n{}").is_err());
+    }
+
+    #[test]
+    fn enforces_size_limits() {
+        let body = format!("POST /timers HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY + 1);
+        assert_eq!(parse(body.as_bytes()).err().as_deref(), Some("Request body too large"));
+        let header = format!("GET /status HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(MAX_HEAD as usize));
+        assert!(parse(header.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn compares_tokens_exactly() {
+        assert!(token_matches("secret", "secret"));
+        assert!(!token_matches("secreT", "secret"));
+        assert!(!token_matches("secret1", "secret"));
+        assert!(!token_matches("", "secret"));
+    }
+}
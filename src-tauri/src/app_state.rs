@@ -0,0 +1,52 @@
+use crate::api::ApiServer;
+use crate::audio::AudioMonitor;
+use crate::audit::{ActionHistory, ActionRecord};
+use crate::media::{MediaMonitor, PlaybackState};
+use crate::profiles::ProfileStore;
+use crate::rules::{Rule, RuleStore};
+use crate::settings::SettingsStore;
+use crate::timers::{ArmedTimer, TimerStatus, TimerStore};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+#[derive(Serialize)]
+pub struct WatcherStatus {
+    media: Option<PlaybackState>,
+    media_ended_at: Option<DateTime<Local>>,
+    // 启动以来从未检测到声音时为空
+    audio_silent_secs: Option<u64>,
+    api_listening: bool,
+}
+
+// 前端重新加载后据此恢复界面，不再假设自己知道后端的状态
+#[derive(Serialize)]
+pub struct AppState {
+    version: &'static str,
+    simulate: bool,
+    active_profile: Option<String>,
+    timers: Vec<TimerStatus>,
+    // 已启用、正在监视的规则
+    rules: Vec<Rule>,
+    watchers: WatcherStatus,
+    last_action: Option<ActionRecord>,
+}
+
+#[tauri::command]
+pub fn get_app_state(app: AppHandle) -> AppState {
+    let media = app.state::<MediaMonitor>();
+    AppState {
+        version: env!("CARGO_PKG_VERSION"),
+        simulate: app.state::<SettingsStore>().get().simulate,
+        active_profile: app.state::<ProfileStore>().active(),
+        timers: app.state::<TimerStore>().all().iter().map(ArmedTimer::status).collect(),
+        rules: app.state::<RuleStore>().all().into_iter().filter(|r| r.enabled).collect(),
+        watchers: WatcherStatus {
+            media: media.state(),
+            media_ended_at: media.ended_at(),
+            audio_silent_secs: app.state::<AudioMonitor>().silent_for().map(|d| d.as_secs()),
+            api_listening: app.state::<ApiServer>().is_running(),
+        },
+        last_action: app.state::<ActionHistory>().0.recent(1).into_iter().next(),
+    }
+}
@@ -0,0 +1,143 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+// 低于该峰值视为静音，避免底噪被当成播放
+const PEAK_THRESHOLD: f32 = 0.001;
+
+// 记录最近一次检测到声音播放的时间
+#[derive(Default)]
+pub struct AudioMonitor {
+    last_heard: Mutex<Option<Instant>>,
+    // 由电源操作前的静音步骤设置，唤醒后据此恢复，用户原本就静音时不设置
+    muted_for_action: AtomicBool,
+}
+
+impl AudioMonitor {
+    // 启动以来从未检测到声音时返回 None
+    pub fn silent_for(&self) -> Option<Duration> {
+        self.last_heard.lock().unwrap().map(|t| t.elapsed())
+    }
+}
+
+// 枚举默认输出设备上的音频会话，任一活动会话有音量峰值即视为正在播放
+#[cfg(windows)]
+fn audio_playing() -> Result<bool, String> {
+    use windows::core::Interface;
+    use windows::Win32::Media::Audio::Endpoints::IAudioMeterInformation;
+    use windows::Win32::Media::Audio::{
+        eMultimedia, eRender, AudioSessionStateActive, IAudioSessionManager2, IMMDeviceEnumerator,
+        MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_ALL};
+
+    unsafe {
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).map_err(|e| e.to_string())?;
+        let device = enumerator
+            .GetDefaultAudioEndpoint(eRender, eMultimedia)
+            .map_err(|e| e.to_string())?;
+        let manager: IAudioSessionManager2 = device.Activate(CLSCTX_ALL, None).map_err(|e| e.to_string())?;
+        let sessions = manager.GetSessionEnumerator().map_err(|e| e.to_string())?;
+        for i in 0..sessions.GetCount().map_err(|e| e.to_string())? {
+            let Ok(session) = sessions.GetSession(i) else {
+                continue;
+            };
+            if session.GetState().ok() != Some(AudioSessionStateActive) {
+                continue;
+            }
+            let peak = session
+                .cast::<IAudioMeterInformation>()
+                .and_then(|meter| meter.GetPeakValue())
+                .unwrap_or(0.0);
+            if peak > PEAK_THRESHOLD {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(not(windows))]
+fn audio_playing() -> Result<bool, String> {
+    Ok(false)
+}
+
+// 设置默认输出设备的静音状态，返回之前是否已静音
+#[cfg(windows)]
+fn set_mute(mute: bool) -> Result<bool, String> {
+    use windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume;
+    use windows::Win32::Media::Audio::{eMultimedia, eRender, IMMDeviceEnumerator, MMDeviceEnumerator};
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED};
+
+    unsafe {
+        // 可能在计时器或电源通知线程上调用，已初始化时忽略返回值
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).map_err(|e| e.to_string())?;
+        let device = enumerator
+            .GetDefaultAudioEndpoint(eRender, eMultimedia)
+            .map_err(|e| e.to_string())?;
+        let volume: IAudioEndpointVolume = device.Activate(CLSCTX_ALL, None).map_err(|e| e.to_string())?;
+        let was_muted = volume.GetMute().map_err(|e| e.to_string())?.as_bool();
+        volume.SetMute(mute, std::ptr::null()).map_err(|e| e.to_string())?;
+        Ok(was_muted)
+    }
+}
+
+#[cfg(not(windows))]
+fn set_mute(_mute: bool) -> Result<bool, String> {
+    Err("Muting audio is only supported on Windows".into())
+}
+
+// 电源操作前静音，避免唤醒后突然大声播放
+pub fn mute_for_action(app: &AppHandle) {
+    match set_mute(true) {
+        Ok(was_muted) => {
+            tracing::info!(was_muted, "muted audio before power action");
+            if !was_muted {
+                app.state::<AudioMonitor>().muted_for_action.store(true, Ordering::SeqCst);
+            }
+        }
+        Err(e) => tracing::warn!(error = %e, "failed to mute audio"),
+    }
+}
+
+// 唤醒或电源操作失败后恢复，只取消由 mute_for_action 设置的静音
+pub fn restore_after_action(app: &AppHandle) {
+    if !app.state::<AudioMonitor>().muted_for_action.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    match set_mute(false) {
+        Ok(_) => tracing::info!("restored audio after power action"),
+        Err(e) => tracing::warn!(error = %e, "failed to unmute audio"),
+    }
+}
+
+pub fn start_monitor(app: AppHandle) {
+    thread::spawn(move || {
+        #[cfg(windows)]
+        unsafe {
+            use windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED};
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        }
+        let monitor = app.state::<AudioMonitor>();
+        let mut logged_error = false;
+        loop {
+            match audio_playing() {
+                Ok(true) => *monitor.last_heard.lock().unwrap() = Some(Instant::now()),
+                Ok(false) => {}
+                // 没有输出设备时会一直失败，只记录一次
+                Err(e) if !logged_error => {
+                    tracing::warn!(error = %e, "failed to query audio sessions");
+                    logged_error = true;
+                }
+                Err(_) => {}
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
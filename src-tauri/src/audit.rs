@@ -0,0 +1,156 @@
+use crate::cleanup::CleanupTask;
+use crate::events::{self, AppEvent};
+use crate::history::HistoryLog;
+use crate::hooks::HookScript;
+use crate::notify::{self, Notice, NoticeKind};
+use crate::power::PowerAction;
+use crate::timers::ArmedTimer;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuditAction {
+    Power { action: PowerAction },
+    Kill { pid: u32, name: String },
+    Cleanup { task: CleanupTask },
+    // 电源操作前停止的 Docker 容器，每个容器一条记录
+    StopContainer { id: String, name: String, timeout_secs: u64 },
+    // 通过网络控制其他电脑的电源
+    RemotePower { host: String, action: PowerAction },
+    // 计时或规则选择的非电源操作
+    KillProcesses {
+        names: Vec<String>,
+        #[serde(default)]
+        tree: bool,
+    },
+    RunScript { script: HookScript },
+    SetPowerPlan { guid: String },
+    SetBrightness { percent: u8 },
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditAction::Power { action } => write!(f, "{:?}", action),
+            AuditAction::Kill { pid, name } => write!(f, "Close {} ({})", name, pid),
+            AuditAction::Cleanup { task } => write!(f, "Cleanup {:?}", task),
+            AuditAction::StopContainer { name, .. } => write!(f, "Stop container {}", name),
+            AuditAction::RemotePower { host, action } => write!(f, "{:?} {}", action, host),
+            AuditAction::KillProcesses { names, .. } => write!(f, "Close {}", names.join(", ")),
+            AuditAction::RunScript { script } => write!(f, "Run {}", script.path),
+            AuditAction::SetPowerPlan { guid } => write!(f, "Switch power plan to {}", guid),
+            AuditAction::SetBrightness { percent } => write!(f, "Set brightness to {}%", percent),
+        }
+    }
+}
+
+// 操作的发起者
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ActionSource {
+    // 用户在界面上直接操作
+    User,
+    Timer { id: String, label: String },
+    Rule { id: String, name: String },
+    Sequence { id: String, name: String },
+    // 由 test_trigger 发起的测试
+    Test,
+    // 通过本地 HTTP 接口发起
+    Api,
+    // 通过 MQTT 命令主题发起，如 Home Assistant
+    Mqtt,
+}
+
+impl fmt::Display for ActionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionSource::User => write!(f, "User"),
+            ActionSource::Timer { id, label } if label.is_empty() => write!(f, "Timer {}", id),
+            ActionSource::Timer { label, .. } => write!(f, "Timer {}", label),
+            ActionSource::Rule { name, .. } => write!(f, "Rule {}", name),
+            ActionSource::Sequence { name, .. } => write!(f, "Sequence {}", name),
+            ActionSource::Test => write!(f, "Test"),
+            ActionSource::Api => write!(f, "API"),
+            ActionSource::Mqtt => write!(f, "MQTT"),
+        }
+    }
+}
+
+impl ActionSource {
+    pub fn from_timer(timer: &ArmedTimer) -> Self {
+        match &timer.spec.rule {
+            Some(rule) => ActionSource::Rule {
+                id: rule.id.clone(),
+                name: rule.name.clone(),
+            },
+            None => ActionSource::Timer {
+                id: timer.id.clone(),
+                label: timer.spec.label.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ActionRecord {
+    pub timestamp: DateTime<Local>,
+    pub action: AuditAction,
+    pub source: ActionSource,
+    pub success: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+    // 模拟模式下只记录未执行
+    #[serde(default)]
+    pub simulated: bool,
+}
+
+pub struct ActionHistory(pub HistoryLog<ActionRecord>);
+
+impl ActionHistory {
+    pub fn open(app: &AppHandle) -> Self {
+        ActionHistory(HistoryLog::open(app, "action_history.jsonl"))
+    }
+}
+
+pub fn record(
+    app: &AppHandle,
+    action: AuditAction,
+    source: ActionSource,
+    result: &Result<(), String>,
+    simulated: bool,
+) {
+    let record = ActionRecord {
+        timestamp: Local::now(),
+        action,
+        source,
+        success: result.is_ok(),
+        error: result.as_ref().err().cloned(),
+        simulated,
+    };
+    if let Err(e) = app.state::<ActionHistory>().0.append(&record) {
+        tracing::warn!(error = %e, "failed to write action history");
+    }
+    let _ = app.emit("action-recorded", &record);
+    let (kind, event) = if record.success {
+        (NoticeKind::ActionResult, AppEvent::ActionSucceeded { record })
+    } else {
+        (NoticeKind::ActionFailed, AppEvent::ActionFailed { record })
+    };
+    let body = match &event {
+        AppEvent::ActionFailed { record } => {
+            format!("{} ({})", event.message(), record.error.as_deref().unwrap_or_default())
+        }
+        _ if simulated => format!("{} (simulated)", event.message()),
+        _ => event.message(),
+    };
+    notify::dispatch(app, Notice::new("AutoShutdown", body).with_kind(kind));
+    events::publish(app, event);
+}
+
+#[tauri::command]
+pub fn get_action_history(history: State<'_, ActionHistory>, limit: Option<usize>) -> Vec<ActionRecord> {
+    history.0.recent(limit.unwrap_or(100))
+}
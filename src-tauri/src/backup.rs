@@ -0,0 +1,64 @@
+use crate::timers;
+use serde::{Deserialize, Serialize};
+use sysinfo::System;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupTool {
+    Robocopy,
+    Macrium,
+    Veeam,
+    Restic,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct BackupPreset {
+    pub tool: BackupTool,
+    pub label: &'static str,
+    // 执行备份任务的进程，常驻的服务进程不在其中
+    pub processes: &'static [&'static str],
+    // 备份过程中持续写入的日志目录
+    pub log_dir: Option<&'static str>,
+}
+
+const PRESETS: &[BackupPreset] = &[
+    BackupPreset {
+        tool: BackupTool::Robocopy,
+        label: "Robocopy",
+        processes: &["robocopy"],
+        log_dir: None,
+    },
+    BackupPreset {
+        tool: BackupTool::Macrium,
+        label: "Macrium Reflect",
+        processes: &["ReflectBin", "Reflect"],
+        log_dir: Some(r"C:\ProgramData\Macrium\Reflect"),
+    },
+    BackupPreset {
+        tool: BackupTool::Veeam,
+        label: "Veeam Agent",
+        processes: &["VeeamAgent"],
+        log_dir: Some(r"C:\ProgramData\Veeam\Endpoint"),
+    },
+    BackupPreset {
+        tool: BackupTool::Restic,
+        label: "restic",
+        processes: &["restic"],
+        log_dir: None,
+    },
+];
+
+impl BackupTool {
+    pub fn preset(self) -> &'static BackupPreset {
+        PRESETS.iter().find(|p| p.tool == self).expect("every tool has a preset")
+    }
+
+    pub fn running(self, sys: &System) -> bool {
+        self.preset().processes.iter().any(|name| timers::process_running(sys, name))
+    }
+}
+
+#[tauri::command]
+pub fn list_backup_presets() -> Vec<BackupPreset> {
+    PRESETS.to_vec()
+}
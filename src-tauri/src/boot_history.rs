@@ -0,0 +1,109 @@
+use crate::error::AutoShutdownError;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+const DEFAULT_LIMIT: usize = 50;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerHistoryKind {
+    // 1074：由程序或用户发起的关机、重启
+    Requested,
+    // 6006：事件日志服务正常停止，说明是正常关机
+    CleanShutdown,
+    // 6008：上次关机是意外的
+    Unexpected,
+    // 41：系统未正常关机就重新启动，通常是断电、死机或蓝屏
+    PowerLoss,
+}
+
+#[cfg(windows)]
+impl PowerHistoryKind {
+    fn from_event_id(id: u32) -> Option<Self> {
+        match id {
+            1074 => Some(PowerHistoryKind::Requested),
+            6006 => Some(PowerHistoryKind::CleanShutdown),
+            6008 => Some(PowerHistoryKind::Unexpected),
+            41 => Some(PowerHistoryKind::PowerLoss),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SystemPowerEvent {
+    pub time: DateTime<Local>,
+    pub event_id: u32,
+    pub kind: PowerHistoryKind,
+    // 以下只有 1074 事件才有
+    pub process: Option<String>,
+    pub reason: Option<String>,
+    pub shutdown_type: Option<String>,
+    pub user: Option<String>,
+    pub comment: Option<String>,
+}
+
+#[cfg(windows)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RawEvent {
+    time: DateTime<Local>,
+    id: u32,
+    properties: Vec<String>,
+}
+
+#[cfg(windows)]
+impl RawEvent {
+    // 1074 的插入字符串依次为：进程、计算机、原因、原因代码、关机类型、注释、用户
+    fn into_event(self) -> Option<SystemPowerEvent> {
+        let kind = PowerHistoryKind::from_event_id(self.id)?;
+        let field = |index: usize| {
+            (kind == PowerHistoryKind::Requested)
+                .then(|| self.properties.get(index).cloned())
+                .flatten()
+                .filter(|v| !v.trim().is_empty())
+        };
+        Some(SystemPowerEvent {
+            process: field(0),
+            reason: field(2),
+            shutdown_type: field(4),
+            comment: field(5),
+            user: field(6),
+            time: self.time,
+            event_id: self.id,
+            kind,
+        })
+    }
+}
+
+// 消息文本随系统语言变化，只读取事件的插入字符串
+#[cfg(windows)]
+fn read(limit: usize) -> Result<Vec<SystemPowerEvent>, String> {
+    let script = format!(
+        "@(Get-WinEvent -FilterHashtable @{{ LogName = 'System'; Id = 1074, 6006, 6008, 41 }} -MaxEvents {} -ErrorAction SilentlyContinue | ForEach-Object {{ [pscustomobject]@{{ Time = $_.TimeCreated.ToString('o'); Id = $_.Id; Properties = @($_.Properties | ForEach-Object {{ [string]$_.Value }}) }} }}) | ConvertTo-Json -Compress -Depth 3",
+        limit
+    );
+    let out = crate::cmd::output("powershell", &["-NoProfile", "-NonInteractive", "-Command", &script])?;
+    let out = out.trim();
+    if out.is_empty() {
+        return Ok(Vec::new());
+    }
+    // 只有一项时 ConvertTo-Json 输出对象而不是数组
+    let raw: Vec<RawEvent> = if out.starts_with('[') {
+        serde_json::from_str(out).map_err(|e| e.to_string())?
+    } else {
+        vec![serde_json::from_str(out).map_err(|e| e.to_string())?]
+    };
+    Ok(raw.into_iter().filter_map(RawEvent::into_event).collect())
+}
+
+#[cfg(not(windows))]
+fn read(_limit: usize) -> Result<Vec<SystemPowerEvent>, String> {
+    Err("Reading the system event log is only supported on Windows".into())
+}
+
+// 按时间倒序返回最近的关机、重启和意外断电记录
+#[tauri::command(async)]
+pub fn get_system_power_history(limit: Option<usize>) -> Result<Vec<SystemPowerEvent>, AutoShutdownError> {
+    Ok(read(limit.unwrap_or(DEFAULT_LIMIT))?)
+}
@@ -0,0 +1,95 @@
+use crate::audit::{ActionSource, AuditAction};
+use crate::error::AutoShutdownError;
+use crate::executor;
+use tauri::AppHandle;
+
+// 笔记本内置屏幕只能通过 WMI 调节，外接显示器通过 DDC/CI
+#[cfg(windows)]
+mod imp {
+    use crate::cmd;
+    use windows::Win32::Devices::Display::{
+        DestroyPhysicalMonitors, GetMonitorBrightness, GetNumberOfPhysicalMonitorsFromHMONITOR,
+        GetPhysicalMonitorsFromHMONITOR, SetMonitorBrightness, PHYSICAL_MONITOR,
+    };
+    use windows::Win32::Foundation::{BOOL, LPARAM, RECT};
+    use windows::Win32::Graphics::Gdi::{EnumDisplayMonitors, HDC, HMONITOR};
+
+    fn set_internal(percent: u8) -> Result<(), String> {
+        let script = format!(
+            "Get-CimInstance -Namespace root/wmi -ClassName WmiMonitorBrightnessMethods -ErrorAction Stop | Invoke-CimMethod -MethodName WmiSetBrightness -Arguments @{{Timeout=0; Brightness={}}} | Out-Null",
+            percent
+        );
+        cmd::output("powershell", &["-NoProfile", "-NonInteractive", "-Command", &script]).map(|_| ())
+    }
+
+    unsafe extern "system" fn collect_monitor(monitor: HMONITOR, _: HDC, _: *mut RECT, data: LPARAM) -> BOOL {
+        let monitors = &mut *(data.0 as *mut Vec<HMONITOR>);
+        monitors.push(monitor);
+        BOOL(1)
+    }
+
+    // 返回调节成功的显示器数量，不支持 DDC/CI 的显示器跳过
+    fn set_external(percent: u8) -> usize {
+        let mut monitors: Vec<HMONITOR> = Vec::new();
+        let mut changed = 0;
+        unsafe {
+            let _ = EnumDisplayMonitors(HDC::default(), None, Some(collect_monitor), LPARAM(&mut monitors as *mut _ as isize));
+            for monitor in monitors {
+                let mut count = 0u32;
+                if GetNumberOfPhysicalMonitorsFromHMONITOR(monitor, &mut count).is_err() || count == 0 {
+                    continue;
+                }
+                let mut physical = vec![PHYSICAL_MONITOR::default(); count as usize];
+                if GetPhysicalMonitorsFromHMONITOR(monitor, &mut physical).is_err() {
+                    continue;
+                }
+                for p in &physical {
+                    let (mut min, mut current, mut max) = (0u32, 0u32, 0u32);
+                    if GetMonitorBrightness(p.hPhysicalMonitor, &mut min, &mut current, &mut max) == 0 || max <= min {
+                        continue;
+                    }
+                    // 显示器的亮度范围不一定是 0-100
+                    let value = min + (max - min) * percent as u32 / 100;
+                    if SetMonitorBrightness(p.hPhysicalMonitor, value) != 0 {
+                        changed += 1;
+                    }
+                }
+                let _ = DestroyPhysicalMonitors(&physical);
+            }
+        }
+        changed
+    }
+
+    pub fn set(percent: u8) -> Result<(), String> {
+        let internal = set_internal(percent);
+        let external = set_external(percent);
+        tracing::info!(percent, internal = internal.is_ok(), external, "set brightness");
+        match internal {
+            Ok(()) => Ok(()),
+            Err(_) if external > 0 => Ok(()),
+            Err(e) => Err(format!("No display accepted the brightness change: {}", e)),
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod imp {
+    pub fn set(_percent: u8) -> Result<(), String> {
+        Err("Brightness control is only supported on Windows".into())
+    }
+}
+
+pub fn set(percent: u8) -> Result<(), String> {
+    if percent > 100 {
+        return Err(format!("Invalid brightness {}%", percent));
+    }
+    imp::set(percent)
+}
+
+#[tauri::command(async)]
+pub fn set_brightness(app: AppHandle, percent: u8) -> Result<(), AutoShutdownError> {
+    if percent > 100 {
+        return Err(AutoShutdownError::InvalidInput(format!("Invalid brightness {}%", percent)));
+    }
+    executor::perform(&app, AuditAction::SetBrightness { percent }, ActionSource::User)
+}
@@ -0,0 +1,640 @@
+use crate::schedule::ScheduleStore;
+use chrono::{DateTime, Datelike, Days, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+// 重复事件展开到多少天之后，只需覆盖到下一次刷新
+const EXPAND_DAYS: i64 = 7;
+
+// 日历中有匹配的事件的日子跳过该日程，如“LAN party”当晚不自动关机
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct CalendarExceptions {
+    // 本地 .ics 文件路径或 http(s)/webcal 地址
+    pub source: String,
+    // 事件标题或分类包含任一关键字即匹配，不区分大小写
+    pub keywords: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+struct CalendarEvent {
+    summary: String,
+    categories: Vec<String>,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+}
+
+impl CalendarEvent {
+    fn tagged(&self, keywords: &[String]) -> bool {
+        keywords.iter().map(|k| k.trim().to_lowercase()).filter(|k| !k.is_empty()).any(|k| {
+            self.summary.to_lowercase().contains(&k) || self.categories.iter().any(|c| c.to_lowercase().contains(&k))
+        })
+    }
+
+    fn overlaps_day(&self, day: NaiveDate) -> bool {
+        let bounds = |date: NaiveDate| Local.from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap()).earliest();
+        let (Some(start), Some(end)) = (bounds(day), day.checked_add_days(Days::new(1)).and_then(bounds)) else {
+            return false;
+        };
+        self.start < end && self.end > start
+    }
+}
+
+// 按来源缓存解析后的事件，由后台线程定期刷新
+#[derive(Default)]
+pub struct CalendarCache(Mutex<HashMap<String, Vec<CalendarEvent>>>);
+
+impl CalendarCache {
+    // 日历尚未读取成功时不跳过，避免因网络问题漏掉关机
+    pub fn skips(&self, exceptions: &CalendarExceptions, now: &DateTime<Local>) -> bool {
+        let cache = self.0.lock().unwrap();
+        let Some(events) = cache.get(exceptions.source.trim()) else {
+            return false;
+        };
+        let today = now.date_naive();
+        events.iter().any(|e| e.tagged(&exceptions.keywords) && e.overlaps_day(today))
+    }
+}
+
+// 日历中的时间值：全天日期、UTC 时间、浮动的本地时间，或引用 VTIMEZONE 的时区时间
+#[derive(Clone, Debug)]
+enum TimeBase {
+    Date,
+    Utc,
+    Floating,
+    Zone(String),
+}
+
+#[derive(Clone, Debug)]
+struct IcsTime {
+    naive: NaiveDateTime,
+    base: TimeBase,
+}
+
+// DTSTART;VALUE=DATE:20261015、DTSTART:20261015T190000Z、DTSTART;TZID=...:20261015T190000
+fn parse_time(params: &str, value: &str) -> Option<IcsTime> {
+    let value = value.trim();
+    if params.contains("VALUE=DATE") || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some(IcsTime { naive: date.and_hms_opt(0, 0, 0)?, base: TimeBase::Date });
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(IcsTime { naive, base: TimeBase::Utc });
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let tzid = params.split(';').find_map(|p| p.strip_prefix("TZID="));
+    let base = match tzid {
+        Some(tzid) => TimeBase::Zone(tzid.trim_matches('"').to_string()),
+        None => TimeBase::Floating,
+    };
+    Some(IcsTime { naive, base })
+}
+
+// 换算成本地时间；不含时区数据库，TZID 只按日历自带的 VTIMEZONE 换算，找不到定义时返回 None
+fn resolve(base: &TimeBase, naive: NaiveDateTime, zones: &HashMap<String, TimeZoneDef>) -> Option<DateTime<Local>> {
+    match base {
+        TimeBase::Date | TimeBase::Floating => Local.from_local_datetime(&naive).earliest(),
+        TimeBase::Utc => Some(Utc.from_utc_datetime(&naive).with_timezone(&Local)),
+        TimeBase::Zone(tzid) => {
+            let offset = zones.get(tzid)?.offset_at(naive)?;
+            Some(offset.from_local_datetime(&naive).earliest()?.with_timezone(&Local))
+        }
+    }
+}
+
+// VTIMEZONE 中的一个时段（STANDARD 或 DAYLIGHT），从 start 起每年按 BYMONTH/BYDAY 切换到 offset
+#[derive(Clone, Debug, Default)]
+struct Observance {
+    start: Option<NaiveDateTime>,
+    offset: Option<FixedOffset>,
+    // (月份, 第几个星期几，负数从月末数起, 星期几)
+    yearly: Option<(u32, i8, Weekday)>,
+}
+
+impl Observance {
+    // 不晚于 local 的最近一次切换时间
+    fn onset_before(&self, local: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = self.start?;
+        let Some((month, nth, weekday)) = self.yearly else {
+            return (start <= local).then_some(start);
+        };
+        let onset = |year: i32| Some(nth_weekday(year, month, nth, weekday)?.and_time(start.time()));
+        let this_year = onset(local.year())?;
+        let onset = if this_year <= local { this_year } else { onset(local.year() - 1)? };
+        (onset >= start).then_some(onset)
+    }
+}
+
+fn nth_weekday(year: i32, month: u32, nth: i8, weekday: Weekday) -> Option<NaiveDate> {
+    if nth > 0 {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, nth as u8)
+    } else {
+        let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+        let last = NaiveDate::from_ymd_opt(next_year, next_month, 1)?.pred_opt()?;
+        let back = (last.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+        last.checked_sub_days(Days::new(back as u64 + 7 * (nth.unsigned_abs() as u64 - 1)))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct TimeZoneDef {
+    observances: Vec<Observance>,
+}
+
+impl TimeZoneDef {
+    fn offset_at(&self, local: NaiveDateTime) -> Option<FixedOffset> {
+        self.observances
+            .iter()
+            .filter_map(|o| Some((o.onset_before(local)?, o.offset?)))
+            .max_by_key(|(onset, _)| *onset)
+            .map(|(_, offset)| offset)
+    }
+}
+
+// +0100、-0500、+013000
+fn parse_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    let sign = match value.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits = &value[1..];
+    if !(digits.len() == 4 || digits.len() == 6) || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let field = |range: std::ops::Range<usize>| digits.get(range).map_or(Some(0), |d| d.parse::<i32>().ok());
+    let seconds = field(0..2)? * 3600 + field(2..4)? * 60 + field(4..6)?;
+    FixedOffset::east_opt(sign * seconds)
+}
+
+fn parse_weekday(code: &str) -> Option<Weekday> {
+    Some(match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+// 只支持 FREQ=DAILY/WEEKLY 及 INTERVAL、COUNT、UNTIL 和不带序号的 BYDAY
+#[derive(Clone, Debug)]
+struct RRule {
+    weekly: bool,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<IcsTime>,
+    by_day: Vec<Weekday>,
+}
+
+fn parse_rrule(value: &str) -> Option<RRule> {
+    let mut rule = RRule { weekly: false, interval: 1, count: None, until: None, by_day: Vec::new() };
+    let mut freq = None;
+    for part in value.split(';') {
+        let (key, value) = part.split_once('=')?;
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => freq = Some(value.to_ascii_uppercase()),
+            "INTERVAL" => rule.interval = value.parse().ok().filter(|i| *i > 0)?,
+            "COUNT" => rule.count = Some(value.parse().ok()?),
+            "UNTIL" => rule.until = Some(parse_time("", value)?),
+            "BYDAY" => {
+                rule.by_day = value.split(',').map(|d| parse_weekday(&d.to_ascii_uppercase())).collect::<Option<_>>()?
+            }
+            "WKST" => {}
+            // BYMONTH、BYSETPOS 等会改变发生日期，无法正确展开
+            _ => return None,
+        }
+    }
+    match freq.as_deref() {
+        Some("DAILY") => {}
+        Some("WEEKLY") => rule.weekly = true,
+        _ => return None,
+    }
+    Some(rule)
+}
+
+// 按重复规则展开的开始时间（事件时区内的时间），不超过 horizon；COUNT 包含首次发生
+fn occurrences(start: NaiveDateTime, rule: &RRule, horizon: NaiveDateTime) -> Vec<NaiveDateTime> {
+    let limit = rule.count.map_or(usize::MAX, |c| c as usize);
+    let mut found = Vec::new();
+    let mut step: u64 = 0;
+    loop {
+        let candidates: Vec<NaiveDateTime> = if rule.weekly {
+            let mut days = if rule.by_day.is_empty() { vec![start.weekday()] } else { rule.by_day.clone() };
+            days.sort_by_key(|d| d.num_days_from_monday());
+            let monday = start.date() - Days::new(start.weekday().num_days_from_monday() as u64);
+            let Some(week) = monday.checked_add_days(Days::new(7 * step * rule.interval as u64)) else {
+                return found;
+            };
+            days.iter()
+                .map(|d| (week + Days::new(d.num_days_from_monday() as u64)).and_time(start.time()))
+                .filter(|t| *t >= start)
+                .collect()
+        } else {
+            let Some(day) = start.checked_add_days(Days::new(step * rule.interval as u64)) else {
+                return found;
+            };
+            if rule.by_day.is_empty() || rule.by_day.contains(&day.weekday()) { vec![day] } else { Vec::new() }
+        };
+        for time in candidates {
+            if time > horizon || found.len() >= limit {
+                return found;
+            }
+            found.push(time);
+        }
+        step += 1;
+    }
+}
+
+#[derive(Default)]
+struct PartialEvent {
+    summary: String,
+    categories: Vec<String>,
+    start: Option<IcsTime>,
+    end: Option<IcsTime>,
+    rrule: Option<String>,
+    exdates: Vec<IcsTime>,
+}
+
+impl PartialEvent {
+    // 展开重复事件；时区无法换算的事件整体丢弃，不按错误的时间匹配
+    fn expand(self, zones: &HashMap<String, TimeZoneDef>, horizon: DateTime<Local>) -> Vec<CalendarEvent> {
+        let Some(start) = self.start else {
+            return Vec::new();
+        };
+        let Some(first) = resolve(&start.base, start.naive, zones) else {
+            tracing::warn!(summary = %self.summary, ?start.base, "calendar event skipped: unknown time zone");
+            return Vec::new();
+        };
+        // 未写 DTEND 时，全天事件持续一天，其余视为瞬时事件
+        let length = match &self.end {
+            Some(end) => match resolve(&end.base, end.naive, zones) {
+                Some(end) => end - first,
+                None => {
+                    tracing::warn!(summary = %self.summary, ?end.base, "calendar event skipped: unknown time zone");
+                    return Vec::new();
+                }
+            },
+            None if matches!(start.base, TimeBase::Date) => chrono::Duration::days(1),
+            None => chrono::Duration::zero(),
+        };
+
+        let rule = self.rrule.as_deref().and_then(|r| {
+            let rule = parse_rrule(r);
+            if rule.is_none() {
+                tracing::debug!(summary = %self.summary, rrule = r, "unsupported RRULE, using the first occurrence only");
+            }
+            rule
+        });
+        let starts = match &rule {
+            // 上限按本地时间比较即可，时区差的几个小时不影响结果
+            Some(rule) => occurrences(start.naive, rule, horizon.naive_local()),
+            None => vec![start.naive],
+        };
+        let until = rule.as_ref().and_then(|r| r.until.as_ref()).map(|u| match u.base {
+            // 只有日期的 UNTIL 包含当天
+            TimeBase::Date => (u.naive + chrono::Duration::days(1), TimeBase::Floating),
+            _ => (u.naive, u.base.clone()),
+        });
+        let until = until.and_then(|(naive, base)| resolve(&base, naive, zones));
+        let exdates: Vec<DateTime<Local>> =
+            self.exdates.iter().filter_map(|e| resolve(&e.base, e.naive, zones)).collect();
+
+        starts
+            .into_iter()
+            .filter_map(|naive| resolve(&start.base, naive, zones))
+            .filter(|time| until.is_none_or(|until| *time <= until))
+            .filter(|time| !exdates.contains(time))
+            .map(|time| CalendarEvent {
+                summary: self.summary.clone(),
+                categories: self.categories.clone(),
+                start: time,
+                end: time + length,
+            })
+            .collect()
+    }
+}
+
+// 展开重复事件到 horizon 为止；只支持按天、按周的重复，其余重复规则按首次发生处理
+fn parse_ics(text: &str, horizon: DateTime<Local>) -> Vec<CalendarEvent> {
+    // 以空格或制表符开头的行是上一行的续行
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest),
+            _ => lines.push(line.trim_end_matches('\r').to_string()),
+        }
+    }
+    let properties = lines.iter().filter_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        let (key, params) = name.split_once(';').unwrap_or((name, ""));
+        Some((key.to_ascii_uppercase(), params, value))
+    });
+
+    // VTIMEZONE 可能出现在引用它的事件之后，先读取全部时区定义
+    let mut zones: HashMap<String, TimeZoneDef> = HashMap::new();
+    let mut zone: Option<(String, TimeZoneDef)> = None;
+    let mut observance: Option<Observance> = None;
+    for (key, params, value) in properties.clone() {
+        let component = value.trim().to_ascii_uppercase();
+        match (key.as_str(), zone.as_mut(), observance.as_mut()) {
+            ("BEGIN", _, _) if component == "VTIMEZONE" => zone = Some(Default::default()),
+            ("END", Some(_), _) if component == "VTIMEZONE" => {
+                let (tzid, def) = zone.take().unwrap();
+                zones.insert(tzid, def);
+            }
+            ("BEGIN", Some(_), _) if component == "STANDARD" || component == "DAYLIGHT" => {
+                observance = Some(Default::default())
+            }
+            ("END", Some((_, def)), Some(_)) if component == "STANDARD" || component == "DAYLIGHT" => {
+                def.observances.push(observance.take().unwrap())
+            }
+            ("TZID", Some((tzid, _)), None) => *tzid = value.trim().to_string(),
+            ("DTSTART", Some(_), Some(o)) => o.start = parse_time(params, value).map(|t| t.naive),
+            ("TZOFFSETTO", Some(_), Some(o)) => o.offset = parse_offset(value),
+            ("RRULE", Some(_), Some(o)) => o.yearly = parse_yearly(value),
+            _ => {}
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<PartialEvent> = None;
+    for (key, params, value) in properties {
+        match (key.as_str(), current.as_mut()) {
+            ("BEGIN", _) if value.eq_ignore_ascii_case("VEVENT") => current = Some(Default::default()),
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                events.extend(current.take().unwrap().expand(&zones, horizon));
+            }
+            ("SUMMARY", Some(event)) => event.summary = value.replace("\\,", ",").replace("\\;", ";"),
+            ("CATEGORIES", Some(event)) => event.categories.extend(value.split(',').map(|c| c.trim().to_string())),
+            ("DTSTART", Some(event)) => event.start = parse_time(params, value),
+            ("DTEND", Some(event)) => event.end = parse_time(params, value),
+            ("RRULE", Some(event)) => event.rrule = Some(value.trim().to_string()),
+            ("EXDATE", Some(event)) => event.exdates.extend(value.split(',').filter_map(|v| parse_time(params, v))),
+            _ => {}
+        }
+    }
+    events
+}
+
+// 时区切换规则，如 FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU（三月最后一个周日）
+fn parse_yearly(value: &str) -> Option<(u32, i8, Weekday)> {
+    let mut month = None;
+    let mut day = None;
+    for part in value.split(';') {
+        match part.split_once('=')? {
+            ("FREQ", freq) if freq.eq_ignore_ascii_case("YEARLY") => {}
+            ("FREQ", _) => return None,
+            ("BYMONTH", m) => month = m.parse().ok(),
+            ("BYDAY", d) => {
+                let (nth, code) = d.split_at(d.len().checked_sub(2)?);
+                let nth: i8 = nth.trim_start_matches('+').parse().ok().filter(|n: &i8| *n != 0 && n.abs() <= 5)?;
+                day = Some((nth, parse_weekday(&code.to_ascii_uppercase())?));
+            }
+            _ => {}
+        }
+    }
+    let (nth, weekday) = day?;
+    Some((month?, nth, weekday))
+}
+
+fn fetch(source: &str) -> Result<String, String> {
+    let url = match source.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None => source.to_string(),
+    };
+    if url.starts_with("http://") || url.starts_with("https://") {
+        ureq::get(&url)
+            .timeout(Duration::from_secs(30))
+            .call()
+            .map_err(|e| e.to_string())?
+            .into_string()
+            .map_err(|e| e.to_string())
+    } else {
+        std::fs::read_to_string(source).map_err(|e| e.to_string())
+    }
+}
+
+pub fn refresh(app: &AppHandle) {
+    let sources: HashSet<String> = app
+        .state::<ScheduleStore>()
+        .all()
+        .into_iter()
+        .filter(|s| s.enabled)
+        .filter_map(|s| s.calendar_exceptions)
+        .map(|c| c.source.trim().to_string())
+        .filter(|source| !source.is_empty())
+        .collect();
+    let cache = app.state::<CalendarCache>();
+    for source in &sources {
+        // 读取失败时保留上一次的结果
+        match fetch(source) {
+            Ok(text) => {
+                let events = parse_ics(&text, Local::now() + chrono::Duration::days(EXPAND_DAYS));
+                tracing::debug!(source, events = events.len(), "calendar refreshed");
+                cache.0.lock().unwrap().insert(source.clone(), events);
+            }
+            Err(e) => tracing::warn!(source, error = %e, "failed to read calendar"),
+        }
+    }
+    cache.0.lock().unwrap().retain(|source, _| sources.contains(source));
+}
+
+pub fn start_refresher(app: AppHandle) {
+    thread::spawn(move || loop {
+        refresh(&app);
+        thread::sleep(REFRESH_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn horizon() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 12, 31, 0, 0, 0).unwrap()
+    }
+
+    fn calendar(body: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n", body)
+    }
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Local> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap().with_timezone(&Local)
+    }
+
+    fn local(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    const EUROPE: &str = "BEGIN:VTIMEZONE\r
+TZID:W. Europe Standard Time\r
+BEGIN:STANDARD\r
+DTSTART:16010101T030000\r
+TZOFFSETFROM:+0200\r
+TZOFFSETTO:+0100\r
+RRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=10\r
+END:STANDARD\r
+BEGIN:DAYLIGHT\r
+DTSTART:16010101T020000\r
+TZOFFSETFROM:+0100\r
+TZOFFSETTO:+0200\r
+RRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=3\r
+END:DAYLIGHT\r
+END:VTIMEZONE\r
+";
+
+    #[test]
+    fn single_utc_event_with_folded_summary() {
+        let events = parse_ics(
+            &calendar(
+                "BEGIN:VEVENT\r\nSUMMARY:LAN\r\n  party\\, all night\r\nCATEGORIES:Games,Friends\r\n\
+                 DTSTART:20261015T190000Z\r\nDTEND:20261015T230000Z\r\nEND:VEVENT\r\n",
+            ),
+            horizon(),
+        );
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary, "LAN party, all night");
+        assert_eq!(events[0].categories, ["Games", "Friends"]);
+        assert_eq!(events[0].start, utc(2026, 10, 15, 19, 0));
+        assert_eq!(events[0].end, utc(2026, 10, 15, 23, 0));
+    }
+
+    #[test]
+    fn all_day_event_lasts_one_day() {
+        let events = parse_ics(
+            &calendar("BEGIN:VEVENT\r\nSUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20261015\r\nEND:VEVENT\r\n"),
+            horizon(),
+        );
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].start, local(2026, 10, 15, 0, 0));
+        assert_eq!(events[0].end, local(2026, 10, 16, 0, 0));
+        assert!(events[0].overlaps_day(NaiveDate::from_ymd_opt(2026, 10, 15).unwrap()));
+        assert!(!events[0].overlaps_day(NaiveDate::from_ymd_opt(2026, 10, 16).unwrap()));
+    }
+
+    #[test]
+    fn daily_with_count() {
+        let events = parse_ics(
+            &calendar(
+                "BEGIN:VEVENT\r\nSUMMARY:Render\r\nDTSTART:20261005T220000\r\nDTEND:20261005T230000\r\n\
+                 RRULE:FREQ=DAILY;COUNT=3\r\nEND:VEVENT\r\n",
+            ),
+            horizon(),
+        );
+        let starts: Vec<_> = events.iter().map(|e| e.start).collect();
+        assert_eq!(starts, [local(2026, 10, 5, 22, 0), local(2026, 10, 6, 22, 0), local(2026, 10, 7, 22, 0)]);
+        assert!(events.iter().all(|e| e.end - e.start == chrono::Duration::hours(1)));
+    }
+
+    #[test]
+    fn weekly_by_day_until() {
+        // 2026-10-05 是周一，UNTIL 只有日期时包含当天
+        let events = parse_ics(
+            &calendar(
+                "BEGIN:VEVENT\r\nSUMMARY:Raid\r\nDTSTART:20261005T200000\r\n\
+                 RRULE:FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20261019\r\nEND:VEVENT\r\n",
+            ),
+            horizon(),
+        );
+        let starts: Vec<_> = events.iter().map(|e| e.start).collect();
+        assert_eq!(
+            starts,
+            [
+                local(2026, 10, 5, 20, 0),
+                local(2026, 10, 7, 20, 0),
+                local(2026, 10, 12, 20, 0),
+                local(2026, 10, 14, 20, 0),
+                local(2026, 10, 19, 20, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn weekly_interval_and_exdate() {
+        let events = parse_ics(
+            &calendar(
+                "BEGIN:VEVENT\r\nSUMMARY:Backup\r\nDTSTART:20261007T010000Z\r\n\
+                 RRULE:FREQ=WEEKLY;INTERVAL=2;COUNT=4\r\nEXDATE:20261021T010000Z\r\nEND:VEVENT\r\n",
+            ),
+            horizon(),
+        );
+        let starts: Vec<_> = events.iter().map(|e| e.start).collect();
+        assert_eq!(starts, [utc(2026, 10, 7, 1, 0), utc(2026, 11, 4, 1, 0), utc(2026, 11, 18, 1, 0)]);
+    }
+
+    #[test]
+    fn open_ended_rule_stops_at_horizon() {
+        let events = parse_ics(
+            &calendar("BEGIN:VEVENT\r\nSUMMARY:Nightly\r\nDTSTART:20261201T230000\r\nRRULE:FREQ=DAILY\r\nEND:VEVENT\r\n"),
+            horizon(),
+        );
+        assert_eq!(events.len(), 30);
+        assert_eq!(events.last().unwrap().start, local(2026, 12, 30, 23, 0));
+    }
+
+    #[test]
+    fn unsupported_rule_uses_first_occurrence() {
+        let events = parse_ics(
+            &calendar(
+                "BEGIN:VEVENT\r\nSUMMARY:Monthly\r\nDTSTART:20261001T200000\r\n\
+                 RRULE:FREQ=MONTHLY;BYMONTHDAY=1\r\nEND:VEVENT\r\n",
+            ),
+            horizon(),
+        );
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].start, local(2026, 10, 1, 20, 0));
+    }
+
+    #[test]
+    fn tzid_uses_vtimezone() {
+        let body = format!(
+            "BEGIN:VEVENT\r\nSUMMARY:Summer\r\nDTSTART;TZID=W. Europe Standard Time:20260701T190000\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nSUMMARY:Winter\r\nDTSTART;TZID=\"W. Europe Standard Time\":20261215T190000\r\nEND:VEVENT\r\n{}",
+            EUROPE
+        );
+        let events = parse_ics(&calendar(&body), horizon());
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].start, utc(2026, 7, 1, 17, 0));
+        assert_eq!(events[1].start, utc(2026, 12, 15, 18, 0));
+    }
+
+    #[test]
+    fn recurring_tzid_crosses_dst_change() {
+        // 2026 年欧洲夏令时在 10 月 25 日结束，当地时间不变，UTC 时间推后一小时
+        let body = format!(
+            "{}BEGIN:VEVENT\r\nSUMMARY:Weekly\r\nDTSTART;TZID=W. Europe Standard Time:20261018T200000\r\n\
+             RRULE:FREQ=WEEKLY;COUNT=2\r\nEND:VEVENT\r\n",
+            EUROPE
+        );
+        let starts: Vec<_> = parse_ics(&calendar(&body), horizon()).iter().map(|e| e.start).collect();
+        assert_eq!(starts, [utc(2026, 10, 18, 18, 0), utc(2026, 10, 25, 19, 0)]);
+    }
+
+    #[test]
+    fn unknown_tzid_is_rejected() {
+        let events = parse_ics(
+            &calendar("BEGIN:VEVENT\r\nSUMMARY:Elsewhere\r\nDTSTART;TZID=Mars/Olympus:20261015T190000\r\nEND:VEVENT\r\n"),
+            horizon(),
+        );
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn nth_weekday_from_either_end() {
+        assert_eq!(nth_weekday(2026, 3, -1, Weekday::Sun), NaiveDate::from_ymd_opt(2026, 3, 29));
+        assert_eq!(nth_weekday(2026, 10, -1, Weekday::Sun), NaiveDate::from_ymd_opt(2026, 10, 25));
+        assert_eq!(nth_weekday(2026, 3, 2, Weekday::Sun), NaiveDate::from_ymd_opt(2026, 3, 8));
+        assert_eq!(nth_weekday(2026, 12, -1, Weekday::Thu), NaiveDate::from_ymd_opt(2026, 12, 31));
+    }
+}
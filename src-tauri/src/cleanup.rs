@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CleanupTask {
+    EmptyRecycleBin,
+    ClearTemp,
+    FlushCaches,
+}
+
+// 关机前可选的清理步骤，按规则单独勾选
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CleanupOptions {
+    pub empty_recycle_bin: bool,
+    pub clear_temp: bool,
+    pub flush_caches: bool,
+}
+
+impl CleanupOptions {
+    pub fn tasks(&self) -> Vec<CleanupTask> {
+        let mut tasks = Vec::new();
+        if self.empty_recycle_bin {
+            tasks.push(CleanupTask::EmptyRecycleBin);
+        }
+        if self.clear_temp {
+            tasks.push(CleanupTask::ClearTemp);
+        }
+        // 放在最后，确保前面删除文件的改动也被写入磁盘
+        if self.flush_caches {
+            tasks.push(CleanupTask::FlushCaches);
+        }
+        tasks
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks().is_empty()
+    }
+}
+
+impl CleanupTask {
+    pub fn run(self) -> Result<(), String> {
+        match self {
+            CleanupTask::EmptyRecycleBin => empty_recycle_bin(),
+            CleanupTask::ClearTemp => clear_temp(),
+            CleanupTask::FlushCaches => flush_caches(),
+        }
+    }
+}
+
+// 正在被占用的文件会删除失败，跳过即可，不算作错误
+fn clear_temp() -> Result<(), String> {
+    let dir = std::env::temp_dir();
+    let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    let (mut removed, mut skipped) = (0, 0);
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let result = match entry.file_type() {
+            Ok(t) if t.is_dir() => fs::remove_dir_all(&path),
+            _ => fs::remove_file(&path),
+        };
+        match result {
+            Ok(()) => removed += 1,
+            Err(_) => skipped += 1,
+        }
+    }
+    tracing::info!(dir = %dir.display(), removed, skipped, "cleared temp directory");
+    Ok(())
+}
+
+#[cfg(windows)]
+fn empty_recycle_bin() -> Result<(), String> {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::{
+        SHEmptyRecycleBinW, SHQueryRecycleBinW, SHERB_NOCONFIRMATION, SHERB_NOPROGRESSUI, SHERB_NOSOUND,
+        SHQUERYRBINFO,
+    };
+
+    unsafe {
+        let mut info = SHQUERYRBINFO {
+            cbSize: std::mem::size_of::<SHQUERYRBINFO>() as u32,
+            ..Default::default()
+        };
+        // 回收站为空时 SHEmptyRecycleBinW 会返回错误，先查询一次
+        if SHQueryRecycleBinW(PCWSTR::null(), &mut info).is_ok() && info.i64NumItems == 0 {
+            return Ok(());
+        }
+        SHEmptyRecycleBinW(
+            HWND::default(),
+            PCWSTR::null(),
+            SHERB_NOCONFIRMATION | SHERB_NOPROGRESSUI | SHERB_NOSOUND,
+        )
+        .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(not(windows))]
+fn empty_recycle_bin() -> Result<(), String> {
+    Err("Emptying the Recycle Bin is only supported on Windows".into())
+}
+
+// 刷新所有本地固定磁盘的写缓存，打开卷句柄需要管理员权限
+#[cfg(windows)]
+fn flush_caches() -> Result<(), String> {
+    use windows::core::HSTRING;
+    use windows::Win32::Foundation::{CloseHandle, GENERIC_READ, GENERIC_WRITE};
+    use windows::Win32::Storage::FileSystem::{
+        CreateFileW, FlushFileBuffers, GetDriveTypeW, GetLogicalDrives, FILE_FLAGS_AND_ATTRIBUTES,
+        FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+    };
+    use windows::Win32::System::WindowsProgramming::DRIVE_FIXED;
+
+    let mask = unsafe { GetLogicalDrives() };
+    let mut failed = Vec::new();
+    for letter in (0..26u8).filter(|i| mask & (1 << i) != 0).map(|i| (b'A' + i) as char) {
+        if unsafe { GetDriveTypeW(&HSTRING::from(format!("{}:\\", letter))) } != DRIVE_FIXED {
+            continue;
+        }
+        let result = unsafe {
+            CreateFileW(
+                &HSTRING::from(format!("\\\\.\\{}:", letter)),
+                (GENERIC_READ | GENERIC_WRITE).0,
+                FILE_SHARE_READ | FILE_SHARE_WRITE,
+                None,
+                OPEN_EXISTING,
+                FILE_FLAGS_AND_ATTRIBUTES(0),
+                None,
+            )
+            .and_then(|handle| {
+                let flushed = FlushFileBuffers(handle);
+                let _ = CloseHandle(handle);
+                flushed
+            })
+        };
+        if let Err(e) = result {
+            tracing::warn!(drive = %letter, error = %e, "failed to flush volume");
+            failed.push(format!("{}: {}", letter, e.message()));
+        }
+    }
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Failed to flush {}", failed.join(", ")))
+    }
+}
+
+#[cfg(not(windows))]
+fn flush_caches() -> Result<(), String> {
+    Err("Flushing write caches is only supported on Windows".into())
+}
@@ -0,0 +1,30 @@
+use crate::timers;
+use std::thread;
+use std::time::Duration;
+use sysinfo::{ProcessesToUpdate, System};
+
+const SYNC_CLIENTS: &[&str] = &["OneDrive", "Dropbox"];
+const SAMPLE_WINDOW: Duration = Duration::from_secs(3);
+// 采样期间同步客户端的磁盘读写量或 CPU 超过阈值时视为正在同步
+const IO_THRESHOLD: u64 = 512 * 1024;
+const CPU_THRESHOLD: f32 = 3.0;
+
+// 两家都没有公开的本地状态接口，根据客户端进程的磁盘读写和 CPU 判断
+pub fn syncing() -> bool {
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::All, true);
+    let is_client = |name: &str| SYNC_CLIENTS.iter().any(|c| timers::name_matches(name, c));
+    if !sys.processes().values().any(|p| is_client(&p.name().to_string_lossy())) {
+        return false;
+    }
+
+    thread::sleep(SAMPLE_WINDOW);
+    sys.refresh_processes(ProcessesToUpdate::All, true);
+    sys.processes()
+        .values()
+        .filter(|p| is_client(&p.name().to_string_lossy()))
+        .any(|p| {
+            let io = p.disk_usage();
+            io.read_bytes + io.written_bytes > IO_THRESHOLD || p.cpu_usage() > CPU_THRESHOLD
+        })
+}
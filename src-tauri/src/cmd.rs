@@ -0,0 +1,29 @@
+use std::process::Command;
+
+// 从界面程序调用控制台工具时不弹出命令行窗口
+pub fn hidden(program: &str) -> Command {
+    #[allow(unused_mut)]
+    let mut command = Command::new(program);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
+
+// 运行命令并返回标准输出，退出码非零时返回标准错误（为空时退回标准输出）
+pub fn output(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = hidden(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if output.status.success() {
+        Ok(stdout)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(if stderr.is_empty() { stdout.trim().to_string() } else { stderr })
+    }
+}
@@ -0,0 +1,64 @@
+use crate::error::AutoShutdownError;
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+use tauri::State;
+
+// 等待用户在界面上确认的请求，值为 None 表示尚未回应
+#[derive(Default)]
+pub struct Confirmations {
+    pending: Mutex<HashMap<String, Option<bool>>>,
+    answered: Condvar,
+}
+
+impl Confirmations {
+    pub fn request(&self) -> String {
+        let id = uuid::Uuid::new_v4().to_string();
+        self.pending.lock().unwrap().insert(id.clone(), None);
+        id
+    }
+
+    // 不等待，已回应时取出回应
+    pub fn take(&self, id: &str) -> Option<bool> {
+        let mut pending = self.pending.lock().unwrap();
+        let answer = (*pending.get(id)?)?;
+        pending.remove(id);
+        Some(answer)
+    }
+
+    // 不再需要回应的请求，之后的回应返回 NotFound
+    pub fn dismiss(&self, id: &str) {
+        self.pending.lock().unwrap().remove(id);
+    }
+
+    // 超时未回应返回 None，请求随之失效
+    pub fn wait(&self, id: &str, timeout: Duration) -> Option<bool> {
+        let deadline = Instant::now() + timeout;
+        let mut pending = self.pending.lock().unwrap();
+        loop {
+            if let Some(Some(answer)) = pending.get(id) {
+                let answer = *answer;
+                pending.remove(id);
+                return Some(answer);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                pending.remove(id);
+                return None;
+            }
+            pending = self.answered.wait_timeout(pending, deadline - now).unwrap().0;
+        }
+    }
+}
+
+#[tauri::command]
+pub fn respond_confirmation(store: State<'_, Confirmations>, id: String, proceed: bool) -> Result<(), AutoShutdownError> {
+    let mut pending = store.pending.lock().unwrap();
+    // 请求已超时或已被回应
+    let answer = pending
+        .get_mut(&id)
+        .ok_or_else(|| AutoShutdownError::NotFound(format!("Confirmation {} is no longer pending", id)))?;
+    *answer = Some(proceed);
+    store.answered.notify_all();
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use crate::actions::Action;
+use crate::error::AutoShutdownError;
+use crate::timers::{ArmedTimer, TimerMode, TimerSpec, TimerStore, TimerTrigger};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+// 界面上的“系统定时任务”是计时器列表中固定 id 的一项
+const COUNTDOWN_ID: &str = "countdown";
+
+#[derive(Serialize, Clone)]
+pub struct CountdownStatus {
+    action: Action,
+    mode: TimerMode,
+    deadline: DateTime<Local>,
+    remaining_secs: i64,
+}
+
+fn status(timer: &ArmedTimer) -> Option<CountdownStatus> {
+    let deadline = timer.deadline()?;
+    Some(CountdownStatus {
+        action: timer.spec.action.clone(),
+        mode: timer.mode,
+        deadline,
+        remaining_secs: (deadline - Local::now()).num_seconds().max(0),
+    })
+}
+
+pub fn emit_tick(app: &AppHandle, store: &TimerStore) {
+    let current = store.get(COUNTDOWN_ID).and_then(|t| status(&t));
+    let _ = app.emit("countdown-tick", current);
+}
+
+#[tauri::command]
+pub fn start_countdown(
+    app: AppHandle,
+    store: State<'_, TimerStore>,
+    action: Action,
+    seconds: u64,
+    os_fallback: bool,
+    // 到期前多少秒提醒，不传时默认提醒一次
+    warning_secs: Option<Vec<i64>>,
+) -> Result<CountdownStatus, AutoShutdownError> {
+    let spec = TimerSpec {
+        os_fallback,
+        warning_secs: warning_secs.unwrap_or_default(),
+        ..TimerSpec::new(String::new(), action, TimerTrigger::After { seconds })
+    };
+    let timer = store.arm(Some(COUNTDOWN_ID.to_string()), spec)?;
+    emit_tick(&app, &store);
+    status(&timer).ok_or_else(|| "Countdown has no deadline".to_string()).map_err(Into::into)
+}
+
+#[tauri::command]
+pub fn cancel_countdown(app: AppHandle, store: State<'_, TimerStore>) -> bool {
+    let cancelled = store.cancel(COUNTDOWN_ID).is_some();
+    emit_tick(&app, &store);
+    cancelled
+}
+
+#[tauri::command]
+pub fn get_countdown_status(store: State<'_, TimerStore>) -> Option<CountdownStatus> {
+    store.get(COUNTDOWN_ID).and_then(|t| status(&t))
+}
@@ -0,0 +1,237 @@
+use crate::audio::AudioMonitor;
+use crate::cloudsync;
+use crate::maintenance;
+use crate::printing;
+use crate::stats;
+use crate::users;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteSessionPolicy {
+    #[default]
+    Ignore,
+    Defer,
+    // 直接取消本次操作
+    Block,
+}
+
+// 条件满足时推迟执行，并按间隔重新检查
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct DeferOptions {
+    pub while_gaming: bool,
+    // 最近 N 分钟内播放过声音时推迟
+    pub quiet_audio_minutes: Option<u64>,
+    // 演示模式或专注助手开启时推迟
+    pub respect_do_not_disturb: bool,
+    pub remote_sessions: RemoteSessionPolicy,
+    // OneDrive、Dropbox 同步完成前推迟
+    pub wait_for_cloud_sync: bool,
+    // 打印队列清空前推迟
+    pub wait_for_print_jobs: bool,
+    // 安装程序、磁盘检查或碎片整理运行期间推迟，避免中途关机损坏安装或文件系统
+    pub wait_for_installers: bool,
+    // 已用内存降到该百分比以下才执行，说明大任务已经释放了内存
+    pub memory_below_percent: Option<u8>,
+    pub recheck_secs: u64,
+}
+
+impl Default for DeferOptions {
+    fn default() -> Self {
+        DeferOptions {
+            while_gaming: false,
+            quiet_audio_minutes: None,
+            respect_do_not_disturb: false,
+            remote_sessions: RemoteSessionPolicy::default(),
+            wait_for_cloud_sync: false,
+            wait_for_print_jobs: false,
+            wait_for_installers: false,
+            memory_below_percent: None,
+            recheck_secs: 60,
+        }
+    }
+}
+
+impl DeferOptions {
+    pub fn is_empty(&self) -> bool {
+        *self == DeferOptions::default()
+    }
+
+    // 返回推迟的原因，None 表示可以执行
+    pub fn blocking_reason(&self, app: &AppHandle) -> Option<DeferReason> {
+        if self.while_gaming && fullscreen_app_running() {
+            return Some(DeferReason::Gaming);
+        }
+        if let Some(minutes) = self.quiet_audio_minutes {
+            let silent_for = app.state::<AudioMonitor>().silent_for();
+            if silent_for.is_some_and(|d| d < Duration::from_secs(minutes * 60)) {
+                return Some(DeferReason::AudioPlaying);
+            }
+        }
+        if self.remote_sessions == RemoteSessionPolicy::Defer && remote_session_active() {
+            return Some(DeferReason::RemoteSession);
+        }
+        if self.wait_for_cloud_sync && cloudsync::syncing() {
+            return Some(DeferReason::CloudSyncing);
+        }
+        if self.wait_for_print_jobs && printing::pending_jobs() > 0 {
+            return Some(DeferReason::Printing);
+        }
+        if self.wait_for_installers {
+            if let Some(installer) = maintenance::running_installer() {
+                tracing::debug!(installer, "installer running");
+                return Some(DeferReason::Installing);
+            }
+            if maintenance::disk_maintenance_running() {
+                return Some(DeferReason::DiskMaintenance);
+            }
+        }
+        if let Some(percent) = self.memory_below_percent {
+            if stats::memory_used_percent() >= percent as f64 {
+                return Some(DeferReason::MemoryInUse);
+            }
+        }
+        if self.respect_do_not_disturb {
+            if presenting() {
+                return Some(DeferReason::Presenting);
+            }
+            if focus_assist_on() {
+                return Some(DeferReason::DoNotDisturb);
+            }
+        }
+        None
+    }
+
+    // 返回取消的原因，优先于推迟检查
+    pub fn cancel_reason(&self) -> Option<DeferReason> {
+        if self.remote_sessions == RemoteSessionPolicy::Block && remote_session_active() {
+            return Some(DeferReason::RemoteSession);
+        }
+        None
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeferReason {
+    Gaming,
+    AudioPlaying,
+    Presenting,
+    DoNotDisturb,
+    RemoteSession,
+    CloudSyncing,
+    Printing,
+    Installing,
+    DiskMaintenance,
+    MemoryInUse,
+}
+
+// 本应用所在会话之外有活动的远程桌面连接
+fn remote_session_active() -> bool {
+    users::list_sessions().iter().any(|s| s.remote && s.active && !s.current)
+}
+
+// 独占全屏由 SHQueryUserNotificationState 报告，无边框全屏则比较前台窗口与所在显示器的范围
+#[cfg(windows)]
+fn fullscreen_app_running() -> bool {
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST};
+    use windows::Win32::UI::Shell::{SHQueryUserNotificationState, QUNS_BUSY, QUNS_RUNNING_D3D_FULL_SCREEN};
+    use windows::Win32::UI::WindowsAndMessaging::{GetDesktopWindow, GetForegroundWindow, GetShellWindow, GetWindowRect};
+
+    unsafe {
+        if let Ok(state) = SHQueryUserNotificationState() {
+            if state == QUNS_RUNNING_D3D_FULL_SCREEN || state == QUNS_BUSY {
+                return true;
+            }
+        }
+
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() || hwnd == GetDesktopWindow() || hwnd == GetShellWindow() {
+            return false;
+        }
+        // 点击桌面时前台是铺满屏幕的桌面窗口
+        if matches!(crate::window_class(hwnd.0 as isize).as_str(), "WorkerW" | "Progman") {
+            return false;
+        }
+        let mut rect = RECT::default();
+        if GetWindowRect(hwnd, &mut rect).is_err() {
+            return false;
+        }
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoW(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST), &mut info).as_bool() {
+            return false;
+        }
+        let monitor = info.rcMonitor;
+        rect.left <= monitor.left
+            && rect.top <= monitor.top
+            && rect.right >= monitor.right
+            && rect.bottom >= monitor.bottom
+    }
+}
+
+#[cfg(not(windows))]
+fn fullscreen_app_running() -> bool {
+    false
+}
+
+#[cfg(windows)]
+fn presenting() -> bool {
+    use windows::Win32::UI::Shell::{SHQueryUserNotificationState, QUNS_PRESENTATION_MODE, QUNS_QUIET_TIME};
+
+    unsafe {
+        SHQueryUserNotificationState()
+            .is_ok_and(|state| state == QUNS_PRESENTATION_MODE || state == QUNS_QUIET_TIME)
+    }
+}
+
+#[cfg(not(windows))]
+fn presenting() -> bool {
+    false
+}
+
+// 专注助手没有公开的 API，只能读取 Shell 发布的 WNF 状态：0 关闭，1 仅优先通知，2 仅闹钟
+#[cfg(windows)]
+fn focus_assist_on() -> bool {
+    use std::ffi::c_void;
+
+    const WNF_SHEL_QUIET_MOMENT_SHELL_MODE_CHANGED: u64 = 0x0D83_063E_A3BF_1C75;
+
+    #[link(name = "ntdll")]
+    extern "system" {
+        fn NtQueryWnfStateData(
+            state_name: *const u64,
+            type_id: *const c_void,
+            explicit_scope: *const c_void,
+            change_stamp: *mut u32,
+            buffer: *mut c_void,
+            buffer_size: *mut u32,
+        ) -> i32;
+    }
+
+    let mut stamp = 0u32;
+    let mut mode = 0u32;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let status = unsafe {
+        NtQueryWnfStateData(
+            &WNF_SHEL_QUIET_MOMENT_SHELL_MODE_CHANGED,
+            std::ptr::null(),
+            std::ptr::null(),
+            &mut stamp,
+            &mut mode as *mut u32 as *mut c_void,
+            &mut size,
+        )
+    };
+    status >= 0 && size > 0 && mode != 0
+}
+
+#[cfg(not(windows))]
+fn focus_assist_on() -> bool {
+    false
+}
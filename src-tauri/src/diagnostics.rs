@@ -0,0 +1,99 @@
+use crate::api::ApiServer;
+use crate::error::AutoShutdownError;
+use crate::logging;
+use crate::power;
+use crate::process_events::ProcessEvents;
+use crate::rules::{self, RuleStore};
+use crate::schedule::ScheduleStore;
+use crate::settings::SettingsStore;
+use crate::storage;
+use crate::timers::TimerStore;
+use crate::{elevation, uptime, winupdate};
+use chrono::Local;
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use sysinfo::System;
+use tauri::{AppHandle, Manager};
+
+// 只附带最近几天的日志，避免压缩包过大
+const LOG_DAYS: usize = 3;
+
+fn write_json(dir: &Path, file: &str, value: &impl Serialize) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    fs::write(dir.join(file), json).map_err(|e| format!("Failed to write {}: {}", file, e))
+}
+
+fn system_info(app: &AppHandle) -> serde_json::Value {
+    let capabilities = match power::capabilities() {
+        Ok(capabilities) => json!(capabilities),
+        Err(e) => json!({ "error": e }),
+    };
+    json!({
+        "app_version": app.package_info().version.to_string(),
+        "os": System::long_os_version(),
+        "kernel": System::kernel_version(),
+        "arch": std::env::consts::ARCH,
+        "elevated": elevation::is_elevated(),
+        "uptime_secs": uptime::uptime_secs(),
+        "power_capabilities": capabilities,
+        "pending_reboot": winupdate::pending_reboot(),
+    })
+}
+
+// 后台监视和计时的当前状态
+fn watcher_status(app: &AppHandle) -> serde_json::Value {
+    let process_events = app.state::<ProcessEvents>();
+    let rules = app.state::<RuleStore>().all();
+    json!({
+        "process_events": { "live": process_events.live(), "exits": process_events.exits() },
+        "api_server_running": app.state::<ApiServer>().is_running(),
+        "timers": app.state::<TimerStore>().all(),
+        "rule_conflicts": rules::find_conflicts(&rules),
+    })
+}
+
+// Windows 10 起自带的 tar（bsdtar）按扩展名生成 zip
+fn compress(dir: &Path, zip: &Path) -> Result<(), String> {
+    let (dir, zip) = (dir.to_string_lossy(), zip.to_string_lossy());
+    crate::cmd::output("tar", &["-a", "-c", "-f", &zip, "-C", &dir, "."]).map(|_| ())
+}
+
+fn collect(app: &AppHandle, dir: &Path) -> Result<(), String> {
+    // 设置中的令牌和密码与导出设置时一样去掉；Webhook 地址的路径中常带有密钥，只保留主机名
+    let mut settings = app.state::<SettingsStore>().get().without_secrets();
+    for webhook in &mut settings.webhooks {
+        webhook.url = format!("{}/...", webhook.url.split('/').take(3).collect::<Vec<_>>().join("/"));
+    }
+    write_json(dir, "settings.json", &settings)?;
+    write_json(dir, "rules.json", &app.state::<RuleStore>().all())?;
+    write_json(dir, "schedules.json", &app.state::<ScheduleStore>().all())?;
+    write_json(dir, "system.json", &system_info(app))?;
+    write_json(dir, "status.json", &watcher_status(app))?;
+
+    let logs = dir.join("logs");
+    fs::create_dir_all(&logs).map_err(|e| e.to_string())?;
+    for file in logging::log_files()?.into_iter().take(LOG_DAYS) {
+        if let Some(name) = file.file_name() {
+            fs::copy(&file, logs.join(name)).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+// 在应用数据目录下生成诊断压缩包，返回其路径，供用户附在问题反馈中
+#[tauri::command(async)]
+pub fn create_diagnostics_bundle(app: AppHandle) -> Result<String, AutoShutdownError> {
+    let name = format!("autoshutdown-diagnostics-{}", Local::now().format("%Y%m%d-%H%M%S"));
+    let root = storage::data_path(&app, "diagnostics");
+    let staging = root.join(&name);
+    let zip = root.join(format!("{}.zip", name));
+    fs::create_dir_all(&staging).map_err(|e| e.to_string())?;
+
+    let result = collect(&app, &staging).and_then(|_| compress(&staging, &zip));
+    let _ = fs::remove_dir_all(&staging);
+    result?;
+    tracing::info!(path = %zip.display(), "diagnostics bundle created");
+    Ok(zip.to_string_lossy().to_string())
+}
@@ -0,0 +1,65 @@
+use crate::cmd;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+// 电源操作前断开的网络连接，部分公司 VPN 在睡眠中途断线后无法恢复
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct NetworkDisconnectOptions {
+    // rasdial 中显示的 VPN 连接名称
+    pub vpn_connections: Vec<String>,
+    // WireGuard 隧道名称，对应 WireGuardTunnel$名称 服务
+    pub wireguard_tunnels: Vec<String>,
+    // 要禁用的网络适配器名称，需要管理员权限
+    pub adapters: Vec<String>,
+}
+
+// 本次禁用的适配器，唤醒或操作失败后重新启用
+static DISABLED_ADAPTERS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+impl NetworkDisconnectOptions {
+    pub fn is_empty(&self) -> bool {
+        self.vpn_connections.is_empty() && self.wireguard_tunnels.is_empty() && self.adapters.is_empty()
+    }
+}
+
+fn set_adapter(name: &str, enabled: bool) -> Result<(), String> {
+    let admin = if enabled { "admin=enabled" } else { "admin=disabled" };
+    cmd::output("netsh", &["interface", "set", "interface", &format!("name={}", name), admin]).map(|_| ())
+}
+
+// 断开失败不取消电源操作，只记录日志
+pub fn run(options: &NetworkDisconnectOptions) {
+    for name in &options.vpn_connections {
+        match cmd::output("rasdial", &[name, "/disconnect"]) {
+            Ok(_) => tracing::info!(name, "disconnected vpn"),
+            Err(e) => tracing::warn!(name, error = %e, "failed to disconnect vpn"),
+        }
+    }
+    for name in &options.wireguard_tunnels {
+        match cmd::output("sc", &["stop", &format!("WireGuardTunnel${}", name)]) {
+            Ok(_) => tracing::info!(name, "stopped wireguard tunnel"),
+            Err(e) => tracing::warn!(name, error = %e, "failed to stop wireguard tunnel"),
+        }
+    }
+    for name in &options.adapters {
+        match set_adapter(name, false) {
+            Ok(()) => {
+                tracing::info!(name, "disabled network adapter");
+                DISABLED_ADAPTERS.lock().unwrap().push(name.clone());
+            }
+            Err(e) => tracing::warn!(name, error = %e, "failed to disable network adapter"),
+        }
+    }
+}
+
+// VPN 由用户或客户端自行重连，这里只恢复被禁用的适配器
+pub fn restore() {
+    let adapters = std::mem::take(&mut *DISABLED_ADAPTERS.lock().unwrap());
+    for name in adapters {
+        match set_adapter(&name, true) {
+            Ok(()) => tracing::info!(name, "re-enabled network adapter"),
+            Err(e) => tracing::warn!(name, error = %e, "failed to re-enable network adapter"),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::notify::Notice;
+use crate::settings::SettingsStore;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct DiscordConfig {
+    pub webhook_url: String,
+    // 覆盖 Webhook 在频道中显示的名字，为空时使用 Discord 中的设置
+    pub username: String,
+}
+
+// Webhook 地址本身就是凭据，不写入日志
+impl fmt::Debug for DiscordConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiscordConfig")
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+pub fn send(config: &DiscordConfig, notice: &Notice) -> Result<(), String> {
+    let mut body = json!({ "content": format!("**{}**\n{}", notice.title, notice.body) });
+    if !config.username.is_empty() {
+        body["username"] = json!(config.username);
+    }
+    ureq::post(&config.webhook_url)
+        .timeout(Duration::from_secs(10))
+        .send_json(body)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+// 在后台线程发送，避免阻塞通知的调用方
+pub fn forward(app: &AppHandle, notice: &Notice) {
+    let Some(config) = app.state::<SettingsStore>().get().discord else {
+        return;
+    };
+    if config.webhook_url.is_empty() {
+        return;
+    }
+    let notice = notice.clone();
+    thread::spawn(move || {
+        if let Err(e) = send(&config, &notice) {
+            tracing::warn!(error = %e, "failed to post discord notification");
+        }
+    });
+}
@@ -0,0 +1,123 @@
+use crate::audit::{ActionSource, AuditAction};
+use crate::executor;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use tauri::AppHandle;
+
+// 电源操作前停止正在运行的容器，让容器内的服务有机会正常退出
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct DockerStopOptions {
+    pub enabled: bool,
+    // 超时后 Docker 强制结束容器，与 docker stop -t 相同
+    pub timeout_secs: u64,
+    // 有容器停止失败时取消本次操作
+    pub abort_on_failure: bool,
+}
+
+impl Default for DockerStopOptions {
+    fn default() -> Self {
+        DockerStopOptions {
+            enabled: false,
+            timeout_secs: 10,
+            abort_on_failure: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct Container {
+    id: String,
+    names: Vec<String>,
+}
+
+impl Container {
+    // 名称带有前导斜杠，如 "/postgres"
+    fn name(&self) -> String {
+        self.names
+            .first()
+            .map(|n| n.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| self.id.chars().take(12).collect())
+    }
+}
+
+#[cfg(windows)]
+fn connect() -> Result<std::fs::File, String> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(r"\\.\pipe\docker_engine")
+        .map_err(|e| format!("Docker is not running: {}", e))
+}
+
+#[cfg(not(windows))]
+fn connect() -> Result<std::os::unix::net::UnixStream, String> {
+    std::os::unix::net::UnixStream::connect("/var/run/docker.sock").map_err(|e| format!("Docker is not running: {}", e))
+}
+
+// 使用 HTTP/1.0 请求 Engine API，响应不分块并在结束后关闭连接，读到末尾即可
+fn request(method: &str, path: &str) -> Result<(u16, String), String> {
+    let mut stream = connect()?;
+    let request = format!("{} {} HTTP/1.0\r\nHost: docker\r\nContent-Length: 0\r\n\r\n", method, path);
+    stream.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).map_err(|e| e.to_string())?;
+    let response = String::from_utf8_lossy(&response);
+    let (head, body) = response.split_once("\r\n\r\n").unwrap_or((&response, ""));
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .ok_or("Invalid response from Docker")?;
+    Ok((status, body.to_string()))
+}
+
+fn running() -> Result<Vec<Container>, String> {
+    let (status, body) = request("GET", "/containers/json")?;
+    if status != 200 {
+        return Err(format!("Docker returned {}: {}", status, body.trim()));
+    }
+    serde_json::from_str(&body).map_err(|e| e.to_string())
+}
+
+// 304 表示容器已经停止
+pub fn stop(id: &str, timeout_secs: u64) -> Result<(), String> {
+    let (status, body) = request("POST", &format!("/containers/{}/stop?t={}", id, timeout_secs))?;
+    match status {
+        204 | 304 => Ok(()),
+        _ => Err(format!("Docker returned {}: {}", status, body.trim())),
+    }
+}
+
+// 逐个停止并记录到操作历史，需要取消操作时返回 Err，其余返回停止失败的容器名
+pub fn stop_all(app: &AppHandle, options: &DockerStopOptions, source: &ActionSource) -> Result<Vec<String>, String> {
+    if !options.enabled {
+        return Ok(Vec::new());
+    }
+    // 未安装或未启动 Docker 时没有需要停止的容器
+    let containers = match running() {
+        Ok(containers) => containers,
+        Err(e) => {
+            tracing::debug!(error = %e, "skipping docker containers");
+            return Ok(Vec::new());
+        }
+    };
+    let mut failed = Vec::new();
+    for container in containers {
+        let name = container.name();
+        let action = AuditAction::StopContainer {
+            id: container.id,
+            name: name.clone(),
+            timeout_secs: options.timeout_secs,
+        };
+        if let Err(e) = executor::perform_with(app, action, source.clone(), false) {
+            tracing::warn!(name, error = %e, "failed to stop container");
+            if options.abort_on_failure {
+                return Err(format!("Failed to stop container {}: {}", name, e));
+            }
+            failed.push(name);
+        }
+    }
+    Ok(failed)
+}
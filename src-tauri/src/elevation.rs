@@ -0,0 +1,134 @@
+use crate::error::AutoShutdownError;
+use tauri::AppHandle;
+
+// 读取进程令牌的提升状态，无权打开令牌时返回 None
+#[cfg(windows)]
+fn token_elevated(process: windows::Win32::Foundation::HANDLE) -> Option<bool> {
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY};
+    use windows::Win32::System::Threading::OpenProcessToken;
+
+    unsafe {
+        let mut token = HANDLE::default();
+        OpenProcessToken(process, TOKEN_QUERY, &mut token).ok()?;
+        let mut elevation = TOKEN_ELEVATION::default();
+        let mut size = 0u32;
+        let result = GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(&mut elevation as *mut _ as *mut _),
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut size,
+        );
+        let _ = CloseHandle(token);
+        result.ok().map(|_| elevation.TokenIsElevated != 0)
+    }
+}
+
+// 结束提升权限的进程、shutdown /fw、powercfg /requests 等需要管理员权限
+#[cfg(windows)]
+pub fn running_elevated() -> bool {
+    use windows::Win32::System::Threading::GetCurrentProcess;
+
+    token_elevated(unsafe { GetCurrentProcess() }).unwrap_or(false)
+}
+
+// 普通权限下打不开提升进程的令牌，这种情况视为已提升
+#[cfg(windows)]
+pub fn process_elevated(pid: u32) -> bool {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        let Ok(process) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) else {
+            return false;
+        };
+        let elevated = token_elevated(process).unwrap_or(true);
+        let _ = CloseHandle(process);
+        elevated
+    }
+}
+
+#[cfg(not(windows))]
+pub fn running_elevated() -> bool {
+    false
+}
+
+#[cfg(not(windows))]
+pub fn process_elevated(_pid: u32) -> bool {
+    false
+}
+
+// 通过 UAC 以管理员身份运行 taskkill，用户拒绝时返回错误
+#[cfg(windows)]
+pub fn kill_elevated(pid: u32) -> Result<(), AutoShutdownError> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_HIDE;
+
+    let result = unsafe {
+        ShellExecuteW(
+            HWND::default(),
+            &HSTRING::from("runas"),
+            &HSTRING::from("taskkill"),
+            &HSTRING::from(format!("/PID {} /F", pid)),
+            PCWSTR::null(),
+            SW_HIDE,
+        )
+    };
+    if result.0 as usize <= 32 {
+        return Err(AutoShutdownError::NeedsAdmin("Elevation was cancelled or failed".into()));
+    }
+    tracing::info!(pid, "kill process via elevated taskkill");
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn kill_elevated(_pid: u32) -> Result<(), AutoShutdownError> {
+    Err(AutoShutdownError::Unsupported("Elevated kill is only supported on Windows".into()))
+}
+
+#[tauri::command]
+pub fn is_elevated() -> bool {
+    running_elevated()
+}
+
+// 以管理员身份启动新实例后退出当前实例，用户在 UAC 中拒绝时返回错误并继续运行
+#[cfg(windows)]
+#[tauri::command]
+pub fn relaunch_elevated(app: AppHandle) -> Result<(), AutoShutdownError> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    if running_elevated() {
+        return Ok(());
+    }
+    let exe = std::env::current_exe().map_err(|e| AutoShutdownError::Failed(e.to_string()))?;
+    let args: Vec<String> = std::env::args().skip(1).map(|a| format!("\"{}\"", a)).collect();
+    let result = unsafe {
+        ShellExecuteW(
+            HWND::default(),
+            &HSTRING::from("runas"),
+            &HSTRING::from(exe.as_os_str()),
+            &HSTRING::from(args.join(" ")),
+            PCWSTR::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    // 返回值不大于 32 表示失败，包括用户取消 UAC
+    if result.0 as usize <= 32 {
+        return Err(AutoShutdownError::NeedsAdmin("Elevation was cancelled or failed".into()));
+    }
+    tracing::info!("relaunched as administrator, exiting");
+    app.exit(0);
+    Ok(())
+}
+
+#[cfg(not(windows))]
+#[tauri::command]
+pub fn relaunch_elevated(_app: AppHandle) -> Result<(), AutoShutdownError> {
+    Err(AutoShutdownError::Unsupported("Relaunching as administrator is only supported on Windows".into()))
+}
@@ -0,0 +1,354 @@
+use crate::error::AutoShutdownError;
+use crate::notify::Notice;
+use crate::settings::SettingsStore;
+use base64::Engine;
+use chrono::Local;
+use native_tls::TlsConnector;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const IO_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    // 连接即 TLS，通常是 465 端口
+    Tls,
+    // 明文连接后升级，通常是 587 端口
+    StartTls,
+    None,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct EmailConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    // 为空时不登录
+    pub username: String,
+    pub password: String,
+    // 不加密的连接默认不登录，需用户明确允许明文发送密码
+    pub allow_plaintext_auth: bool,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        EmailConfig {
+            host: String::new(),
+            port: 587,
+            security: SmtpSecurity::StartTls,
+            username: String::new(),
+            password: String::new(),
+            allow_plaintext_auth: false,
+            from: String::new(),
+            to: Vec::new(),
+        }
+    }
+}
+
+// 设置会写入日志，不输出密码
+impl fmt::Debug for EmailConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EmailConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("security", &self.security)
+            .field("username", &self.username)
+            .field("allow_plaintext_auth", &self.allow_plaintext_auth)
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .finish_non_exhaustive()
+    }
+}
+
+// 地址和标题会原样写入 SMTP 命令和邮件头，换行会被当作新的命令或头部
+fn single_line(field: &str, value: &str) -> Result<(), String> {
+    if value.contains(['\r', '\n']) {
+        return Err(format!("Email {} must not contain line breaks", field));
+    }
+    Ok(())
+}
+
+impl EmailConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        single_line("sender", &self.from)?;
+        for to in &self.to {
+            single_line("recipient", to)?;
+        }
+        single_line("username", &self.username)?;
+        if self.security == SmtpSecurity::None && !self.username.is_empty() && !self.allow_plaintext_auth {
+            return Err("Logging in without TLS sends the password in cleartext; use TLS or STARTTLS".into());
+        }
+        Ok(())
+    }
+}
+
+// EHLO 回复中本模块关心的扩展
+#[derive(Default, Debug, PartialEq)]
+struct Extensions {
+    starttls: bool,
+    auth: Vec<String>,
+}
+
+impl Extensions {
+    // 第一行是问候，其余每行一个扩展；旧服务器会写成 AUTH=LOGIN
+    fn parse(reply: &str) -> Self {
+        let mut extensions = Extensions::default();
+        for line in reply.lines().skip(1) {
+            let mut words = line.split([' ', '=']).filter(|w| !w.is_empty()).map(str::to_ascii_uppercase);
+            match words.next().as_deref() {
+                Some("STARTTLS") => extensions.starttls = true,
+                Some("AUTH") => extensions.auth.extend(words),
+                _ => {}
+            }
+        }
+        extensions
+    }
+
+    fn supports(&self, mechanism: &str) -> bool {
+        self.auth.iter().any(|m| m == mechanism)
+    }
+}
+
+// 只实现发送一封邮件需要的 SMTP 命令
+struct Session<S: Read + Write> {
+    stream: BufReader<S>,
+}
+
+impl<S: Read + Write> Session<S> {
+    fn new(stream: S) -> Self {
+        Session {
+            stream: BufReader::new(stream),
+        }
+    }
+
+    // 多行回复以 "250-" 续行，"250 " 结束，返回的文本每行对应一行回复
+    fn reply(&mut self) -> Result<(u16, String), String> {
+        let mut text = String::new();
+        loop {
+            let mut line = String::new();
+            if self.stream.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+                return Err("SMTP server closed the connection".into());
+            }
+            let code = line.get(..3).and_then(|c| c.parse().ok()).ok_or("Malformed SMTP reply")?;
+            text.push_str(line.get(4..).unwrap_or_default().trim_end());
+            text.push('\n');
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return Ok((code, text.trim_end().to_string()));
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: u16) -> Result<String, String> {
+        let (code, text) = self.reply()?;
+        if code != expected {
+            return Err(format!("SMTP error {}: {}", code, text));
+        }
+        Ok(text)
+    }
+
+    fn command(&mut self, line: &str, expected: u16) -> Result<String, String> {
+        let stream = self.stream.get_mut();
+        stream
+            .write_all(format!("{}\r\n", line).as_bytes())
+            .and_then(|_| stream.flush())
+            .map_err(|e| e.to_string())?;
+        self.expect(expected)
+    }
+
+    fn ehlo(&mut self) -> Result<Extensions, String> {
+        self.command("EHLO localhost", 250).map(|text| Extensions::parse(&text))
+    }
+
+    fn login(&mut self, config: &EmailConfig, extensions: &Extensions) -> Result<(), String> {
+        let encode = |value: &str| base64::engine::general_purpose::STANDARD.encode(value);
+        if extensions.supports("PLAIN") {
+            let credentials = format!("\0{}\0{}", config.username, config.password);
+            self.command(&format!("AUTH PLAIN {}", encode(&credentials)), 235)?;
+        } else if extensions.supports("LOGIN") {
+            self.command("AUTH LOGIN", 334)?;
+            self.command(&encode(&config.username), 334)?;
+            self.command(&encode(&config.password), 235)?;
+        } else {
+            return Err("SMTP server does not offer PLAIN or LOGIN authentication".into());
+        }
+        Ok(())
+    }
+
+    fn send_mail(&mut self, config: &EmailConfig, extensions: &Extensions, message: &str) -> Result<(), String> {
+        if !config.username.is_empty() {
+            self.login(config, extensions)?;
+        }
+        self.command(&format!("MAIL FROM:<{}>", config.from), 250)?;
+        for to in &config.to {
+            self.command(&format!("RCPT TO:<{}>", to), 250)?;
+        }
+        self.command("DATA", 354)?;
+        self.command(&format!("{}.", dot_stuff(message)), 250)?;
+        let _ = self.command("QUIT", 221);
+        Ok(())
+    }
+}
+
+// 以 "." 开头的行需要再加一个 "."，否则会被当作结束标记；换行统一为 CRLF
+fn dot_stuff(message: &str) -> String {
+    message
+        .lines()
+        .map(|line| if line.starts_with('.') { format!(".{}\r\n", line) } else { format!("{}\r\n", line) })
+        .collect()
+}
+
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        value.to_string()
+    } else {
+        format!("=?UTF-8?B?{}?=", base64::engine::general_purpose::STANDARD.encode(value))
+    }
+}
+
+fn compose(config: &EmailConfig, subject: &str, body: &str) -> String {
+    format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n{}",
+        config.from,
+        config.to.join(", "),
+        encode_header(subject),
+        Local::now().to_rfc2822(),
+        body
+    )
+}
+
+pub fn send(config: &EmailConfig, subject: &str, body: &str) -> Result<(), String> {
+    if config.host.is_empty() || config.from.is_empty() || config.to.is_empty() {
+        return Err("Email needs a server, a sender and at least one recipient".into());
+    }
+    config.validate()?;
+    single_line("subject", subject)?;
+    let message = compose(config, subject, body);
+    let stream = TcpStream::connect((config.host.as_str(), config.port))
+        .map_err(|e| format!("Failed to connect to {}:{}: {}", config.host, config.port, e))?;
+    stream.set_read_timeout(Some(IO_TIMEOUT)).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(IO_TIMEOUT)).map_err(|e| e.to_string())?;
+    let connector = TlsConnector::new().map_err(|e| e.to_string())?;
+
+    match config.security {
+        SmtpSecurity::Tls => {
+            let tls = connector.connect(&config.host, stream).map_err(|e| e.to_string())?;
+            let mut session = Session::new(tls);
+            session.expect(220)?;
+            let extensions = session.ehlo()?;
+            session.send_mail(config, &extensions, &message)
+        }
+        SmtpSecurity::StartTls => {
+            let mut session = Session::new(stream);
+            session.expect(220)?;
+            if !session.ehlo()?.starttls {
+                return Err("SMTP server does not support STARTTLS".into());
+            }
+            session.command("STARTTLS", 220)?;
+            let tls = connector
+                .connect(&config.host, session.stream.into_inner())
+                .map_err(|e| e.to_string())?;
+            let mut session = Session::new(tls);
+            // 升级后需重新 EHLO，很多服务器只在加密后才列出 AUTH
+            let extensions = session.ehlo()?;
+            session.send_mail(config, &extensions, &message)
+        }
+        SmtpSecurity::None => {
+            let mut session = Session::new(stream);
+            session.expect(220)?;
+            let extensions = session.ehlo()?;
+            session.send_mail(config, &extensions, &message)
+        }
+    }
+}
+
+// 在后台线程发送，SMTP 往返较慢
+pub fn forward(app: &AppHandle, notice: &Notice) {
+    let Some(config) = app.state::<SettingsStore>().get().email else {
+        return;
+    };
+    if config.host.is_empty() {
+        return;
+    }
+    let notice = notice.clone();
+    thread::spawn(move || {
+        if let Err(e) = send(&config, &notice.title, &notice.body) {
+            tracing::warn!(error = %e, "failed to send email notification");
+        }
+    });
+}
+
+// 用界面上尚未保存的配置发送测试邮件
+#[tauri::command(async)]
+pub fn send_test_email(config: EmailConfig) -> Result<(), AutoShutdownError> {
+    send(
+        &config,
+        "AutoShutdown test email",
+        "Email notifications are working.",
+    )
+    .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(extra: serde_json::Value) -> EmailConfig {
+        let mut config = serde_json::json!({ "host": "smtp.example.com", "from": "pc@example.com", "to": ["me@example.com"] });
+        config.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(config).unwrap()
+    }
+
+    #[test]
+    fn rejects_line_breaks_in_addresses() {
+        assert!(config(serde_json::json!({})).validate().is_ok());
+        assert!(config(serde_json::json!({ "from": "pc@example.com\r\nBcc: x@evil.com" })).validate().is_err());
+        assert!(config(serde_json::json!({ "to": ["me@example.com", "x@evil.com>\r\nRCPT TO:<y@evil.com"] })).validate().is_err());
+        assert!(config(serde_json::json!({ "username": "me\nQUIT" })).validate().is_err());
+        assert!(single_line("subject", "Shutdown in 5 minutes").is_ok());
+        assert!(single_line("subject", "Shutdown\r\nBcc: x@evil.com").is_err());
+    }
+
+    #[test]
+    fn refuses_cleartext_login_unless_allowed() {
+        let plain = serde_json::json!({ "security": "none", "username": "me", "password": "secret" });
+        assert!(config(plain.clone()).validate().is_err());
+        let mut allowed = plain.clone();
+        allowed["allow_plaintext_auth"] = true.into();
+        assert!(config(allowed).validate().is_ok());
+        // 不登录时不涉及密码
+        assert!(config(serde_json::json!({ "security": "none" })).validate().is_ok());
+        assert!(config(serde_json::json!({ "security": "start_tls", "username": "me" })).validate().is_ok());
+    }
+
+    #[test]
+    fn parses_ehlo_extensions() {
+        let extensions = Extensions::parse("smtp.example.com Hello\nSIZE 35882577\nSTARTTLS\nAUTH LOGIN plain XOAUTH2\n");
+        assert!(extensions.starttls);
+        assert!(extensions.supports("PLAIN"));
+        assert!(extensions.supports("LOGIN"));
+        assert!(!extensions.supports("CRAM-MD5"));
+
+        let old = Extensions::parse("smtp.example.com\nAUTH=LOGIN\n8BITMIME");
+        assert!(!old.starttls);
+        assert_eq!(old.auth, vec!["LOGIN"]);
+        // 问候行不是扩展
+        assert_eq!(Extensions::parse("STARTTLS ready"), Extensions::default());
+    }
+
+    #[test]
+    fn dot_stuffs_message_lines() {
+        assert_eq!(dot_stuff("Subject: x\r\n\r\nbody"), "Subject: x\r\n\r\nbody\r\n");
+        assert_eq!(dot_stuff(".\n..twice\nnot.leading"), "..\r\n...twice\r\nnot.leading\r\n");
+        // 单独的 "." 行不会提前结束 DATA
+        assert!(!dot_stuff("a\n.\nb").contains("\r\n.\r\n"));
+    }
+}
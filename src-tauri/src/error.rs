@@ -0,0 +1,119 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+// 所有命令统一返回的错误，前端按 kind 显示对应的提示和操作
+#[derive(Debug, Error)]
+pub enum AutoShutdownError {
+    #[error("Administrator privileges are required: {0}")]
+    NeedsAdmin(String),
+    #[error("Not supported on this system: {0}")]
+    Unsupported(String),
+    // 手机端不结束进程
+    #[cfg_attr(mobile, allow(dead_code))]
+    #[error("Process {pid} is no longer running")]
+    ProcessGone { pid: u32 },
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("{0}")]
+    Failed(String),
+}
+
+impl AutoShutdownError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AutoShutdownError::NeedsAdmin(_) => "needs_admin",
+            AutoShutdownError::Unsupported(_) => "unsupported",
+            AutoShutdownError::ProcessGone { .. } => "process_gone",
+            AutoShutdownError::NotFound(_) => "not_found",
+            AutoShutdownError::InvalidInput(_) => "invalid_input",
+            AutoShutdownError::Failed(_) => "failed",
+        }
+    }
+}
+
+// 内部函数仍返回字符串错误，无法判断类别时一律视为失败；需要具体类别的由来源直接返回对应变体
+impl From<String> for AutoShutdownError {
+    fn from(message: String) -> Self {
+        AutoShutdownError::Failed(message)
+    }
+}
+
+// 启动程序等系统调用的错误按 io 错误类别归类
+impl From<std::io::Error> for AutoShutdownError {
+    fn from(e: std::io::Error) -> Self {
+        let message = e.to_string();
+        match e.kind() {
+            std::io::ErrorKind::PermissionDenied => AutoShutdownError::NeedsAdmin(message),
+            std::io::ErrorKind::Unsupported => AutoShutdownError::Unsupported(message),
+            std::io::ErrorKind::NotFound => AutoShutdownError::NotFound(message),
+            std::io::ErrorKind::InvalidInput => AutoShutdownError::InvalidInput(message),
+            _ => AutoShutdownError::Failed(message),
+        }
+    }
+}
+
+impl From<&str> for AutoShutdownError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+// 序列化为 { kind, message }
+impl Serialize for AutoShutdownError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AutoShutdownError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Error, ErrorKind};
+
+    fn serialized(error: AutoShutdownError) -> serde_json::Value {
+        serde_json::to_value(error).unwrap()
+    }
+
+    #[test]
+    fn serializes_each_kind() {
+        let cases = [
+            (AutoShutdownError::NeedsAdmin("x".into()), "needs_admin"),
+            (AutoShutdownError::Unsupported("x".into()), "unsupported"),
+            (AutoShutdownError::ProcessGone { pid: 42 }, "process_gone"),
+            (AutoShutdownError::NotFound("x".into()), "not_found"),
+            (AutoShutdownError::InvalidInput("x".into()), "invalid_input"),
+            (AutoShutdownError::Failed("x".into()), "failed"),
+        ];
+        for (error, kind) in cases {
+            assert_eq!(serialized(error)["kind"], kind);
+        }
+        assert_eq!(
+            serialized(AutoShutdownError::ProcessGone { pid: 42 })["message"],
+            "Process 42 is no longer running"
+        );
+    }
+
+    #[test]
+    fn strings_are_never_guessed() {
+        for message in ["Access is denied", "Timer 1 not found", "Invalid time", "Sleep is only supported on Windows"] {
+            assert_eq!(AutoShutdownError::from(message).kind(), "failed");
+        }
+        assert_eq!(serialized("Access is denied".into())["message"], "Access is denied");
+    }
+
+    #[test]
+    fn maps_io_errors_by_kind() {
+        let kind = |kind: ErrorKind| AutoShutdownError::from(Error::from(kind)).kind();
+        assert_eq!(kind(ErrorKind::PermissionDenied), "needs_admin");
+        assert_eq!(kind(ErrorKind::Unsupported), "unsupported");
+        assert_eq!(kind(ErrorKind::NotFound), "not_found");
+        assert_eq!(kind(ErrorKind::InvalidInput), "invalid_input");
+        assert_eq!(kind(ErrorKind::TimedOut), "failed");
+    }
+}
@@ -0,0 +1,68 @@
+use crate::audit::ActionRecord;
+use crate::notify::Notice;
+use crate::timers::ArmedTimer;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use tauri::AppHandle;
+
+// 对外广播的应用事件，供 Webhook 等外部集成订阅
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AppEvent {
+    CountdownStarted { timer: ArmedTimer },
+    CountdownCancelled { timer: ArmedTimer },
+    CountdownFinished { timer: ArmedTimer },
+    ActionSucceeded { record: ActionRecord },
+    ActionFailed { record: ActionRecord },
+    Notification { notice: Notice },
+}
+
+impl AppEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppEvent::CountdownStarted { .. } => "countdown_started",
+            AppEvent::CountdownCancelled { .. } => "countdown_cancelled",
+            AppEvent::CountdownFinished { .. } => "countdown_finished",
+            AppEvent::ActionSucceeded { .. } => "action_succeeded",
+            AppEvent::ActionFailed { .. } => "action_failed",
+            AppEvent::Notification { .. } => "notification",
+        }
+    }
+
+    // 简短的可读描述，用于消息模板
+    pub fn message(&self) -> String {
+        let timer_label = |t: &ArmedTimer| {
+            if t.spec.label.is_empty() {
+                t.spec.action.to_string()
+            } else {
+                format!("{} ({})", t.spec.label, t.spec.action)
+            }
+        };
+        match self {
+            AppEvent::CountdownStarted { timer } => format!("Timer started: {}", timer_label(timer)),
+            AppEvent::CountdownCancelled { timer } => format!("Timer cancelled: {}", timer_label(timer)),
+            AppEvent::CountdownFinished { timer } => format!("Timer finished: {}", timer_label(timer)),
+            AppEvent::ActionSucceeded { record } => format!("Action succeeded: {:?}", record.action),
+            AppEvent::ActionFailed { record } => format!("Action failed: {:?}", record.action),
+            AppEvent::Notification { notice } => format!("{}: {}", notice.title, notice.body),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Envelope {
+    pub timestamp: DateTime<Local>,
+    pub message: String,
+    #[serde(flatten)]
+    pub event: AppEvent,
+}
+
+pub fn publish(app: &AppHandle, event: AppEvent) {
+    tracing::debug!(event = event.name(), "publish event");
+    let envelope = Envelope {
+        timestamp: Local::now(),
+        message: event.message(),
+        event,
+    };
+    crate::webhooks::dispatch(app, &envelope);
+}
@@ -0,0 +1,265 @@
+use crate::actions::{Action, FallbackStep};
+use crate::audio;
+use crate::audit::{self, ActionSource, AuditAction};
+use crate::brightness;
+use crate::disconnect;
+use crate::docker;
+use crate::error::AutoShutdownError;
+use crate::hooks;
+use crate::jobs;
+use crate::power::PowerAction;
+use crate::powerplan;
+use crate::pre_action::{self, PreActionOptions};
+use crate::relaunch;
+use crate::remote;
+use crate::session;
+use crate::settings::SettingsStore;
+use crate::timers;
+use crate::SharedSystem;
+use chrono::Local;
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use sysinfo::Pid;
+use tauri::{AppHandle, Emitter, Manager};
+
+// 正在执行的操作数及最近一次结束的时间，进程看护据此区分本应用关闭的程序和意外退出
+static ACTING: AtomicUsize = AtomicUsize::new(0);
+static LAST_ACTED: Mutex<Option<Instant>> = Mutex::new(None);
+
+struct Acting;
+
+impl Acting {
+    fn start() -> Self {
+        ACTING.fetch_add(1, Ordering::SeqCst);
+        Acting
+    }
+}
+
+impl Drop for Acting {
+    fn drop(&mut self) {
+        *LAST_ACTED.lock().unwrap() = Some(Instant::now());
+        ACTING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// 有操作正在执行，或在 window 之内刚结束
+pub fn acted_within(window: Duration) -> bool {
+    ACTING.load(Ordering::SeqCst) > 0 || LAST_ACTED.lock().unwrap().is_some_and(|t| t.elapsed() < window)
+}
+
+// 回退链中每一步的结果
+#[derive(Serialize, Clone)]
+struct FallbackAttempt<'a> {
+    source: &'a ActionSource,
+    // 从 0 开始的步骤序号
+    step: usize,
+    action: PowerAction,
+    error: Option<&'a str>,
+    // 失败后是否还有下一步可以尝试
+    falling_back: bool,
+}
+
+#[derive(Serialize, Clone)]
+struct WouldHaveExecuted<'a> {
+    action: &'a AuditAction,
+    source: &'a ActionSource,
+}
+
+// 复用共享的进程列表，只刷新目标进程
+fn kill(app: &AppHandle, pid: u32) -> Result<(), AutoShutdownError> {
+    let shared = app.state::<SharedSystem>();
+    let sys = shared.refresh(&[Pid::from_u32(pid)]);
+    let process = sys.process(Pid::from_u32(pid)).ok_or(AutoShutdownError::ProcessGone { pid })?;
+    if process.kill() {
+        Ok(())
+    } else {
+        Err(AutoShutdownError::Failed("Kill signal failed".into()))
+    }
+}
+
+// 结束所有同名进程，tree 为 true 时连同它们的子进程一起结束，没有运行时视为成功
+fn kill_all(app: &AppHandle, names: &[String], tree: bool) -> Result<(), String> {
+    let shared = app.state::<SharedSystem>();
+    let sys = shared.refresh_all();
+    let matches = |p: &sysinfo::Process| names.iter().any(|n| timers::name_matches(&p.name().to_string_lossy(), n));
+    // 结束进程树时跳过父进程也在目标中的项，它们会随父进程的树一起结束
+    let targets = sys.processes().values().filter(|p| matches(p)).filter(|p| {
+        !tree || !p.parent().and_then(|parent| sys.process(parent)).is_some_and(&matches)
+    });
+    let mut failed: Vec<String> = Vec::new();
+    for process in targets {
+        if tree {
+            failed.extend(jobs::kill_tree(&sys, process.pid()));
+        } else {
+            tracing::info!(pid = process.pid().as_u32(), name = ?process.name(), "kill process");
+            if !process.kill() {
+                failed.push(process.name().to_string_lossy().to_string());
+            }
+        }
+    }
+    failed.sort();
+    failed.dedup();
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Failed to close {}", failed.join(", ")))
+    }
+}
+
+// 所有电源操作和结束进程都经过这里，统一处理模拟模式和操作记录
+pub fn perform(app: &AppHandle, action: AuditAction, source: ActionSource) -> Result<(), AutoShutdownError> {
+    let simulate = app.state::<SettingsStore>().get().simulate;
+    perform_with(app, action, source, simulate)
+}
+
+pub fn perform_with(
+    app: &AppHandle,
+    action: AuditAction,
+    source: ActionSource,
+    simulate: bool,
+) -> Result<(), AutoShutdownError> {
+    if simulate {
+        tracing::info!(?action, ?source, "simulated action");
+        let _ = app.emit(
+            "would-have-executed",
+            WouldHaveExecuted {
+                action: &action,
+                source: &source,
+            },
+        );
+        audit::record(app, action, source, &Ok(()), true);
+        return Ok(());
+    }
+
+    let _acting = Acting::start();
+    let result: Result<(), AutoShutdownError> = match &action {
+        AuditAction::Power { action } => action.execute(),
+        AuditAction::Kill { pid, name } => {
+            tracing::info!(pid, name, "kill process");
+            kill(app, *pid)
+        }
+        AuditAction::Cleanup { task } => {
+            tracing::info!(?task, "cleanup");
+            task.run().map_err(Into::into)
+        }
+        AuditAction::StopContainer { id, name, timeout_secs } => {
+            tracing::info!(id, name, "stop container");
+            docker::stop(id, *timeout_secs).map_err(Into::into)
+        }
+        AuditAction::RemotePower { host, action } => remote::power(app, host, *action).map_err(Into::into),
+        AuditAction::KillProcesses { names, tree } => kill_all(app, names, *tree).map_err(Into::into),
+        AuditAction::SetPowerPlan { guid } => powerplan::activate(guid).map_err(Into::into),
+        AuditAction::SetBrightness { percent } => brightness::set(*percent).map_err(Into::into),
+        AuditAction::RunScript { script } => hooks::run(app, script).and_then(|result| {
+            if result.succeeded() {
+                Ok(())
+            } else if result.timed_out {
+                Err(format!("{} timed out", script.path))
+            } else {
+                Err(format!("{} exited with {:?}", script.path, result.exit_code))
+            }
+        }).map_err(Into::into),
+    };
+    audit::record(app, action, source, &result.as_ref().map(|_| ()).map_err(ToString::to_string), false);
+    result
+}
+
+// 先执行准备步骤再执行电源操作，模拟模式下跳过准备步骤
+// 计划的重启会先保存当前会话
+pub fn perform_power(
+    app: &AppHandle,
+    action: PowerAction,
+    source: ActionSource,
+    options: &PreActionOptions,
+) -> Result<(), AutoShutdownError> {
+    let simulate = app.state::<SettingsStore>().get().simulate;
+    // 准备步骤中会关闭应用
+    let _acting = (!simulate).then(Acting::start);
+    if !simulate {
+        // 需在软关闭应用之前保存，否则记录不到已关闭的应用
+        if action == PowerAction::Restart && !matches!(source, ActionSource::User) {
+            session::snapshot(app);
+        }
+        if let Err(e) = pre_action::run(app, options, &source) {
+            tracing::warn!(?action, error = %e, "power action aborted");
+            audit::record(app, AuditAction::Power { action }, source, &Err(e.clone()), false);
+            return Err(AutoShutdownError::Failed(e));
+        }
+    }
+    let relaunching = action == PowerAction::Restart && !simulate;
+    if relaunching {
+        relaunch::prepare(app);
+    }
+    let result = perform_with(app, AuditAction::Power { action }, source, simulate);
+    if result.is_err() {
+        audio::restore_after_action(app);
+        disconnect::restore();
+        if relaunching {
+            relaunch::cancel(app);
+        }
+    }
+    result
+}
+
+// 准备步骤只在第一步之前执行一次，之后依次尝试，全部失败时返回最后一步的错误
+fn perform_fallback(
+    app: &AppHandle,
+    steps: &[FallbackStep],
+    source: ActionSource,
+    options: &PreActionOptions,
+) -> Result<(), AutoShutdownError> {
+    let simulate = app.state::<SettingsStore>().get().simulate;
+    if !simulate {
+        if let Err(e) = pre_action::run(app, options, &source) {
+            tracing::warn!(error = %e, "fallback chain aborted");
+            return Err(AutoShutdownError::Failed(e));
+        }
+    }
+    let mut last = Err(AutoShutdownError::Failed("No action to try".into()));
+    for (i, step) in steps.iter().enumerate() {
+        let started = Local::now();
+        let mut result = perform_power(app, step.action, source.clone(), &PreActionOptions::default());
+        let suspends = matches!(step.action, PowerAction::Sleep | PowerAction::Hibernate);
+        if result.is_ok() && suspends && step.wake_within_secs > 0 && !simulate {
+            // SetSuspendState 在唤醒后才返回，按墙上时钟计算睡了多久
+            let slept = (Local::now() - started).num_seconds().max(0);
+            if slept < step.wake_within_secs as i64 {
+                result = Err(AutoShutdownError::Failed(format!("{:?} ended after {} seconds", step.action, slept)));
+            }
+        }
+        let falling_back = result.is_err() && i + 1 < steps.len();
+        let error = result.as_ref().err().map(ToString::to_string);
+        tracing::info!(step = i, action = ?step.action, error = ?error, falling_back, "fallback step");
+        let _ = app.emit(
+            "fallback-attempt",
+            FallbackAttempt {
+                source: &source,
+                step: i,
+                action: step.action,
+                error: error.as_deref(),
+                falling_back,
+            },
+        );
+        if result.is_ok() {
+            return result;
+        }
+        last = result;
+    }
+    last
+}
+
+// 计时、规则和日程到期时执行，电源操作会先执行准备步骤
+pub fn perform_action(
+    app: &AppHandle,
+    action: &Action,
+    source: ActionSource,
+    options: &PreActionOptions,
+) -> Result<(), AutoShutdownError> {
+    match action {
+        Action::Power(action) => perform_power(app, *action, source, options),
+        Action::Fallback { steps } => perform_fallback(app, steps, source, options),
+        other => perform(app, other.audit(), source),
+    }
+}
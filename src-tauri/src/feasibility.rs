@@ -0,0 +1,155 @@
+use crate::actions::Action;
+use crate::power::PowerAction;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct FeasibilityIssue {
+    // 为 true 时操作必然失败，否则只是可能受影响
+    pub blocking: bool,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Feasibility {
+    pub feasible: bool,
+    pub issues: Vec<FeasibilityIssue>,
+}
+
+impl Feasibility {
+    fn from_issues(issues: Vec<FeasibilityIssue>) -> Self {
+        Feasibility {
+            feasible: !issues.iter().any(|i| i.blocking),
+            issues,
+        }
+    }
+}
+
+fn blocking(message: impl Into<String>) -> FeasibilityIssue {
+    FeasibilityIssue {
+        blocking: true,
+        message: message.into(),
+    }
+}
+
+fn warning(message: impl Into<String>) -> FeasibilityIssue {
+    FeasibilityIssue {
+        blocking: false,
+        message: message.into(),
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use crate::cmd;
+
+    // 受限账户或远程会话可能没有关机权限，禁用状态的权限在执行时会自动启用
+    pub fn has_shutdown_privilege() -> Option<bool> {
+        cmd::output("whoami", &["/priv"]).ok().map(|out| out.contains("SeShutdownPrivilege"))
+    }
+
+    // 组策略写入的 DWORD 值，未设置时返回 None
+    pub fn policy(key: &str, name: &str) -> Option<u32> {
+        let out = cmd::output("reg", &["query", key, "/v", name]).ok()?;
+        let value = out.lines().find(|l| l.contains("REG_DWORD"))?.split_whitespace().last()?;
+        u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+    }
+}
+
+// 无法判断时为 None
+#[cfg(windows)]
+pub fn shutdown_privilege() -> Option<bool> {
+    imp::has_shutdown_privilege()
+}
+
+#[cfg(not(windows))]
+pub fn shutdown_privilege() -> Option<bool> {
+    None
+}
+
+#[cfg(windows)]
+fn check_power(action: PowerAction) -> Vec<FeasibilityIssue> {
+    const EXPLORER_POLICIES: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Policies\Explorer";
+    const SYSTEM_POLICIES: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Policies\System";
+
+    let mut issues = Vec::new();
+    match action {
+        PowerAction::Shutdown | PowerAction::FullShutdown | PowerAction::Restart | PowerAction::LogOff => {
+            if imp::has_shutdown_privilege() == Some(false) {
+                issues.push(blocking("This account does not have the shutdown privilege (SeShutdownPrivilege)"));
+            }
+            if imp::policy(EXPLORER_POLICIES, "NoClose") == Some(1) {
+                issues.push(warning("Group policy hides shutdown from the Start menu; it may also be blocked for this user"));
+            }
+        }
+        PowerAction::Sleep | PowerAction::Hibernate => match crate::power::capabilities() {
+            Ok(caps) if action == PowerAction::Sleep && !caps.sleep => {
+                issues.push(blocking(if caps.modern_standby {
+                    "This PC uses Modern Standby and does not support classic sleep"
+                } else {
+                    "Sleep is not available on this PC"
+                }));
+            }
+            Ok(caps) if action == PowerAction::Hibernate && !caps.hibernate => {
+                issues.push(blocking("Hibernation is disabled (enable it with powercfg /hibernate on)"));
+            }
+            Ok(_) => {}
+            Err(e) => issues.push(warning(e)),
+        },
+        PowerAction::Lock => {
+            if imp::policy(SYSTEM_POLICIES, "DisableLockWorkstation") == Some(1) {
+                issues.push(blocking("Locking the workstation is disabled by group policy"));
+            }
+        }
+        PowerAction::DisplayOff => {}
+    }
+    issues
+}
+
+#[cfg(not(windows))]
+fn check_power(action: PowerAction) -> Vec<FeasibilityIssue> {
+    vec![blocking(format!("{:?} is only supported on Windows", action))]
+}
+
+// 在无人值守的计划执行前提前发现权限、策略和系统配置问题
+pub fn check(action: &Action) -> Feasibility {
+    let mut issues = Vec::new();
+    match action {
+        Action::Power(action) => issues.extend(check_power(*action)),
+        Action::RunScript { script } => {
+            if !Path::new(&script.path).is_file() {
+                issues.push(blocking(format!("Script {} does not exist", script.path)));
+            }
+        }
+        Action::SetPowerPlan { guid } => match crate::powerplan::list() {
+            Ok(plans) if !plans.iter().any(|p| p.guid.eq_ignore_ascii_case(guid.trim_matches(['{', '}']))) => {
+                issues.push(blocking(format!("Power plan {} does not exist", guid)));
+            }
+            Ok(_) => {}
+            Err(e) => issues.push(warning(e)),
+        },
+        Action::KillProcesses { .. } => {
+            if !crate::elevation::running_elevated() {
+                issues.push(warning("Processes running as administrator can only be closed when AutoShutdown runs elevated"));
+            }
+        }
+        Action::SetBrightness { .. } => {}
+        // 只要有一步可行整体就可行，其余步骤的问题降为警告
+        Action::Fallback { steps } => {
+            let checks: Vec<Vec<FeasibilityIssue>> = steps.iter().map(|s| check_power(s.action)).collect();
+            let any_feasible = checks.iter().any(|c| !c.iter().any(|i| i.blocking));
+            for (step, step_issues) in steps.iter().zip(checks) {
+                issues.extend(step_issues.into_iter().map(|i| FeasibilityIssue {
+                    blocking: i.blocking && !any_feasible,
+                    message: format!("{:?}: {}", step.action, i.message),
+                }));
+            }
+        }
+    }
+    Feasibility::from_issues(issues)
+}
+
+#[tauri::command(async)]
+pub fn check_action_feasibility(action: Action) -> Feasibility {
+    check(&action)
+}
@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// 浏览器和下载工具未完成文件的扩展名
+const PARTIAL_EXTENSIONS: &[&str] = &["crdownload", "part", "partial", "download", "!ut", "!qb", "tmp"];
+// 目录可能很大，两次采样之间至少间隔该时长
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+const MAX_DEPTH: usize = 8;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Scan {
+    pub total_size: u64,
+    pub files: usize,
+    pub partial_files: usize,
+}
+
+fn walk(path: &Path, depth: usize, scan: &mut Scan) {
+    let Ok(meta) = fs::metadata(path) else {
+        return;
+    };
+    if meta.is_file() {
+        scan.total_size += meta.len();
+        scan.files += 1;
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if PARTIAL_EXTENSIONS.contains(&ext.as_str()) {
+            scan.partial_files += 1;
+        }
+        return;
+    }
+    if depth >= MAX_DEPTH {
+        return;
+    }
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            walk(&entry.path(), depth + 1, scan);
+        }
+    }
+}
+
+// 文件或目录（递归）的统计信息，路径不存在时返回 None
+pub fn scan(path: &str) -> Option<Scan> {
+    let path = Path::new(path);
+    if !path.exists() {
+        return None;
+    }
+    let mut scan = Scan::default();
+    walk(path, 0, &mut scan);
+    Some(scan)
+}
+
+// 记录大小最近一次变化的时间
+#[derive(Default)]
+pub struct GrowthTracker {
+    last_sample: Option<Instant>,
+    last: Option<Scan>,
+    last_change: Option<Instant>,
+}
+
+impl GrowthTracker {
+    // 未到采样间隔时返回 None
+    pub fn sample(&mut self, path: &str) -> Option<Scan> {
+        if self.last_sample.is_some_and(|t| t.elapsed() < SAMPLE_INTERVAL) {
+            return None;
+        }
+        self.last_sample = Some(Instant::now());
+        let scan = scan(path).unwrap_or_default();
+        if let Some(last) = self.last {
+            if last.total_size != scan.total_size || last.files != scan.files {
+                self.last_change = Some(Instant::now());
+            }
+        }
+        self.last = Some(scan);
+        Some(scan)
+    }
+
+    // 开始观察后大小从未变化时返回 None
+    pub fn stable_for(&self) -> Option<Duration> {
+        self.last_change.map(|t| t.elapsed())
+    }
+
+    pub fn reset(&mut self) {
+        *self = GrowthTracker::default();
+    }
+}
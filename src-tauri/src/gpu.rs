@@ -0,0 +1,64 @@
+// 两次采集之间的间隔，PDH 的百分比计数器需要两个样本
+#[cfg(windows)]
+const SAMPLE_WINDOW: std::time::Duration = std::time::Duration::from_secs(1);
+
+// 与任务管理器一致：按引擎类型（3D、Compute、Copy 等）汇总各进程的占用，取最忙的一类
+#[cfg(windows)]
+pub fn utilization() -> Option<f64> {
+    use std::collections::HashMap;
+    use windows::core::w;
+    use windows::Win32::System::Performance::{
+        PdhAddEnglishCounterW, PdhCloseQuery, PdhCollectQueryData, PdhGetFormattedCounterArrayW, PdhOpenQueryW,
+        PDH_FMT_COUNTERVALUE_ITEM_W, PDH_FMT_DOUBLE, PDH_MORE_DATA,
+    };
+
+    let mut query = 0isize;
+    if unsafe { PdhOpenQueryW(None, 0, &mut query) } != 0 {
+        return None;
+    }
+    let mut counter = 0isize;
+    let result = (|| unsafe {
+        if PdhAddEnglishCounterW(query, w!(r"\GPU Engine(*)\Utilization Percentage"), 0, &mut counter) != 0
+            || PdhCollectQueryData(query) != 0
+        {
+            return None;
+        }
+        std::thread::sleep(SAMPLE_WINDOW);
+        if PdhCollectQueryData(query) != 0 {
+            return None;
+        }
+
+        let (mut size, mut count) = (0u32, 0u32);
+        if PdhGetFormattedCounterArrayW(counter, PDH_FMT_DOUBLE, &mut size, &mut count, None) != PDH_MORE_DATA {
+            // 没有任何 GPU 引擎实例
+            return Some(0.0);
+        }
+        // 用 u64 分配以满足结构体的对齐要求
+        let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+        let items = buffer.as_mut_ptr() as *mut PDH_FMT_COUNTERVALUE_ITEM_W;
+        if PdhGetFormattedCounterArrayW(counter, PDH_FMT_DOUBLE, &mut size, &mut count, Some(items)) != 0 {
+            return None;
+        }
+
+        // 实例名形如 pid_1234_luid_0x0_0x1_phys_0_eng_0_engtype_3D
+        let mut by_engine: HashMap<String, f64> = HashMap::new();
+        for item in std::slice::from_raw_parts(items, count as usize) {
+            if item.FmtValue.CStatus != 0 {
+                continue;
+            }
+            let name = item.szName.to_string().unwrap_or_default();
+            let engine = name.rsplit_once("engtype_").map(|(_, t)| t).unwrap_or_default().to_string();
+            *by_engine.entry(engine).or_default() += item.FmtValue.Anonymous.doubleValue;
+        }
+        Some(by_engine.into_values().fold(0.0, f64::max).min(100.0))
+    })();
+    unsafe {
+        PdhCloseQuery(query);
+    }
+    result
+}
+
+#[cfg(not(windows))]
+pub fn utilization() -> Option<f64> {
+    None
+}
@@ -0,0 +1,67 @@
+use crate::confirm::Confirmations;
+use crate::idle;
+use crate::notify::{self, Notice, NoticeKind};
+use crate::timers::ArmedTimer;
+use serde::Serialize;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Serialize, Clone)]
+struct IdleGrace<'a> {
+    id: &'a str,
+    // 以 proceed = false 回应表示“我还在”，true 表示立即执行
+    confirmation_id: &'a str,
+    label: &'a str,
+    seconds: u64,
+}
+
+// 空闲触发的操作到期后给出宽限期，期间有键盘鼠标输入或在界面上回应“我还在”时返回 true
+// 完全在后台判断，主窗口关闭时也有效
+pub fn user_present(app: &AppHandle, timer: &ArmedTimer, grace: Duration) -> bool {
+    let confirmations = app.state::<Confirmations>();
+    let id = confirmations.request();
+    let _ = app.emit(
+        "idle-grace",
+        IdleGrace {
+            id: &timer.id,
+            confirmation_id: &id,
+            label: &timer.spec.label,
+            seconds: grace.as_secs(),
+        },
+    );
+    notify::dispatch(
+        app,
+        Notice::new(
+            "AutoShutdown",
+            format!(
+                "{} in {} seconds because the computer is idle. Move the mouse or press a key to cancel.",
+                timer.spec.action,
+                grace.as_secs()
+            ),
+        )
+        .with_kind(NoticeKind::CountdownWarning)
+        .with_sound(),
+    );
+
+    let started = Instant::now();
+    loop {
+        if let Some(proceed) = confirmations.take(&id) {
+            tracing::info!(id = %timer.id, proceed, "idle grace answered");
+            return !proceed;
+        }
+        // 空闲时长短于宽限期已过去的时间，说明期间有过输入
+        if idle::idle_duration() < started.elapsed() {
+            confirmations.dismiss(&id);
+            tracing::info!(id = %timer.id, "input during idle grace");
+            return true;
+        }
+        if started.elapsed() >= grace {
+            confirmations.dismiss(&id);
+            return false;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
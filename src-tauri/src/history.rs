@@ -0,0 +1,60 @@
+use crate::storage;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+// 超过上限时只保留最新的记录
+const MAX_ENTRIES: usize = 5000;
+
+// 追加写入的 JSON Lines 历史记录，每行一条
+pub struct HistoryLog<T> {
+    path: PathBuf,
+    lock: Mutex<()>,
+    _entry: PhantomData<T>,
+}
+
+impl<T: Serialize + DeserializeOwned> HistoryLog<T> {
+    pub fn open(app: &AppHandle, file: &str) -> Self {
+        HistoryLog {
+            path: storage::data_path(app, file),
+            lock: Mutex::new(()),
+            _entry: PhantomData,
+        }
+    }
+
+    pub fn append(&self, entry: &T) -> Result<(), String> {
+        let _guard = self.lock.lock().unwrap();
+        let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+        drop(file);
+
+        let content = fs::read_to_string(&self.path).unwrap_or_default();
+        let count = content.lines().count();
+        if count > MAX_ENTRIES {
+            let kept: Vec<&str> = content.lines().skip(count - MAX_ENTRIES).collect();
+            fs::write(&self.path, kept.join("\n") + "\n").map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    // 按时间倒序返回，最新的在前
+    pub fn recent(&self, limit: usize) -> Vec<T> {
+        let _guard = self.lock.lock().unwrap();
+        let content = fs::read_to_string(&self.path).unwrap_or_default();
+        content
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .take(limit)
+            .collect()
+    }
+}
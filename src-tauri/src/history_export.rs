@@ -0,0 +1,184 @@
+use crate::audit::{ActionHistory, ActionRecord};
+use crate::error::AutoShutdownError;
+use crate::rules::{RuleExecution, RuleHistory, RuleOutcome};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::State;
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    // 操作和规则记录合并为一张按时间排序的表
+    Csv,
+    // 保留完整的记录结构
+    Json,
+}
+
+// 导出的时间范围，未指定的一端不限
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ExportRange {
+    pub from: Option<DateTime<Local>>,
+    pub to: Option<DateTime<Local>>,
+}
+
+impl ExportRange {
+    fn contains(&self, time: &DateTime<Local>) -> bool {
+        self.from.is_none_or(|from| *time >= from) && self.to.is_none_or(|to| *time <= to)
+    }
+}
+
+#[derive(Serialize)]
+struct ExportBundle {
+    exported_at: DateTime<Local>,
+    actions: Vec<ActionRecord>,
+    rule_executions: Vec<RuleExecution>,
+}
+
+const CSV_HEADER: &str = "timestamp,type,action,source,result,detail";
+
+// 含逗号、引号或换行的字段加引号，内部的引号写两次
+// 以 = + - @ 开头的字段（如进程名、错误信息）前加单引号，避免 Excel 当作公式执行
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn csv_row(fields: [&str; 6]) -> String {
+    fields.map(csv_field).join(",")
+}
+
+fn action_row(record: &ActionRecord) -> (DateTime<Local>, String) {
+    let result = match (record.simulated, record.success) {
+        (true, _) => "simulated",
+        (false, true) => "success",
+        (false, false) => "failed",
+    };
+    let row = csv_row([
+        &record.timestamp.to_rfc3339(),
+        "action",
+        &record.action.to_string(),
+        &record.source.to_string(),
+        result,
+        record.error.as_deref().unwrap_or_default(),
+    ]);
+    (record.timestamp, row)
+}
+
+fn rule_row(execution: &RuleExecution) -> (DateTime<Local>, String) {
+    let (result, detail) = match &execution.outcome {
+        RuleOutcome::Executed => ("executed", ""),
+        RuleOutcome::Skipped { reason } => ("skipped", reason.as_str()),
+        RuleOutcome::Failed { error } => ("failed", error.as_str()),
+    };
+    let row = csv_row([
+        &execution.timestamp.to_rfc3339(),
+        "rule",
+        &execution.action.to_string(),
+        &format!("Rule {}", execution.rule.name),
+        result,
+        detail,
+    ]);
+    (execution.timestamp, row)
+}
+
+// 将操作历史和规则执行历史写入文件，返回导出的记录数
+#[tauri::command(async)]
+pub fn export_history(
+    actions: State<'_, ActionHistory>,
+    rules: State<'_, RuleHistory>,
+    format: ExportFormat,
+    range: Option<ExportRange>,
+    path: String,
+) -> Result<usize, AutoShutdownError> {
+    let range = range.unwrap_or_default();
+    // 历史按时间倒序读出，导出时改为正序
+    let mut action_records: Vec<ActionRecord> = actions
+        .0
+        .recent(usize::MAX)
+        .into_iter()
+        .filter(|r| range.contains(&r.timestamp))
+        .collect();
+    action_records.reverse();
+    let mut rule_executions: Vec<RuleExecution> = rules
+        .0
+        .recent(usize::MAX)
+        .into_iter()
+        .filter(|r| range.contains(&r.timestamp))
+        .collect();
+    rule_executions.reverse();
+    let count = action_records.len() + rule_executions.len();
+
+    let content = match format {
+        ExportFormat::Json => {
+            let bundle = ExportBundle {
+                exported_at: Local::now(),
+                actions: action_records,
+                rule_executions,
+            };
+            serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?
+        }
+        ExportFormat::Csv => {
+            let mut rows: Vec<(DateTime<Local>, String)> = action_records.iter().map(action_row).collect();
+            rows.extend(rule_executions.iter().map(rule_row));
+            rows.sort_by_key(|(time, _)| *time);
+            // 带 BOM，Excel 才能正确识别 UTF-8 中的中文
+            let mut csv = format!("\u{feff}{}\r\n", CSV_HEADER);
+            for (_, row) in rows {
+                csv.push_str(&row);
+                csv.push_str("\r\n");
+            }
+            csv
+        }
+    };
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    tracing::info!(path, ?format, count, "history exported");
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_fields_are_unchanged() {
+        assert_eq!(csv_field("shutdown"), "shutdown");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("2026-10-15T22:30:00+08:00"), "2026-10-15T22:30:00+08:00");
+    }
+
+    #[test]
+    fn quotes_commas_quotes_and_newlines() {
+        assert_eq!(csv_field("Rule a, b"), "\"Rule a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line 1\nline 2"), "\"line 1\nline 2\"");
+        assert_eq!(csv_field("line 1\r\nline 2"), "\"line 1\r\nline 2\"");
+    }
+
+    #[test]
+    fn escapes_formula_prefixes() {
+        assert_eq!(csv_field("=HYPERLINK(\"http://x\")"), "\"'=HYPERLINK(\"\"http://x\"\")\"");
+        assert_eq!(csv_field("+1"), "'+1");
+        assert_eq!(csv_field("-cmd"), "'-cmd");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_field("\tx"), "'\tx");
+        assert_eq!(csv_field("a=b"), "a=b");
+    }
+
+    #[test]
+    fn rows_join_escaped_fields() {
+        assert_eq!(
+            csv_row(["t", "action", "=1+1", "Rule \"x\"", "failed", "a,b"]),
+            "t,action,'=1+1,\"Rule \"\"x\"\"\",failed,\"a,b\""
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use crate::cmd;
+use crate::jobs;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HookScript {
+    pub path: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+    // 脚本返回非零或超时时取消本次操作
+    #[serde(default)]
+    pub abort_on_failure: bool,
+}
+
+fn default_timeout() -> u64 {
+    60
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct HookResult {
+    pub path: String,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub output: String,
+}
+
+impl HookResult {
+    pub fn succeeded(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0)
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct HookOutput<'a> {
+    path: &'a str,
+    stream: &'static str,
+    line: String,
+}
+
+// 根据扩展名选择解释器
+fn command_for(hook: &HookScript) -> Command {
+    let ext = Path::new(&hook.path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mut command = match ext.as_str() {
+        "ps1" => {
+            let mut c = cmd::hidden("powershell");
+            c.args(["-NoProfile", "-ExecutionPolicy", "Bypass", "-File", &hook.path]);
+            c
+        }
+        "bat" | "cmd" => {
+            let mut c = cmd::hidden("cmd");
+            c.args(["/C", &hook.path]);
+            c
+        }
+        "sh" => {
+            let mut c = cmd::hidden("sh");
+            c.arg(&hook.path);
+            c
+        }
+        _ => cmd::hidden(&hook.path),
+    };
+    command.args(&hook.args);
+    command
+}
+
+// 逐行读取输出，转发给前端并累计到结果中
+fn pump(
+    app: AppHandle,
+    path: String,
+    stream: &'static str,
+    reader: impl Read + Send + 'static,
+    output: Arc<Mutex<String>>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            {
+                let mut output = output.lock().unwrap();
+                output.push_str(&line);
+                output.push('\n');
+            }
+            let _ = app.emit("hook-output", HookOutput { path: &path, stream, line });
+        }
+    })
+}
+
+pub fn run(app: &AppHandle, hook: &HookScript) -> Result<HookResult, String> {
+    tracing::info!(path = %hook.path, "running hook script");
+    let mut child = command_for(hook)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .stdin(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", hook.path, e))?;
+    // 脚本启动的子进程都加入作业，超时时一起结束
+    let job = jobs::Job::new(false)
+        .and_then(|job| job.assign(child.id()).map(|_| job))
+        .inspect_err(|e| tracing::debug!(error = %e, "hook script runs without a job object"))
+        .ok();
+
+    let output = Arc::new(Mutex::new(String::new()));
+    let mut pumps = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        pumps.push(pump(app.clone(), hook.path.clone(), "stdout", stdout, output.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        pumps.push(pump(app.clone(), hook.path.clone(), "stderr", stderr, output.clone()));
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(hook.timeout_secs);
+    let (exit_code, timed_out) = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break (status.code(), false);
+        }
+        if Instant::now() >= deadline {
+            if job.as_ref().is_none_or(|job| job.terminate().is_err()) {
+                let _ = child.kill();
+            }
+            let _ = child.wait();
+            break (None, true);
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+    // 超时时未能加入作业的子进程可能仍占用管道，不再等待读取线程
+    if !timed_out {
+        for pump in pumps {
+            let _ = pump.join();
+        }
+    }
+
+    let result = HookResult {
+        path: hook.path.clone(),
+        exit_code,
+        timed_out,
+        output: output.lock().unwrap().clone(),
+    };
+    tracing::info!(path = %hook.path, ?exit_code, timed_out, "hook script finished");
+    let _ = app.emit("hook-finished", &result);
+    Ok(result)
+}
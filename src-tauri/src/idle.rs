@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ActivityResponse {
+    Cancel,
+    // 从检测到输入起重新延后指定秒数
+    Postpone { seconds: u64 },
+}
+
+// 倒计时最后 last_secs 秒内检测到键盘或鼠标输入时取消或延后
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActivityAbort {
+    pub last_secs: u64,
+    pub response: ActivityResponse,
+}
+
+// 距离最后一次键盘或鼠标输入的时长
+#[cfg(windows)]
+pub fn idle_duration() -> Duration {
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    unsafe {
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return Duration::ZERO;
+        }
+        // 两者都是 32 位毫秒计数，约 49 天回绕一次
+        Duration::from_millis(GetTickCount().wrapping_sub(info.dwTime) as u64)
+    }
+}
+
+#[cfg(not(windows))]
+pub fn idle_duration() -> Duration {
+    Duration::ZERO
+}
+
+impl ActivityAbort {
+    // remaining 为倒计时剩余秒数，输入发生在最后阶段开始之后才算
+    pub fn input_detected(&self, remaining: i64) -> bool {
+        let remaining = remaining.max(0) as u64;
+        if remaining > self.last_secs {
+            return false;
+        }
+        idle_duration() < Duration::from_secs(self.last_secs - remaining)
+    }
+}
@@ -0,0 +1,129 @@
+use sysinfo::{Pid, System};
+
+// 按父进程关系收集 root 及其所有后代
+// Windows 不会重新指定孤儿进程的父进程，启动时间早于父进程的项说明父 pid 已被复用，忽略
+pub fn descendants(sys: &System, root: Pid) -> Vec<Pid> {
+    let mut found = vec![root];
+    let mut index = 0;
+    while index < found.len() {
+        let parent = found[index];
+        let parent_start = sys.process(parent).map_or(0, |p| p.start_time());
+        for (pid, process) in sys.processes() {
+            if process.parent() == Some(parent) && process.start_time() >= parent_start && !found.contains(pid) {
+                found.push(*pid);
+            }
+        }
+        index += 1;
+    }
+    found
+}
+
+// 作业对象中的进程派生的子进程自动加入同一作业，结束作业即可结束整棵进程树，
+// 不受中间进程先行退出的影响
+#[cfg(windows)]
+mod imp {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation, SetInformationJobObject,
+        TerminateJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_SET_QUOTA, PROCESS_TERMINATE};
+
+    pub struct Job(HANDLE);
+
+    // 作业句柄可在线程间使用
+    unsafe impl Send for Job {}
+    unsafe impl Sync for Job {}
+
+    impl Job {
+        // kill_on_close 为 true 时关闭句柄（包括本应用退出）会结束作业中的所有进程
+        pub fn new(kill_on_close: bool) -> Result<Job, String> {
+            let handle = unsafe { CreateJobObjectW(None, PCWSTR::null()) }
+                .map_err(|e| format!("Failed to create job object: {}", e.message()))?;
+            let job = Job(handle);
+            if kill_on_close {
+                let mut limits = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+                limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+                unsafe {
+                    SetInformationJobObject(
+                        job.0,
+                        JobObjectExtendedLimitInformation,
+                        &limits as *const _ as *const _,
+                        std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                    )
+                }
+                .map_err(|e| format!("Failed to configure job object: {}", e.message()))?;
+            }
+            Ok(job)
+        }
+
+        pub fn assign(&self, pid: u32) -> Result<(), String> {
+            unsafe {
+                let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, false, pid)
+                    .map_err(|e| format!("Failed to open process {}: {}", pid, e.message()))?;
+                let result = AssignProcessToJobObject(self.0, process);
+                let _ = CloseHandle(process);
+                result.map_err(|e| format!("Failed to add process {} to job: {}", pid, e.message()))
+            }
+        }
+
+        pub fn terminate(&self) -> Result<(), String> {
+            unsafe { TerminateJobObject(self.0, 1) }
+                .map_err(|e| format!("Failed to terminate job: {}", e.message()))
+        }
+    }
+
+    impl Drop for Job {
+        fn drop(&mut self) {
+            unsafe {
+                let _ = CloseHandle(self.0);
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod imp {
+    pub struct Job;
+
+    impl Job {
+        pub fn new(_kill_on_close: bool) -> Result<Job, String> {
+            Err("Job objects are only supported on Windows".into())
+        }
+
+        pub fn assign(&self, _pid: u32) -> Result<(), String> {
+            Err("Job objects are only supported on Windows".into())
+        }
+
+        pub fn terminate(&self) -> Result<(), String> {
+            Err("Job objects are only supported on Windows".into())
+        }
+    }
+}
+
+pub use imp::Job;
+
+// 先把整棵树放进同一个作业再结束作业，期间新启动的子进程也会被结束；
+// 无法加入作业的进程（如已提升权限的）单独结束，返回仍未结束的进程名
+pub fn kill_tree(sys: &System, root: Pid) -> Vec<String> {
+    let job = Job::new(false).ok();
+    let mut assigned = Vec::new();
+    let mut failed = Vec::new();
+    for pid in descendants(sys, root) {
+        let Some(process) = sys.process(pid) else {
+            continue;
+        };
+        tracing::info!(pid = pid.as_u32(), name = ?process.name(), root = root.as_u32(), "kill process tree");
+        if job.as_ref().is_some_and(|j| j.assign(pid.as_u32()).is_ok()) {
+            assigned.push(process);
+        } else if !process.kill() {
+            failed.push(process.name().to_string_lossy().to_string());
+        }
+    }
+    if let Some(Err(e)) = job.as_ref().filter(|_| !assigned.is_empty()).map(Job::terminate) {
+        tracing::warn!(error = %e, "failed to terminate job, killing processes one by one");
+        failed.extend(assigned.into_iter().filter(|p| !p.kill()).map(|p| p.name().to_string_lossy().to_string()));
+    }
+    failed
+}
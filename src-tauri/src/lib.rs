@@ -1,8 +1,110 @@
-use serde::Serialize;
-use sysinfo::{System, Pid};
-use std::process::Command;
-use std::collections::HashMap;
-use std::sync::Mutex;
+// 手机端只作为已配对桌面实例的遥控器，其余模块只在桌面端编译
+macro_rules! desktop_only {
+    ($($item:item)*) => {
+        $(#[cfg(desktop)] $item)*
+    };
+}
+
+mod error;
+mod logging;
+mod peers;
+mod storage;
+
+desktop_only! {
+    mod actions;
+    mod api;
+    mod app_state;
+    mod audio;
+    mod audit;
+    mod backup;
+    mod boot_history;
+    mod brightness;
+    mod calendar;
+    mod cleanup;
+    mod cloudsync;
+    mod cmd;
+    mod confirm;
+    mod countdown;
+    mod defer;
+    mod diagnostics;
+    mod disconnect;
+    mod discord;
+    mod docker;
+    mod elevation;
+    mod email;
+    mod events;
+    mod executor;
+    mod feasibility;
+    mod fswatch;
+    mod gpu;
+    mod grace;
+    mod history;
+    mod history_export;
+    mod hooks;
+    mod idle;
+    mod jobs;
+    mod machines;
+    mod maintenance;
+    mod media;
+    mod mqtt;
+    mod net;
+    mod notify;
+    mod ntfy;
+    mod os_guard;
+    mod power;
+    mod power_events;
+    mod powerplan;
+    mod pre_action;
+    mod printing;
+    mod process_events;
+    mod profiles;
+    mod qbittorrent;
+    mod relaunch;
+    mod reload;
+    mod remote;
+    mod rules;
+    mod schedule;
+    mod schedule_text;
+    mod self_test;
+    mod sequence;
+    mod services;
+    mod session;
+    mod settings;
+    mod soft_close;
+    mod ssh;
+    mod startup;
+    mod stats;
+    mod steam;
+    mod task_scheduler;
+    mod telegram;
+    mod testfire;
+    mod timers;
+    mod titles;
+    mod unsaved;
+    mod uptime;
+    mod users;
+    mod vms;
+    mod watchdog;
+    mod webhooks;
+    mod window_events;
+    mod winupdate;
+    mod wol;
+    mod ws;
+    mod wsl;
+}
+
+use tauri::Manager;
+
+desktop_only! {
+    use audit::{ActionSource, AuditAction};
+    use error::AutoShutdownError;
+    use power::PowerAction;
+    use serde::Serialize;
+    use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+}
 
 #[cfg(windows)]
 use windows::{
@@ -20,17 +122,22 @@ use windows::{
     },
 };
 
+#[cfg(desktop)]
 #[derive(Serialize, Clone)]
 pub struct ProcessInfo {
     pid: u32,
     name: String,
     title: String,
     icon: Option<String>, // base64 encoded PNG
+    // 以管理员身份运行，本应用未提升时无法结束
+    elevated: bool,
 }
 
-struct WindowInfo {
-    pid: u32,
-    title: String,
+#[cfg(desktop)]
+pub(crate) struct WindowInfo {
+    pub(crate) hwnd: isize,
+    pub(crate) pid: u32,
+    pub(crate) title: String,
 }
 
 #[cfg(windows)]
@@ -144,11 +251,9 @@ fn get_process_icon(exe_path: &str) -> Option<String> {
     }
 }
 
+// 枚举所有带标题的可见窗口
 #[cfg(windows)]
-#[tauri::command]
-fn get_running_apps() -> Vec<ProcessInfo> {
-
-    
+pub(crate) fn enumerate_windows() -> Vec<WindowInfo> {
     let windows_data: Mutex<Vec<WindowInfo>> = Mutex::new(Vec::new());
     
     unsafe {
@@ -158,9 +263,23 @@ fn get_running_apps() -> Vec<ProcessInfo> {
         );
     }
     
-    let windows = windows_data.into_inner().unwrap();
+    windows_data.into_inner().unwrap()
+}
+
+#[cfg(windows)]
+pub(crate) fn window_class(hwnd: isize) -> String {
+    use windows::Win32::UI::WindowsAndMessaging::GetClassNameW;
+
+    let mut buffer = [0u16; 256];
+    let len = unsafe { GetClassNameW(HWND(hwnd as _), &mut buffer) };
+    String::from_utf16_lossy(&buffer[..len.max(0) as usize])
+}
+
+// 根据 PID 去重并保留最长的标题
+#[cfg(windows)]
+pub(crate) fn collect_windows() -> HashMap<u32, WindowInfo> {
+    let windows = enumerate_windows();
     
-    // 根据 PID 去重
     let mut pid_map: HashMap<u32, WindowInfo> = HashMap::new();
     for w in windows {
         if let Some(existing) = pid_map.get(&w.pid) {
@@ -171,182 +290,282 @@ fn get_running_apps() -> Vec<ProcessInfo> {
             pid_map.insert(w.pid, w);
         }
     }
-    
-    let mut sys = System::new();
-    sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
-    
+    pid_map
+}
+
+// 排除系统外壳进程和本应用自身
+#[cfg(windows)]
+pub(crate) fn is_user_app(name: &str) -> bool {
+    !name.contains("explorer") 
+        && !name.contains("TextInputHost")
+        && !name.contains("SearchHost")
+        && !name.contains("ShellExperienceHost")
+        && !name.contains("StartMenuExperienceHost")
+        && !name.contains("autoshutdownapp")
+}
+
+// 进程列表在多次调用间复用，每次只刷新用到的进程
+#[cfg(desktop)]
+#[derive(Default)]
+pub struct SharedSystem(Mutex<System>);
+
+#[cfg(desktop)]
+impl SharedSystem {
+    // 刷新指定进程的名称和路径，已退出的进程随之移除
+    pub fn refresh(&self, pids: &[Pid]) -> std::sync::MutexGuard<'_, System> {
+        let mut sys = self.0.lock().unwrap();
+        sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(pids),
+            true,
+            ProcessRefreshKind::new().with_exe(UpdateKind::OnlyIfNotSet),
+        );
+        sys
+    }
+
+    // 刷新全部进程，只读取名称和父进程等基本信息
+    pub fn refresh_all(&self) -> std::sync::MutexGuard<'_, System> {
+        let mut sys = self.0.lock().unwrap();
+        sys.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::new());
+        sys
+    }
+}
+
+// 短时间内重复请求时复用上一次的应用列表，避免每次都重新枚举窗口和提取图标
+#[cfg(desktop)]
+const APP_LIST_TTL: Duration = Duration::from_secs(2);
+
+// 上一次的应用列表，连同生成时间和当时的窗口事件计数
+#[cfg(desktop)]
+#[derive(Default)]
+struct AppListCache(Mutex<Option<(Instant, u64, Vec<ProcessInfo>)>>);
+
+// 窗口有变化或超过有效期时重新生成；force_refresh 为 true 时总是重新生成
+#[cfg(desktop)]
+#[tauri::command]
+fn get_running_apps(
+    shared: tauri::State<'_, SharedSystem>,
+    cache: tauri::State<'_, AppListCache>,
+    force_refresh: Option<bool>,
+) -> Vec<ProcessInfo> {
+    let generation = titles::generation();
+    let mut cached = cache.0.lock().unwrap();
+    if let Some((at, seen, apps)) = cached.as_ref() {
+        if force_refresh != Some(true) && *seen == generation && at.elapsed() < APP_LIST_TTL {
+            return apps.clone();
+        }
+    }
+    let apps = collect_running_apps(&shared);
+    *cached = Some((Instant::now(), generation, apps.clone()));
+    apps
+}
+
+#[cfg(windows)]
+fn collect_running_apps(shared: &SharedSystem) -> Vec<ProcessInfo> {
+    let mut pid_map = collect_windows();
+    let pids: Vec<Pid> = pid_map.keys().map(|pid| Pid::from_u32(*pid)).collect();
+
+    // 先取出名称和路径再提取图标，避免提取期间一直占用锁
+    let candidates: Vec<(u32, String, Option<String>)> = {
+        let sys = shared.refresh(&pids);
+        pid_map
+            .keys()
+            .filter_map(|pid| {
+                let process = sys.process(Pid::from_u32(*pid))?;
+                let name = process.name().to_string_lossy().to_string();
+                let exe = process.exe().map(|p| p.to_string_lossy().to_string());
+                Some((*pid, name, exe))
+            })
+            .collect()
+    };
+
     let mut apps: Vec<ProcessInfo> = Vec::new();
-    
-    for (pid, window) in pid_map {
-        if let Some(process) = sys.process(Pid::from_u32(pid)) {
-            let name = process.name().to_string_lossy().to_string();
-            if !name.contains("explorer") 
-                && !name.contains("TextInputHost")
-                && !name.contains("SearchHost")
-                && !name.contains("ShellExperienceHost")
-                && !name.contains("StartMenuExperienceHost")
-                && !name.contains("autoshutdownapp")
-            {
-                // 获取图标
-                let icon = if let Some(exe_path) = process.exe() {
-                    get_process_icon(&exe_path.to_string_lossy())
-                } else {
-                    None
-                };
-                
-                apps.push(ProcessInfo {
-                    pid,
-                    name,
-                    title: window.title,
-                    icon,
-                });
-            }
+    for (pid, name, exe) in candidates {
+        if !is_user_app(&name) {
+            continue;
         }
+        let Some(window) = pid_map.remove(&pid) else {
+            continue;
+        };
+        apps.push(ProcessInfo {
+            pid,
+            name,
+            title: window.title,
+            icon: exe.and_then(|path| get_process_icon(&path)),
+            elevated: elevation::process_elevated(pid),
+        });
     }
     
-    apps.sort_by(|a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase()));
+    apps.sort_by_key(|a| a.title.to_lowercase());
     apps
 }
 
 #[cfg(windows)]
 unsafe extern "system" fn enum_window_callback(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    if let Some(window) = user_window(hwnd) {
+        let data = &*(lparam.0 as *const Mutex<Vec<WindowInfo>>);
+        if let Ok(mut windows) = data.lock() {
+            windows.push(window);
+        }
+    }
+
+    BOOL(1)
+}
+
+// 可见且有标题的用户窗口，排除桌面和输入法等系统窗口
+#[cfg(windows)]
+pub(crate) fn user_window(hwnd: HWND) -> Option<WindowInfo> {
     use std::ffi::OsString;
     use std::os::windows::ffi::OsStringExt;
-    
-    if !IsWindowVisible(hwnd).as_bool() {
-        return BOOL(1);
-    }
-    
-    let length = GetWindowTextLengthW(hwnd);
-    if length == 0 {
-        return BOOL(1);
-    }
-    
-    let mut buffer: Vec<u16> = vec![0; (length + 1) as usize];
-    let actual_length = GetWindowTextW(hwnd, &mut buffer);
-    if actual_length == 0 {
-        return BOOL(1);
-    }
-    
-    let title = OsString::from_wide(&buffer[..actual_length as usize])
-        .to_string_lossy()
-        .to_string();
-    
-    if title.is_empty() 
-        || title == "Program Manager" 
-        || title == "Windows Input Experience"
-        || title.starts_with("MSCTFIME")
-    {
-        return BOOL(1);
-    }
-    
-    let mut pid: u32 = 0;
-    GetWindowThreadProcessId(hwnd, Some(&mut pid));
-    
-    if pid == 0 {
-        return BOOL(1);
-    }
-    
-    let data = &*(lparam.0 as *const Mutex<Vec<WindowInfo>>);
-    if let Ok(mut windows) = data.lock() {
-        windows.push(WindowInfo { pid, title });
+
+    unsafe {
+        if !IsWindowVisible(hwnd).as_bool() {
+            return None;
+        }
+
+        let length = GetWindowTextLengthW(hwnd);
+        if length == 0 {
+            return None;
+        }
+
+        let mut buffer: Vec<u16> = vec![0; (length + 1) as usize];
+        let actual_length = GetWindowTextW(hwnd, &mut buffer);
+        if actual_length == 0 {
+            return None;
+        }
+
+        let title = OsString::from_wide(&buffer[..actual_length as usize])
+            .to_string_lossy()
+            .to_string();
+
+        if title.is_empty()
+            || title == "Program Manager"
+            || title == "Windows Input Experience"
+            || title.starts_with("MSCTFIME")
+        {
+            return None;
+        }
+
+        let mut pid: u32 = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+
+        if pid == 0 {
+            return None;
+        }
+
+        Some(WindowInfo { hwnd: hwnd.0 as isize, pid, title })
     }
-    
-    BOOL(1)
 }
 
-#[cfg(not(windows))]
-#[tauri::command]
-fn get_running_apps() -> Vec<ProcessInfo> {
+#[cfg(all(desktop, not(windows)))]
+fn collect_running_apps(_shared: &SharedSystem) -> Vec<ProcessInfo> {
     Vec::new()
 }
 
+#[cfg(desktop)]
 #[tauri::command]
-fn kill_process(pid: u32) -> bool {
-    let sys = System::new_all();
-    if let Some(process) = sys.process(Pid::from_u32(pid)) {
-        process.kill();
-        true
-    } else {
-        false
+fn kill_process(
+    app: tauri::AppHandle,
+    shared: tauri::State<'_, SharedSystem>,
+    pid: u32,
+    // 为 true 时对提升的进程弹出 UAC，通过管理员权限的 taskkill 结束
+    use_elevation: Option<bool>,
+) -> Result<(), AutoShutdownError> {
+    let name = shared
+        .refresh(&[Pid::from_u32(pid)])
+        .process(Pid::from_u32(pid))
+        .map(|p| p.name().to_string_lossy().to_string());
+    let Some(name) = name else {
+        tracing::warn!(pid, "kill process: not found");
+        return Err(AutoShutdownError::ProcessGone { pid });
+    };
+    if !elevation::running_elevated() && elevation::process_elevated(pid) {
+        if use_elevation == Some(true) {
+            return elevation::kill_elevated(pid);
+        }
+        return Err(AutoShutdownError::NeedsAdmin(format!("{} is running as administrator", name)));
     }
+    executor::perform(&app, AuditAction::Kill { pid, name }, ActionSource::User)
 }
 
+#[cfg(desktop)]
+fn user_power_action(app: &tauri::AppHandle, action: PowerAction) -> Result<(), AutoShutdownError> {
+    executor::perform(app, AuditAction::Power { action }, ActionSource::User)
+}
+
+#[cfg(desktop)]
 #[tauri::command]
-fn system_shutdown() {
-    #[cfg(target_os = "windows")]
-    {
-        Command::new("shutdown").args(["/s", "/t", "0"]).spawn().ok();
-    }
+fn system_shutdown(app: tauri::AppHandle) -> Result<(), AutoShutdownError> {
+    user_power_action(&app, PowerAction::Shutdown)
 }
 
+#[cfg(desktop)]
 #[tauri::command]
-fn system_restart() {
-    #[cfg(target_os = "windows")]
-    {
-        Command::new("shutdown").args(["/r", "/t", "0"]).spawn().ok();
-    }
+fn system_restart(app: tauri::AppHandle) -> Result<(), AutoShutdownError> {
+    user_power_action(&app, PowerAction::Restart)
 }
 
+#[cfg(desktop)]
 #[tauri::command]
-fn system_sleep() {
-    #[cfg(target_os = "windows")]
-    {
-        Command::new("rundll32.exe").args(["powrprof.dll,SetSuspendState", "0,1,0"]).spawn().ok();
-    }
+fn system_sleep(app: tauri::AppHandle) -> Result<(), AutoShutdownError> {
+    user_power_action(&app, PowerAction::Sleep)
 }
 
 #[cfg(windows)]
 mod window_watcher {
+    use crate::window_events::{self, WindowEventKind};
     use std::sync::atomic::{AtomicBool, Ordering};
     use std::sync::OnceLock;
     use std::thread;
-    use std::time::Duration;
-    use tauri::{AppHandle, Emitter};
+    use tauri::AppHandle;
     use windows::Win32::UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK};
     use windows::Win32::UI::WindowsAndMessaging::{
-        GetMessageW, MSG, EVENT_OBJECT_CREATE, EVENT_OBJECT_DESTROY,
-        EVENT_OBJECT_SHOW, EVENT_OBJECT_HIDE, WINEVENT_OUTOFCONTEXT,
+        GetAncestor, GetMessageW, MSG, CHILDID_SELF, EVENT_OBJECT_CREATE, EVENT_OBJECT_DESTROY,
+        EVENT_OBJECT_SHOW, EVENT_OBJECT_HIDE, EVENT_OBJECT_NAMECHANGE, GA_ROOT, WINEVENT_OUTOFCONTEXT,
         OBJID_WINDOW,
     };
     use windows::Win32::Foundation::HWND;
 
     static RUNNING: AtomicBool = AtomicBool::new(false);
     static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
-    static DEBOUNCE_FLAG: AtomicBool = AtomicBool::new(false);
+
+    // 窗口事件钩子是否已安装
+    pub fn is_running() -> bool {
+        RUNNING.load(Ordering::SeqCst)
+    }
+
+    // 只关心可见的顶层窗口，控件和子窗口的名称变化很频繁
+    unsafe fn top_level_window(hwnd: HWND) -> Option<(u32, String)> {
+        if GetAncestor(hwnd, GA_ROOT) != hwnd {
+            return None;
+        }
+        crate::user_window(hwnd).map(|window| (window.pid, window.title))
+    }
 
     unsafe extern "system" fn win_event_proc(
         _hook: HWINEVENTHOOK,
         event: u32,
-        _hwnd: HWND,
+        hwnd: HWND,
         id_object: i32,
-        _id_child: i32,
+        id_child: i32,
         _id_event_thread: u32,
         _dwms_event_time: u32,
     ) {
         // 只处理窗口对象
-        if id_object != OBJID_WINDOW.0 {
+        if id_object != OBJID_WINDOW.0 || id_child != CHILDID_SELF as i32 {
             return;
         }
 
-        // 检查是否是我们关心的事件
-        if event == EVENT_OBJECT_CREATE 
-            || event == EVENT_OBJECT_DESTROY
-            || event == EVENT_OBJECT_SHOW
-            || event == EVENT_OBJECT_HIDE
-        {
-            // 防抖：避免短时间内多次触发
-            if DEBOUNCE_FLAG.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
-                if let Some(app) = APP_HANDLE.get() {
-                    let _ = app.emit("window-changed", ());
-                }
-                
-                // 500ms 后重置标志
-                thread::spawn(|| {
-                    thread::sleep(Duration::from_millis(500));
-                    DEBOUNCE_FLAG.store(false, Ordering::SeqCst);
-                });
-            }
+        let kind = match event {
+            EVENT_OBJECT_CREATE => WindowEventKind::Create,
+            EVENT_OBJECT_DESTROY => WindowEventKind::Destroy,
+            EVENT_OBJECT_SHOW => WindowEventKind::Show,
+            EVENT_OBJECT_HIDE => WindowEventKind::Hide,
+            EVENT_OBJECT_NAMECHANGE => WindowEventKind::NameChange,
+            _ => return,
+        };
+        let window = if kind == WindowEventKind::NameChange { top_level_window(hwnd) } else { None };
+        if let Some(app) = APP_HANDLE.get() {
+            window_events::handle(app, kind, hwnd.0 as isize, window);
         }
     }
 
@@ -370,10 +589,25 @@ mod window_watcher {
                 );
 
                 if hook.is_invalid() {
+                    tracing::error!("failed to install window event hook");
                     RUNNING.store(false, Ordering::SeqCst);
                     return;
                 }
 
+                // 标题变化单独挂钩，避免范围内其他高频事件
+                let name_hook = SetWinEventHook(
+                    EVENT_OBJECT_NAMECHANGE,
+                    EVENT_OBJECT_NAMECHANGE,
+                    None,
+                    Some(win_event_proc),
+                    0,
+                    0,
+                    WINEVENT_OUTOFCONTEXT,
+                );
+                if name_hook.is_invalid() {
+                    tracing::warn!("failed to install title change hook, title rules fall back to polling");
+                }
+
                 // 消息循环
                 let mut msg = MSG::default();
                 while RUNNING.load(Ordering::SeqCst) {
@@ -383,6 +617,9 @@ mod window_watcher {
                 }
 
                 let _ = UnhookWinEvent(hook);
+                if !name_hook.is_invalid() {
+                    let _ = UnhookWinEvent(name_hook);
+                }
             }
         });
     }
@@ -390,23 +627,176 @@ mod window_watcher {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_shell::init())
-        .invoke_handler(tauri::generate_handler![
+    #[cfg(desktop)]
+    let handler: fn(tauri::ipc::Invoke) -> bool = tauri::generate_handler![
             get_running_apps,
             kill_process,
             system_shutdown,
             system_restart,
-            system_sleep
-        ])
+            system_sleep,
+            power::get_power_capabilities,
+            powerplan::list_power_plans,
+            powerplan::set_power_plan,
+            brightness::set_brightness,
+            services::list_services,
+            services::control_service,
+            vms::list_running_vms,
+            winupdate::get_pending_reboot_state,
+            uptime::get_uptime_info,
+            startup::list_startup_entries,
+            startup::set_startup_entry_enabled,
+            elevation::is_elevated,
+            elevation::relaunch_elevated,
+            feasibility::check_action_feasibility,
+            countdown::start_countdown,
+            countdown::cancel_countdown,
+            countdown::get_countdown_status,
+            timers::arm_timer,
+            timers::cancel_timer,
+            timers::snooze_timer,
+            timers::list_timers,
+            timers::get_timer_status,
+            app_state::get_app_state,
+            rules::list_rules,
+            rules::create_rule,
+            rules::update_rule,
+            rules::delete_rule,
+            rules::toggle_rule,
+            rules::get_rule_history,
+            rules::get_rule_conflicts,
+            history_export::export_history,
+            schedule::get_schedules,
+            schedule::save_schedule,
+            schedule::delete_schedule,
+            schedule_text::parse_schedule,
+            task_scheduler::sync_task_scheduler,
+            task_scheduler::list_mirrored_tasks,
+            task_scheduler::remove_mirrored_tasks,
+            task_scheduler::list_power_tasks,
+            logging::get_recent_logs,
+            diagnostics::create_diagnostics_bundle,
+            audit::get_action_history,
+            boot_history::get_system_power_history,
+            stats::get_statistics,
+            settings::get_settings,
+            settings::update_settings,
+            settings::export_settings,
+            settings::import_settings,
+            email::send_test_email,
+            testfire::test_trigger,
+            self_test::run_self_test,
+            window_events::inject_window_event,
+            sequence::list_sequences,
+            sequence::save_sequence,
+            sequence::delete_sequence,
+            sequence::run_sequence,
+            sequence::cancel_sequence,
+            session::get_saved_session,
+            session::restore_session,
+            confirm::respond_confirmation,
+            users::list_user_sessions,
+            backup::list_backup_presets,
+            machines::list_machines,
+            machines::save_machine,
+            machines::delete_machine,
+            remote::remote_shutdown,
+            wol::send_wol,
+            wol::wake_machine,
+            peers::discover_instances,
+            peers::start_pairing,
+            peers::pair_instance,
+            peers::list_peers,
+            peers::remove_peer,
+            peers::send_peer_command,
+            peers::peer_status,
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::delete_profile,
+            profiles::switch_profile,
+            profiles::export_profile,
+            profiles::import_profile
+    ];
+    #[cfg(mobile)]
+    let handler: fn(tauri::ipc::Invoke) -> bool = tauri::generate_handler![
+            logging::get_recent_logs,
+            peers::discover_instances,
+            peers::pair_instance,
+            peers::list_peers,
+            peers::remove_peer,
+            peers::send_peer_command,
+            peers::peer_status
+    ];
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
+        .invoke_handler(move |invoke| {
+            tracing::debug!(command = invoke.message.command(), "invoke");
+            handler(invoke)
+        })
         .setup(|app| {
-            #[cfg(windows)]
-            {
-                window_watcher::start_watching(app.handle().clone());
-            }
+            logging::init(app.handle());
+            tracing::info!(version = env!("CARGO_PKG_VERSION"), "starting");
+            app.manage(peers::PeerStore::load(app.handle()));
+            #[cfg(desktop)]
+            start_desktop(app);
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, _event| {
+            #[cfg(desktop)]
+            if let tauri::RunEvent::Exit = _event {
+                os_guard::on_exit(_app);
+            }
+        });
+}
+
+// 桌面端的状态和后台任务
+#[cfg(desktop)]
+fn start_desktop(app: &mut tauri::App) {
+    app.manage(settings::SettingsStore::load(app.handle()));
+    app.manage(schedule::ScheduleStore::load(app.handle()));
+    app.manage(rules::RuleStore::load(app.handle()));
+    app.manage(rules::RuleHistory::open(app.handle()));
+    app.manage(audit::ActionHistory::open(app.handle()));
+    app.manage(stats::UsageStore::load(app.handle()));
+    app.manage(sequence::SequenceStore::load(app.handle()));
+    app.manage(session::SessionStore::load(app.handle()));
+    app.manage(relaunch::PendingTimers::load(app.handle()));
+    app.manage(machines::MachineStore::load(app.handle()));
+    app.manage(profiles::ProfileStore::load(app.handle()));
+    app.manage(timers::TimerStore::default());
+    os_guard::install(app.handle());
+    app.manage(confirm::Confirmations::default());
+    app.manage(audio::AudioMonitor::default());
+    app.manage(media::MediaMonitor::default());
+    app.manage(api::ApiServer::default());
+    app.manage(api::CancelTokens::default());
+    app.manage(ws::EventStream::default());
+    app.manage(SharedSystem::default());
+    app.manage(AppListCache::default());
+    app.manage(process_events::ProcessEvents::default());
+    app.manage(calendar::CalendarCache::default());
+    timers::start_engine(app.handle().clone());
+    schedule::start_scheduler(app.handle().clone());
+    calendar::start_refresher(app.handle().clone());
+    rules::start_rule_engine(app.handle().clone());
+    stats::start_sampler(app.handle().clone());
+    audio::start_monitor(app.handle().clone());
+    power_events::start(app.handle().clone());
+    process_events::start(app.handle().clone());
+    media::start_monitor(app.handle().clone());
+    ws::start_forwarding(app.handle().clone());
+    api::start_server(app.handle().clone());
+    mqtt::start(app.handle().clone());
+    telegram::start(app.handle().clone());
+    reload::start_watcher(app.handle().clone());
+    relaunch::restore(app.handle());
+
+    #[cfg(windows)]
+    {
+        window_watcher::start_watching(app.handle().clone());
+    }
 }
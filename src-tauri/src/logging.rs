@@ -0,0 +1,109 @@
+use crate::error::AutoShutdownError;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use tauri::AppHandle;
+use tracing::Level;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::prelude::*;
+
+const LOG_PREFIX: &str = "autoshutdown";
+const MAX_LOG_FILES: usize = 7;
+
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Serialize, Clone)]
+pub struct LogEntry {
+    timestamp: String,
+    level: String,
+    target: String,
+    message: String,
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+// 按天滚动写入应用数据目录下的 logs，文件内容为 JSON Lines 便于读取
+pub fn init(app: &AppHandle) {
+    let dir = crate::storage::data_path(app, "logs");
+    let _ = fs::create_dir_all(&dir);
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir);
+    let file_layer = appender.ok().map(|appender| {
+        tracing_subscriber::fmt::layer()
+            .json()
+            .with_writer(appender)
+            .with_ansi(false)
+    });
+
+    let filter = Targets::new()
+        .with_default(Level::INFO)
+        .with_target(env!("CARGO_CRATE_NAME"), Level::DEBUG);
+
+    let _ = tracing_subscriber::registry()
+        .with(file_layer)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(filter)
+        .try_init();
+    let _ = LOG_DIR.set(dir);
+}
+
+fn parse_entry(line: &str) -> Option<LogEntry> {
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    let mut fields = value.get("fields")?.as_object()?.clone();
+    let message = fields
+        .remove("message")
+        .and_then(|m| m.as_str().map(str::to_string))
+        .unwrap_or_default();
+    Some(LogEntry {
+        timestamp: value.get("timestamp")?.as_str()?.to_string(),
+        level: value.get("level")?.as_str()?.to_string(),
+        target: value.get("target")?.as_str().unwrap_or_default().to_string(),
+        message,
+        fields,
+    })
+}
+
+// 日志文件从新到旧排列，日志尚未初始化时为空
+pub fn log_files() -> Result<Vec<PathBuf>, String> {
+    let Some(dir) = LOG_DIR.get() else {
+        return Ok(Vec::new());
+    };
+    // 文件名带日期，按名称倒序即从新到旧
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.file_name().is_some_and(|n| n.to_string_lossy().starts_with(LOG_PREFIX)))
+        .collect();
+    files.sort_by(|a, b| b.cmp(a));
+    Ok(files)
+}
+
+// 返回不低于指定级别的最新日志，最新的在前
+#[tauri::command]
+pub fn get_recent_logs(level: Option<String>, limit: Option<usize>) -> Result<Vec<LogEntry>, AutoShutdownError> {
+    let min_level = match level {
+        Some(level) => Level::from_str(&level).map_err(|e| e.to_string())?,
+        None => Level::INFO,
+    };
+    let limit = limit.unwrap_or(200);
+    let mut entries = Vec::new();
+    for file in log_files()? {
+        let content = fs::read_to_string(&file).unwrap_or_default();
+        for entry in content.lines().rev().filter_map(parse_entry) {
+            if Level::from_str(&entry.level).is_ok_and(|l| l <= min_level) {
+                entries.push(entry);
+                if entries.len() >= limit {
+                    return Ok(entries);
+                }
+            }
+        }
+    }
+    Ok(entries)
+}
@@ -0,0 +1,93 @@
+use crate::error::AutoShutdownError;
+use crate::ssh::SshConfig;
+use crate::storage::JsonStore;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tauri::{AppHandle, State};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Credentials {
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+}
+
+// 不输出密码
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+// 保存的其他电脑，供远程关机等操作选用
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Machine {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub host: String,
+    // 为空时使用当前登录用户的凭据
+    #[serde(default)]
+    pub credentials: Option<Credentials>,
+    // 设置后通过 SSH 控制（Linux 服务器），否则按 Windows 远程关机处理
+    #[serde(default)]
+    pub ssh: Option<SshConfig>,
+    // 网络唤醒用的 MAC 地址和广播地址
+    #[serde(default)]
+    pub mac: Option<String>,
+    #[serde(default)]
+    pub broadcast: Option<String>,
+}
+
+pub struct MachineStore(JsonStore<Vec<Machine>>);
+
+impl MachineStore {
+    pub fn load(app: &AppHandle) -> Self {
+        MachineStore(JsonStore::load(app, "machines.json"))
+    }
+
+    pub fn all(&self) -> Vec<Machine> {
+        self.0.get()
+    }
+
+    pub fn find_by_host(&self, host: &str) -> Option<Machine> {
+        self.all().into_iter().find(|m| m.host.eq_ignore_ascii_case(host))
+    }
+}
+
+#[tauri::command]
+pub fn list_machines(store: State<'_, MachineStore>) -> Vec<Machine> {
+    store.all()
+}
+
+#[tauri::command]
+pub fn save_machine(store: State<'_, MachineStore>, mut machine: Machine) -> Result<Machine, AutoShutdownError> {
+    if machine.host.trim().is_empty() {
+        return Err("Host must not be empty".into());
+    }
+    if machine.id.is_empty() {
+        machine.id = uuid::Uuid::new_v4().to_string();
+    }
+    let saved = machine.clone();
+    store.0.update(|machines| {
+        if let Some(existing) = machines.iter_mut().find(|m| m.id == machine.id) {
+            *existing = machine;
+        } else {
+            machines.push(machine);
+        }
+    })?;
+    Ok(saved)
+}
+
+#[tauri::command]
+pub fn delete_machine(store: State<'_, MachineStore>, id: String) -> Result<bool, AutoShutdownError> {
+    store.0.update(|machines| {
+        let before = machines.len();
+        machines.retain(|m| m.id != id);
+        machines.len() != before
+    })
+    .map_err(Into::into)
+}
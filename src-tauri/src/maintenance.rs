@@ -0,0 +1,64 @@
+use crate::services::{self, ServiceState};
+use crate::timers;
+use sysinfo::{ProcessesToUpdate, System};
+
+const DISK_TOOLS: &[&str] = &["defrag", "chkdsk"];
+// 名称像安装程序但常驻或与安装无关的进程
+const NOT_INSTALLERS: &[&str] = &["trustedinstaller", "msiexec"];
+
+// Windows Installer 在安装或卸载期间持有该互斥量，服务空闲时常驻的 msiexec 不会持有
+#[cfg(windows)]
+fn msi_installing() -> bool {
+    use windows::core::w;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{OpenMutexW, SYNCHRONIZATION_SYNCHRONIZE};
+
+    unsafe {
+        match OpenMutexW(SYNCHRONIZATION_SYNCHRONIZE, false, w!("Global\\_MSIExecute")) {
+            Ok(handle) => {
+                let _ = CloseHandle(handle);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn msi_installing() -> bool {
+    false
+}
+
+// setup.exe、xxx_installer.exe、Inno Setup 的卸载程序 unins000.exe 等
+fn looks_like_installer(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let stem = name.trim_end_matches(".exe");
+    if NOT_INSTALLERS.contains(&stem) {
+        return false;
+    }
+    stem.starts_with("setup") || stem.ends_with("setup") || stem.contains("install") || stem.starts_with("unins")
+}
+
+// 正在运行的安装程序名称，MSI 安装没有单独的进程名时返回 "Windows Installer"
+pub fn running_installer() -> Option<String> {
+    if msi_installing() {
+        return Some("Windows Installer".into());
+    }
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::All, false);
+    sys.processes()
+        .values()
+        .map(|p| p.name().to_string_lossy().to_string())
+        .find(|name| looks_like_installer(name))
+}
+
+// 磁盘检查、碎片整理或“优化驱动器”计划任务正在运行
+pub fn disk_maintenance_running() -> bool {
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::All, false);
+    if DISK_TOOLS.iter().any(|tool| timers::process_running(&sys, tool)) {
+        return true;
+    }
+    // 计划任务通过 defragsvc 服务执行优化，不一定启动 defrag.exe
+    cfg!(windows) && services::query_state("defragsvc").is_ok_and(|s| s == ServiceState::Running)
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+// 播放列表切换曲目时会短暂停止，持续停止超过该时长才视为播放结束
+const END_DEBOUNCE: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackState {
+    Playing,
+    Paused,
+    Stopped,
+    // 没有任何媒体会话
+    Closed,
+}
+
+#[derive(Default)]
+struct MediaState {
+    state: Option<PlaybackState>,
+    playing: bool,
+    stopped_since: Option<Instant>,
+    ended_at: Option<DateTime<Local>>,
+}
+
+#[derive(Default)]
+pub struct MediaMonitor(Mutex<MediaState>);
+
+impl MediaMonitor {
+    // 最近一次从播放转为停止的时间
+    pub fn ended_at(&self) -> Option<DateTime<Local>> {
+        self.0.lock().unwrap().ended_at
+    }
+
+    pub fn state(&self) -> Option<PlaybackState> {
+        self.0.lock().unwrap().state
+    }
+}
+
+#[cfg(windows)]
+fn playback_state() -> Result<PlaybackState, String> {
+    use windows::Media::Control::{
+        GlobalSystemMediaTransportControlsSessionManager as SessionManager,
+        GlobalSystemMediaTransportControlsSessionPlaybackStatus as Status,
+    };
+
+    let manager = SessionManager::RequestAsync()
+        .and_then(|op| op.get())
+        .map_err(|e| e.to_string())?;
+    let Ok(session) = manager.GetCurrentSession() else {
+        return Ok(PlaybackState::Closed);
+    };
+    let status = session
+        .GetPlaybackInfo()
+        .and_then(|info| info.PlaybackStatus())
+        .map_err(|e| e.to_string())?;
+    Ok(match status {
+        Status::Playing => PlaybackState::Playing,
+        Status::Paused | Status::Changing => PlaybackState::Paused,
+        Status::Stopped => PlaybackState::Stopped,
+        _ => PlaybackState::Closed,
+    })
+}
+
+#[cfg(not(windows))]
+fn playback_state() -> Result<PlaybackState, String> {
+    Ok(PlaybackState::Closed)
+}
+
+fn update(app: &AppHandle, monitor: &MediaMonitor, state: PlaybackState) {
+    let mut media = monitor.0.lock().unwrap();
+    if media.state != Some(state) {
+        media.state = Some(state);
+        let _ = app.emit("media-state", state);
+    }
+    match state {
+        PlaybackState::Playing => {
+            media.playing = true;
+            media.stopped_since = None;
+        }
+        PlaybackState::Paused => media.stopped_since = None,
+        PlaybackState::Stopped | PlaybackState::Closed if media.playing => {
+            let since = *media.stopped_since.get_or_insert_with(Instant::now);
+            if since.elapsed() >= END_DEBOUNCE {
+                let now = Local::now();
+                tracing::info!("media playback ended");
+                media.playing = false;
+                media.stopped_since = None;
+                media.ended_at = Some(now);
+                let _ = app.emit("media-ended", now);
+            }
+        }
+        PlaybackState::Stopped | PlaybackState::Closed => {}
+    }
+}
+
+pub fn start_monitor(app: AppHandle) {
+    thread::spawn(move || {
+        #[cfg(windows)]
+        unsafe {
+            use windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED};
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        }
+        let monitor = app.state::<MediaMonitor>();
+        let mut logged_error = false;
+        loop {
+            match playback_state() {
+                Ok(state) => update(&app, &monitor, state),
+                Err(e) if !logged_error => {
+                    tracing::warn!(error = %e, "failed to query media session");
+                    logged_error = true;
+                }
+                Err(_) => {}
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
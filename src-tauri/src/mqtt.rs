@@ -0,0 +1,462 @@
+use crate::audit::{ActionSource, AuditAction};
+use crate::executor;
+use crate::power::PowerAction;
+use crate::settings::SettingsStore;
+use crate::stats;
+use crate::timers::TimerStore;
+use chrono::Local;
+use native_tls::TlsConnector;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fmt;
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::System;
+use tauri::{AppHandle, Manager};
+
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+const STATE_INTERVAL: Duration = Duration::from_secs(10);
+const RECONNECT_DELAY: Duration = Duration::from_secs(15);
+const READ_POLL: Duration = Duration::from_secs(1);
+// 只接收命令主题上的短消息，更大的报文视为连接出错
+const MAX_PACKET: usize = 256 * 1024;
+// Home Assistant 中可按的按钮，payload 即命令主题收到的内容
+const BUTTONS: &[(&str, &str)] = &[
+    ("shutdown", "Shut down"),
+    ("restart", "Restart"),
+    ("sleep", "Sleep"),
+    ("cancel", "Cancel timers"),
+];
+// (键, 名称, 单位)
+const SENSORS: &[(&str, &str, &str)] = &[
+    ("countdown", "Countdown", "s"),
+    ("cpu", "CPU usage", "%"),
+    ("memory", "Memory usage", "%"),
+];
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct MqttConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    // 未启用 TLS 时密码随 CONNECT 报文明文发送
+    pub password: String,
+    // 通常使用 8883 端口
+    pub tls: bool,
+    // 为空时使用 autoshutdown/<计算机名>
+    pub base_topic: String,
+    // 向 Home Assistant 发布自动发现配置
+    pub discovery: bool,
+    pub discovery_prefix: String,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        MqttConfig {
+            host: String::new(),
+            port: 1883,
+            username: String::new(),
+            password: String::new(),
+            tls: false,
+            base_topic: String::new(),
+            discovery: true,
+            discovery_prefix: "homeassistant".into(),
+        }
+    }
+}
+
+// 设置会写入日志，不输出密码
+impl fmt::Debug for MqttConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MqttConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .field("tls", &self.tls)
+            .field("base_topic", &self.base_topic)
+            .field("discovery", &self.discovery)
+            .finish_non_exhaustive()
+    }
+}
+
+fn node_id() -> String {
+    System::host_name()
+        .unwrap_or_else(|| "pc".into())
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+impl MqttConfig {
+    fn base(&self) -> String {
+        if self.base_topic.is_empty() {
+            format!("autoshutdown/{}", node_id())
+        } else {
+            self.base_topic.trim_end_matches('/').to_string()
+        }
+    }
+}
+
+// MQTT 3.1.1 中本模块用到的最小子集：QoS 0 的发布和订阅
+mod packet {
+    use std::io::Read;
+
+    pub const CONNECT: u8 = 0x10;
+    pub const CONNACK: u8 = 0x20;
+    pub const PUBLISH: u8 = 0x30;
+    pub const SUBSCRIBE: u8 = 0x82;
+    pub const PINGREQ: u8 = 0xC0;
+
+    pub fn string(out: &mut Vec<u8>, value: &[u8]) {
+        out.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        out.extend_from_slice(value);
+    }
+
+    // 固定头：类型和标志 + 变长编码的剩余长度
+    pub fn frame(header: u8, body: Vec<u8>) -> Vec<u8> {
+        let mut out = vec![header];
+        let mut len = body.len();
+        loop {
+            let mut byte = (len % 128) as u8;
+            len /= 128;
+            if len > 0 {
+                byte |= 0x80;
+            }
+            out.push(byte);
+            if len == 0 {
+                break;
+            }
+        }
+        out.extend(body);
+        out
+    }
+
+    // 剩余长度最多四个字节（MQTT 3.1.1 §2.2.3）
+    pub fn remaining_length(reader: &mut impl Read) -> Result<usize, String> {
+        let mut len = 0usize;
+        for shift in (0..28).step_by(7) {
+            let mut byte = [0u8; 1];
+            reader.read_exact(&mut byte).map_err(|e| e.to_string())?;
+            len += ((byte[0] & 0x7F) as usize) << shift;
+            if byte[0] & 0x80 == 0 {
+                if len > super::MAX_PACKET {
+                    return Err(format!("MQTT packet too large ({} bytes)", len));
+                }
+                return Ok(len);
+            }
+        }
+        Err("Malformed MQTT remaining length".into())
+    }
+
+    // 发布报文的主题和内容，其他报文或内容不完整时返回 None
+    pub fn publish(header: u8, body: &[u8]) -> Option<(String, String)> {
+        if header & 0xF0 != PUBLISH || body.len() < 2 {
+            return None;
+        }
+        let topic_len = u16::from_be_bytes([body[0], body[1]]) as usize;
+        // QoS 大于 0 时主题后还有两字节的报文标识
+        let offset = 2 + topic_len + if header & 0x06 != 0 { 2 } else { 0 };
+        if body.len() < offset {
+            return None;
+        }
+        let topic = String::from_utf8_lossy(&body[2..2 + topic_len]).to_string();
+        let payload = String::from_utf8_lossy(&body[offset..]).trim().to_string();
+        Some((topic, payload))
+    }
+}
+
+// TLS 与明文连接共用的读写接口
+trait Transport: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Transport for T {}
+
+struct Connection {
+    stream: Box<dyn Transport>,
+    last_sent: Instant,
+}
+
+impl Connection {
+    fn open(config: &MqttConfig, availability: &str) -> Result<Connection, String> {
+        let socket = TcpStream::connect((config.host.as_str(), config.port)).map_err(|e| e.to_string())?;
+        socket.set_read_timeout(Some(Duration::from_secs(10))).map_err(|e| e.to_string())?;
+        let mut stream: Box<dyn Transport> = if config.tls {
+            let connector = TlsConnector::new().map_err(|e| e.to_string())?;
+            let tls = connector
+                .connect(&config.host, socket.try_clone().map_err(|e| e.to_string())?)
+                .map_err(|e| e.to_string())?;
+            Box::new(tls)
+        } else {
+            if !config.password.is_empty() {
+                tracing::warn!(host = %config.host, "mqtt password is sent in cleartext, enable tls to protect it");
+            }
+            Box::new(socket.try_clone().map_err(|e| e.to_string())?)
+        };
+
+        // 遗嘱消息：连接意外断开时由服务器把状态置为 offline
+        let mut flags = 0x02 | 0x04 | 0x20;
+        if !config.username.is_empty() {
+            flags |= 0x80;
+            if !config.password.is_empty() {
+                flags |= 0x40;
+            }
+        }
+        let mut body = Vec::new();
+        packet::string(&mut body, b"MQTT");
+        body.push(4);
+        body.push(flags);
+        body.extend_from_slice(&(KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+        packet::string(&mut body, format!("autoshutdown-{}", node_id()).as_bytes());
+        packet::string(&mut body, availability.as_bytes());
+        packet::string(&mut body, b"offline");
+        if !config.username.is_empty() {
+            packet::string(&mut body, config.username.as_bytes());
+            if !config.password.is_empty() {
+                packet::string(&mut body, config.password.as_bytes());
+            }
+        }
+        stream
+            .write_all(&packet::frame(packet::CONNECT, body))
+            .map_err(|e| e.to_string())?;
+
+        let mut ack = [0u8; 4];
+        stream.read_exact(&mut ack).map_err(|e| e.to_string())?;
+        if ack[0] != packet::CONNACK || ack[3] != 0 {
+            return Err(format!("Broker refused the connection (code {})", ack[3]));
+        }
+        socket.set_read_timeout(Some(READ_POLL)).map_err(|e| e.to_string())?;
+        Ok(Connection {
+            stream,
+            last_sent: Instant::now(),
+        })
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), String> {
+        self.last_sent = Instant::now();
+        self.stream.write_all(data).map_err(|e| e.to_string())
+    }
+
+    fn publish(&mut self, topic: &str, payload: &str, retain: bool) -> Result<(), String> {
+        let mut body = Vec::new();
+        packet::string(&mut body, topic.as_bytes());
+        body.extend_from_slice(payload.as_bytes());
+        self.send(&packet::frame(packet::PUBLISH | retain as u8, body))
+    }
+
+    fn subscribe(&mut self, topic: &str) -> Result<(), String> {
+        let mut body = 1u16.to_be_bytes().to_vec();
+        packet::string(&mut body, topic.as_bytes());
+        body.push(0);
+        self.send(&packet::frame(packet::SUBSCRIBE, body))
+    }
+
+    fn ping_if_idle(&mut self) -> Result<(), String> {
+        if self.last_sent.elapsed() >= KEEP_ALIVE / 2 {
+            self.send(&[packet::PINGREQ, 0])?;
+        }
+        Ok(())
+    }
+
+    // 读取一个报文，超时返回 None；只关心发布报文，返回其主题和内容
+    fn poll(&mut self) -> Result<Option<(String, String)>, String> {
+        let mut header = [0u8; 1];
+        match self.stream.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        }
+        let len = packet::remaining_length(&mut self.stream)?;
+        let mut body = vec![0u8; len];
+        self.stream.read_exact(&mut body).map_err(|e| e.to_string())?;
+        Ok(packet::publish(header[0], &body))
+    }
+}
+
+fn publish_discovery(conn: &mut Connection, config: &MqttConfig, base: &str) -> Result<(), String> {
+    let node = node_id();
+    let device = json!({
+        "identifiers": [format!("autoshutdown_{}", node)],
+        "name": System::host_name().unwrap_or_else(|| node.clone()),
+        "manufacturer": "AutoShutdown",
+        "sw_version": env!("CARGO_PKG_VERSION"),
+    });
+    let availability = format!("{}/availability", base);
+    for (key, name) in BUTTONS {
+        let config_json = json!({
+            "name": name,
+            "unique_id": format!("autoshutdown_{}_{}", node, key),
+            "command_topic": format!("{}/command", base),
+            "payload_press": key,
+            "availability_topic": availability,
+            "device": device,
+        });
+        let topic = format!("{}/button/{}/{}/config", config.discovery_prefix, node, key);
+        conn.publish(&topic, &config_json.to_string(), true)?;
+    }
+    for (key, name, unit) in SENSORS {
+        let config_json = json!({
+            "name": name,
+            "unique_id": format!("autoshutdown_{}_{}", node, key),
+            "state_topic": format!("{}/state", base),
+            "value_template": format!("{{{{ value_json.{} }}}}", key),
+            "unit_of_measurement": unit,
+            "availability_topic": availability,
+            "device": device,
+        });
+        let topic = format!("{}/sensor/{}/{}/config", config.discovery_prefix, node, key);
+        conn.publish(&topic, &config_json.to_string(), true)?;
+    }
+    Ok(())
+}
+
+fn state_payload(app: &AppHandle, sys: &mut System) -> String {
+    sys.refresh_cpu_usage();
+    // 最近一个到期的计时
+    let next = app
+        .state::<TimerStore>()
+        .all()
+        .iter()
+        .filter_map(|t| t.deadline().map(|d| (d, t.spec.action.clone())))
+        .min_by_key(|(d, _)| *d);
+    let countdown = next.as_ref().map(|(d, _)| (*d - Local::now()).num_seconds().max(0));
+    json!({
+        "countdown": countdown,
+        "action": next.map(|(_, a)| a),
+        "cpu": (sys.global_cpu_usage() * 10.0).round() / 10.0,
+        "memory": (stats::memory_used_percent() * 10.0).round() / 10.0,
+    })
+    .to_string()
+}
+
+fn handle_command(app: &AppHandle, command: &str) {
+    tracing::info!(command, "mqtt command received");
+    let action = match command {
+        "shutdown" => PowerAction::Shutdown,
+        "restart" => PowerAction::Restart,
+        "sleep" => PowerAction::Sleep,
+        "cancel" => {
+            app.state::<TimerStore>().cancel_all();
+            return;
+        }
+        _ => {
+            tracing::warn!(command, "unknown mqtt command");
+            return;
+        }
+    };
+    let _ = executor::perform(app, AuditAction::Power { action }, ActionSource::Mqtt);
+}
+
+// 连接后一直运行到出错或设置变化
+fn session(app: &AppHandle, config: &MqttConfig) -> Result<(), String> {
+    let base = config.base();
+    let availability = format!("{}/availability", base);
+    let command_topic = format!("{}/command", base);
+    let mut conn = Connection::open(config, &availability)?;
+    tracing::info!(host = %config.host, base = %base, "mqtt connected");
+    conn.publish(&availability, "online", true)?;
+    if config.discovery {
+        publish_discovery(&mut conn, config, &base)?;
+    }
+    conn.subscribe(&command_topic)?;
+
+    let mut sys = System::new();
+    let mut last_state: Option<Instant> = None;
+    loop {
+        if app.state::<SettingsStore>().get().mqtt.as_ref() != Some(config) {
+            let _ = conn.publish(&availability, "offline", true);
+            return Ok(());
+        }
+        if last_state.is_none_or(|t| t.elapsed() >= STATE_INTERVAL) {
+            conn.publish(&format!("{}/state", base), &state_payload(app, &mut sys), false)?;
+            last_state = Some(Instant::now());
+        }
+        if let Some((topic, payload)) = conn.poll()? {
+            if topic == command_topic {
+                handle_command(app, &payload.to_lowercase());
+            }
+        }
+        conn.ping_if_idle()?;
+    }
+}
+
+pub fn start(app: AppHandle) {
+    thread::spawn(move || loop {
+        match app.state::<SettingsStore>().get().mqtt {
+            Some(config) if !config.host.is_empty() => {
+                if let Err(e) = session(&app, &config) {
+                    tracing::warn!(error = %e, "mqtt connection lost");
+                    thread::sleep(RECONNECT_DELAY);
+                }
+            }
+            _ => thread::sleep(RECONNECT_DELAY),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_remaining_length() {
+        assert_eq!(packet::remaining_length(&mut [0x00u8].as_slice()), Ok(0));
+        assert_eq!(packet::remaining_length(&mut [0x7Fu8].as_slice()), Ok(127));
+        assert_eq!(packet::remaining_length(&mut [0x80u8, 0x01].as_slice()), Ok(128));
+        assert_eq!(packet::remaining_length(&mut [0xFFu8, 0xFF, 0x0F].as_slice()), Ok(262_143));
+    }
+
+    #[test]
+    fn rejects_five_byte_remaining_length() {
+        assert!(packet::remaining_length(&mut [0xFFu8, 0xFF, 0xFF, 0xFF, 0x01].as_slice()).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_packets() {
+        // 268435455，协议允许的最大值
+        assert!(packet::remaining_length(&mut [0xFFu8, 0xFF, 0xFF, 0x7F].as_slice()).is_err());
+        assert!(packet::remaining_length(&mut [0x81u8, 0x80, 0x10].as_slice()).is_err());
+    }
+
+    #[test]
+    fn frame_round_trips_length() {
+        let frame = packet::frame(packet::PUBLISH, vec![0; 300]);
+        let mut rest = &frame[1..];
+        assert_eq!(packet::remaining_length(&mut rest), Ok(300));
+        assert_eq!(rest.len(), 300);
+    }
+
+    fn publish_body(topic: &str, packet_id: Option<u16>, payload: &str) -> Vec<u8> {
+        let mut body = Vec::new();
+        packet::string(&mut body, topic.as_bytes());
+        if let Some(id) = packet_id {
+            body.extend_from_slice(&id.to_be_bytes());
+        }
+        body.extend_from_slice(payload.as_bytes());
+        body
+    }
+
+    #[test]
+    fn decodes_publish_packets() {
+        let body = publish_body("autoshutdown/pc/command", None, "shutdown\n");
+        assert_eq!(
+            packet::publish(packet::PUBLISH, &body),
+            Some(("autoshutdown/pc/command".into(), "shutdown".into()))
+        );
+        // QoS 1 带报文标识，retain 标志不影响解析
+        let body = publish_body("a/b", Some(7), "cancel");
+        assert_eq!(packet::publish(packet::PUBLISH | 0x02 | 0x01, &body), Some(("a/b".into(), "cancel".into())));
+    }
+
+    #[test]
+    fn ignores_other_and_truncated_packets() {
+        let body = publish_body("a/b", None, "sleep");
+        assert_eq!(packet::publish(0x90, &body), None);
+        assert_eq!(packet::publish(packet::PUBLISH, &body[..1]), None);
+        assert_eq!(packet::publish(packet::PUBLISH, &body[..4]), None);
+        // 声明了报文标识但长度不够
+        assert_eq!(packet::publish(packet::PUBLISH | 0x02, &publish_body("a/b", None, "")), None);
+    }
+}
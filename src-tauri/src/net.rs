@@ -0,0 +1,61 @@
+use std::net::{IpAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+// 解析出的任一地址能在超时内建立连接即视为端口开放
+pub fn port_open(host: &str, port: u16) -> bool {
+    let Ok(addrs) = (host, port).to_socket_addrs() else {
+        return false;
+    };
+    addrs
+        .into_iter()
+        .any(|addr| TcpStream::connect_timeout(&addr, PROBE_TIMEOUT).is_ok())
+}
+
+// 先发 ICMP 回显请求；对方屏蔽 ICMP 时可以指定一个端口改用 TCP 探测
+pub fn host_reachable(host: &str, tcp_port: Option<u16>) -> bool {
+    let echoed = (host, 0).to_socket_addrs().is_ok_and(|mut addrs| {
+        addrs.any(|addr| match addr.ip() {
+            IpAddr::V4(ip) => icmp_echo(ip.octets()),
+            IpAddr::V6(_) => false,
+        })
+    });
+    echoed || tcp_port.is_some_and(|port| port_open(host, port))
+}
+
+#[cfg(windows)]
+fn icmp_echo(octets: [u8; 4]) -> bool {
+    use windows::Win32::NetworkManagement::IpHelper::{
+        IcmpCloseHandle, IcmpCreateFile, IcmpSendEcho, ICMP_ECHO_REPLY,
+    };
+
+    let Ok(handle) = (unsafe { IcmpCreateFile() }) else {
+        return false;
+    };
+    let payload = [0u8; 32];
+    // 回复缓冲区需要容纳一个回复结构、回显数据和 ICMP 错误信息
+    let mut reply = vec![0u8; std::mem::size_of::<ICMP_ECHO_REPLY>() + payload.len() + 8];
+    let replies = unsafe {
+        IcmpSendEcho(
+            handle,
+            u32::from_ne_bytes(octets),
+            payload.as_ptr() as _,
+            payload.len() as u16,
+            None,
+            reply.as_mut_ptr() as _,
+            reply.len() as u32,
+            PROBE_TIMEOUT.as_millis() as u32,
+        )
+    };
+    unsafe {
+        let _ = IcmpCloseHandle(handle);
+    }
+    // Status 为 0 (IP_SUCCESS) 才是目标主机的回复，其余是路由器返回的不可达等
+    replies > 0 && unsafe { (*(reply.as_ptr() as *const ICMP_ECHO_REPLY)).Status } == 0
+}
+
+#[cfg(not(windows))]
+fn icmp_echo(_octets: [u8; 4]) -> bool {
+    false
+}
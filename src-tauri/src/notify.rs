@@ -0,0 +1,181 @@
+use crate::events::AppEvent;
+use crate::settings::SettingsStore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+// 通知类别，用户按类别选择发送渠道
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum NoticeKind {
+    CountdownWarning,
+    ActionResult,
+    // 操作失败单独分类，便于只为失败发送邮件
+    ActionFailed,
+    RuleFired,
+    General,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    Toast,
+    Telegram,
+    Discord,
+    Email,
+    Ntfy,
+    // 发往订阅了 notification 事件的 Webhook
+    Webhook,
+}
+
+// 未在 routes 中列出的类别使用默认渠道；未配置的渠道会被跳过
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct NotificationRoutes {
+    pub routes: HashMap<NoticeKind, Vec<Channel>>,
+}
+
+impl NotificationRoutes {
+    pub fn channels(&self, kind: NoticeKind) -> Vec<Channel> {
+        if let Some(channels) = self.routes.get(&kind) {
+            return channels.clone();
+        }
+        match kind {
+            NoticeKind::CountdownWarning | NoticeKind::General => {
+                vec![Channel::Toast, Channel::Telegram, Channel::Discord, Channel::Ntfy]
+            }
+            // 操作结果和规则触发默认不弹系统通知，只发往远程渠道
+            NoticeKind::ActionResult | NoticeKind::RuleFired => vec![Channel::Telegram, Channel::Discord],
+            // 邮件只默认用于操作失败这类重要事件
+            NoticeKind::ActionFailed => vec![
+                Channel::Toast,
+                Channel::Telegram,
+                Channel::Discord,
+                Channel::Email,
+                Channel::Ntfy,
+            ],
+        }
+    }
+}
+
+// 提醒中可以推迟的计时，前端据此显示“推迟”按钮
+#[derive(Serialize, Clone, Debug)]
+pub struct Snooze {
+    pub timer_id: String,
+    pub minutes: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Notice {
+    pub kind: NoticeKind,
+    pub title: String,
+    pub body: String,
+    // 同时播放提示音
+    pub sound: bool,
+    // 规则自带的渠道，设置后代替按类别选择的渠道
+    #[serde(skip)]
+    pub channels: Option<Vec<Channel>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snooze: Option<Snooze>,
+    // 倒计时提醒对应的计时，外部渠道据此附带取消按钮
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timer_id: Option<String>,
+}
+
+impl Notice {
+    pub fn new(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Notice {
+            kind: NoticeKind::General,
+            title: title.into(),
+            body: body.into(),
+            sound: false,
+            channels: None,
+            snooze: None,
+            timer_id: None,
+        }
+    }
+
+    pub fn with_kind(mut self, kind: NoticeKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_sound(mut self) -> Self {
+        self.sound = true;
+        self
+    }
+
+    pub fn with_channels(mut self, channels: Vec<Channel>) -> Self {
+        self.channels = Some(channels);
+        self
+    }
+
+    pub fn with_snooze(mut self, timer_id: String, minutes: u64) -> Self {
+        self.snooze = Some(Snooze { timer_id, minutes });
+        self
+    }
+
+    pub fn with_timer(mut self, timer_id: String) -> Self {
+        self.timer_id = Some(timer_id);
+        self
+    }
+}
+
+pub fn show_toast(app: &AppHandle, notice: &Notice) -> Result<(), String> {
+    app.notification()
+        .builder()
+        .title(&notice.title)
+        .body(&notice.body)
+        .show()
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(windows)]
+pub fn play_alarm() -> Result<(), String> {
+    use windows::core::w;
+    use windows::Win32::Media::Audio::{PlaySoundW, SND_ALIAS, SND_ASYNC};
+
+    unsafe {
+        if PlaySoundW(w!("SystemExclamation"), None, SND_ALIAS | SND_ASYNC).as_bool() {
+            Ok(())
+        } else {
+            Err("No sound device or system sound available".into())
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn play_alarm() -> Result<(), String> {
+    Err("Alarm sound is only supported on Windows".into())
+}
+
+// 统一的通知出口：按类别发往选定的渠道，并转发给前端
+pub fn dispatch(app: &AppHandle, notice: Notice) {
+    tracing::info!(kind = ?notice.kind, title = %notice.title, body = %notice.body, "notification");
+    let channels = match &notice.channels {
+        Some(channels) => channels.clone(),
+        None => app.state::<SettingsStore>().get().notifications.channels(notice.kind),
+    };
+    // 提示音跟随系统通知，规则自带渠道时由规则单独决定是否播放
+    if notice.sound && (notice.channels.is_some() || channels.contains(&Channel::Toast)) {
+        if let Err(e) = play_alarm() {
+            tracing::warn!(error = %e, "failed to play alarm");
+        }
+    }
+    for channel in channels {
+        match channel {
+            Channel::Toast => {
+                if let Err(e) = show_toast(app, &notice) {
+                    tracing::warn!(error = %e, "failed to show notification");
+                }
+            }
+            Channel::Telegram => crate::telegram::forward(app, &notice),
+            Channel::Discord => crate::discord::forward(app, &notice),
+            Channel::Email => crate::email::forward(app, &notice),
+            Channel::Ntfy => crate::ntfy::forward(app, &notice),
+            Channel::Webhook => crate::events::publish(app, AppEvent::Notification { notice: notice.clone() }),
+        }
+    }
+    let _ = app.emit("notification", notice);
+}
@@ -0,0 +1,94 @@
+use crate::api;
+use crate::notify::{Notice, NoticeKind};
+use crate::settings::SettingsStore;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct NtfyConfig {
+    pub server: String,
+    pub topic: String,
+    // 受保护的主题需要访问令牌
+    pub access_token: String,
+    // 手机访问本机 HTTP 接口的地址，如 http://192.168.1.10:47800；为空时不附带取消按钮
+    pub api_base_url: String,
+}
+
+impl Default for NtfyConfig {
+    fn default() -> Self {
+        NtfyConfig {
+            server: "https://ntfy.sh".into(),
+            topic: String::new(),
+            access_token: String::new(),
+            api_base_url: String::new(),
+        }
+    }
+}
+
+// 设置会写入日志，不输出令牌
+impl fmt::Debug for NtfyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NtfyConfig")
+            .field("server", &self.server)
+            .field("topic", &self.topic)
+            .field("api_base_url", &self.api_base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+// 倒计时警告附带一个取消该计时的按钮
+// 消息会保存在 ntfy 服务器上，按钮中只放一次性的取消令牌，不放 API 令牌
+fn cancel_action(app: &AppHandle, config: &NtfyConfig, timer_id: &str) -> Option<String> {
+    if config.api_base_url.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "http, Cancel, {}/cancel/{}, method=POST, clear=true",
+        config.api_base_url.trim_end_matches('/'),
+        api::mint_cancel_token(app, timer_id)
+    ))
+}
+
+pub fn send(config: &NtfyConfig, notice: &Notice, action: Option<&str>) -> Result<(), String> {
+    let url = format!("{}/{}", config.server.trim_end_matches('/'), config.topic);
+    let priority = match notice.kind {
+        NoticeKind::CountdownWarning | NoticeKind::ActionFailed => "high",
+        _ => "default",
+    };
+    let mut request = ureq::post(&url)
+        .timeout(Duration::from_secs(10))
+        .set("Title", &notice.title)
+        .set("Priority", priority);
+    if !config.access_token.is_empty() {
+        request = request.set("Authorization", &format!("Bearer {}", config.access_token));
+    }
+    if let Some(action) = action {
+        request = request.set("Actions", action);
+    }
+    request.send_string(&notice.body).map(|_| ()).map_err(|e| e.to_string())
+}
+
+// 在后台线程发送，避免阻塞通知的调用方
+pub fn forward(app: &AppHandle, notice: &Notice) {
+    let settings = app.state::<SettingsStore>().get();
+    let Some(config) = settings.ntfy else {
+        return;
+    };
+    if config.topic.is_empty() {
+        return;
+    }
+    let action = match (&notice.kind, &notice.timer_id) {
+        (NoticeKind::CountdownWarning, Some(id)) if settings.api.enabled => cancel_action(app, &config, id),
+        _ => None,
+    };
+    let notice = notice.clone();
+    thread::spawn(move || {
+        if let Err(e) = send(&config, &notice, action.as_deref()) {
+            tracing::warn!(error = %e, "failed to publish ntfy notification");
+        }
+    });
+}
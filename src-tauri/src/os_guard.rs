@@ -0,0 +1,96 @@
+use crate::audit::{self, ActionSource};
+use crate::notify::{self, Notice, NoticeKind};
+use crate::storage;
+use crate::timers::{self, ArmedTimer, TimerStore};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::{AppHandle, Manager};
+
+// 崩溃时写入，下次启动时报告后删除
+const INCIDENT_FILE: &str = "os_shutdown_incident.json";
+// 距离到期不足该秒数时退出视为系统正在执行计划
+const EXIT_GRACE_SECS: i64 = 5;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Incident {
+    at: DateTime<Local>,
+    timer: ArmedTimer,
+    reason: String,
+}
+
+fn record(app: &AppHandle, incident: &Incident) {
+    tracing::warn!(id = %incident.timer.id, reason = %incident.reason, "scheduled system shutdown aborted");
+    audit::record(
+        app,
+        incident.timer.spec.action.audit(),
+        ActionSource::from_timer(&incident.timer),
+        &Err(incident.reason.clone()),
+        false,
+    );
+    notify::dispatch(
+        app,
+        Notice::new(
+            "AutoShutdown",
+            format!(
+                "The scheduled {} was cancelled at {} because {}",
+                incident.timer.spec.action,
+                incident.at.format("%H:%M:%S"),
+                incident.reason
+            ),
+        )
+        .with_kind(NoticeKind::ActionFailed),
+    );
+}
+
+// 上次运行崩溃时留下的记录
+fn report_previous(app: &AppHandle) {
+    let path = storage::data_path(app, INCIDENT_FILE);
+    if let Some(incident) = storage::load::<Option<Incident>>(&path) {
+        record(app, &incident);
+        let _ = fs::remove_file(&path);
+    }
+}
+
+// 主线程或计时线程崩溃时撤销本应用交给 shutdown.exe 的计划，避免应用不在时留下用户看不到的关机
+// 崩溃处理中不写操作历史，只落盘一份记录，下次启动时再报告
+pub fn install(app: &AppHandle) {
+    report_previous(app);
+    let handle = app.clone();
+    let path = storage::data_path(app, INCIDENT_FILE);
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // 其他后台线程崩溃时应用和计时仍在运行，系统计划照常保留
+        let fatal = matches!(std::thread::current().name(), Some("main") | Some(timers::ENGINE_THREAD));
+        if let Some(timer) = timers::os_timer().filter(|_| fatal) {
+            timers::abort_os_on_panic();
+            handle.state::<TimerStore>().release_os();
+            let incident = Incident {
+                at: Local::now(),
+                timer,
+                reason: format!("AutoShutdown crashed: {}", info),
+            };
+            let _ = storage::save(&path, &incident);
+        }
+        previous(info);
+    }));
+}
+
+// 正常退出时同样撤销；即将到期时可能正是该计划让系统关机，不再撤销
+pub fn on_exit(app: &AppHandle) {
+    let Some(timer) = timers::os_timer() else {
+        return;
+    };
+    if timer.deadline().is_none_or(|d| d <= Local::now() + chrono::Duration::seconds(EXIT_GRACE_SECS)) {
+        return;
+    }
+    timers::abort_os();
+    record(
+        app,
+        &Incident {
+            at: Local::now(),
+            timer,
+            reason: "AutoShutdown exited".into(),
+        },
+    );
+}
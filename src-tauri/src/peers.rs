@@ -0,0 +1,629 @@
+#[cfg(desktop)]
+use crate::api::ApiSettings;
+use crate::error::AutoShutdownError;
+#[cfg(desktop)]
+use crate::settings::SettingsStore;
+use crate::storage::JsonStore;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+use std::net::IpAddr;
+#[cfg(desktop)]
+use std::sync::Mutex;
+use std::time::Duration;
+#[cfg(desktop)]
+use std::time::Instant;
+use sysinfo::System;
+use tauri::{AppHandle, Manager, State};
+
+const SERVICE_TYPE: &str = "_autoshutdown._tcp.local";
+const BROWSE_TIME: Duration = Duration::from_secs(3);
+#[cfg(desktop)]
+const PAIRING_VALID: Duration = Duration::from_secs(120);
+// 配对码只有六位，错误次数过多时作废
+#[cfg(desktop)]
+const MAX_PAIRING_ATTEMPTS: u32 = 5;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 局域网中通过 mDNS 发现的实例
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct DiscoveredInstance {
+    pub name: String,
+    pub host: String,
+    pub address: Option<IpAddr>,
+    pub port: u16,
+}
+
+// 已配对的实例，token 是对方签发给本机的令牌，granted 是本机签发给对方的令牌
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Peer {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub token: String,
+    // 只能读取状态、执行电源操作和取消计时，解除配对后作废
+    #[serde(default)]
+    pub granted: String,
+}
+
+// 返回给前端的信息，不含令牌
+#[derive(Serialize, Clone, Debug)]
+pub struct PeerInfo {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+}
+
+impl From<Peer> for PeerInfo {
+    fn from(peer: Peer) -> Self {
+        PeerInfo {
+            id: peer.id,
+            name: peer.name,
+            host: peer.host,
+            port: peer.port,
+        }
+    }
+}
+
+// 不输出令牌
+impl fmt::Debug for Peer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Peer")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerCommand {
+    Shutdown,
+    Restart,
+    Sleep,
+    // 取消对方所有计时
+    Cancel,
+}
+
+// 配对时双方交换的信息
+#[derive(Serialize, Deserialize)]
+pub struct PairRequest {
+    pub code: String,
+    pub name: String,
+    pub port: u16,
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PairResponse {
+    pub name: String,
+    pub token: String,
+}
+
+#[cfg(desktop)]
+struct PairingCode {
+    code: String,
+    issued: Instant,
+    attempts: u32,
+    // 第一个请求的来源，之后只接受这个地址
+    address: Option<IpAddr>,
+}
+
+pub struct PeerStore {
+    peers: JsonStore<Vec<Peer>>,
+    // 手机端只发起配对，不接受配对
+    #[cfg(desktop)]
+    pairing: Mutex<Option<PairingCode>>,
+}
+
+impl PeerStore {
+    pub fn load(app: &AppHandle) -> Self {
+        PeerStore {
+            peers: JsonStore::load(app, "peers.json"),
+            #[cfg(desktop)]
+            pairing: Mutex::new(None),
+        }
+    }
+
+    fn save(&self, peer: Peer) -> Result<(), String> {
+        self.peers.update(|peers| {
+            // 同一台机器重新配对时替换旧记录
+            peers.retain(|p| p.id != peer.id && !(p.host == peer.host && p.port == peer.port));
+            peers.push(peer);
+        })
+    }
+
+    // 对方调用本机接口时带的令牌，返回对应的实例
+    #[cfg(desktop)]
+    pub fn granted_to(&self, token: &str) -> Option<Peer> {
+        self.peers
+            .get()
+            .into_iter()
+            .find(|p| !p.granted.is_empty() && crate::api::token_matches(token, &p.granted))
+    }
+}
+
+#[cfg(desktop)]
+fn mint_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+fn instance_name() -> String {
+    System::host_name().unwrap_or_else(|| "AutoShutdown".into())
+}
+
+// HTTP 接口开始监听后才广播，随接口停止而撤销
+#[cfg(desktop)]
+pub fn advertise(config: &ApiSettings) {
+    imp::advertise(&instance_name(), config.port);
+}
+
+#[cfg(desktop)]
+pub fn withdraw() {
+    imp::withdraw();
+}
+
+// 对方实例通过 HTTP 接口的 /pair 发来请求，配对码正确时保存对方并返回为它签发的令牌
+#[cfg(desktop)]
+pub fn accept_pairing(app: &AppHandle, request: PairRequest, address: IpAddr) -> Result<PairResponse, String> {
+    let store = app.state::<PeerStore>();
+    {
+        let mut pairing = store.pairing.lock().unwrap();
+        let Some(current) = pairing.as_mut().filter(|p| p.issued.elapsed() < PAIRING_VALID) else {
+            *pairing = None;
+            return Err("No pairing in progress".into());
+        };
+        if current.address.is_some_and(|a| a != address) {
+            return Err("Pairing is in progress with another device".into());
+        }
+        current.address = Some(address);
+        if current.code != request.code {
+            current.attempts += 1;
+            if current.attempts >= MAX_PAIRING_ATTEMPTS {
+                *pairing = None;
+            }
+            return Err("Wrong pairing code".into());
+        }
+        *pairing = None;
+    }
+
+    let granted = mint_token();
+    store.save(Peer {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: request.name.clone(),
+        host: address.to_string(),
+        port: request.port,
+        token: request.token,
+        granted: granted.clone(),
+    })?;
+    tracing::info!(name = %request.name, %address, "paired with instance");
+    Ok(PairResponse {
+        name: instance_name(),
+        token: granted,
+    })
+}
+
+// 需要等待数秒收集回应，不占用主线程
+#[tauri::command(async)]
+pub fn discover_instances() -> Vec<DiscoveredInstance> {
+    imp::browse(BROWSE_TIME)
+}
+
+// 生成配对码显示给用户，在另一台机器上输入
+#[cfg(desktop)]
+#[tauri::command]
+pub fn start_pairing(store: State<'_, PeerStore>, settings: State<'_, SettingsStore>) -> Result<String, AutoShutdownError> {
+    if !settings.get().api.enabled {
+        return Err("Enable the HTTP API before pairing".into());
+    }
+    let random = uuid::Uuid::new_v4();
+    let code = format!("{:06}", u32::from_le_bytes(random.as_bytes()[..4].try_into().unwrap()) % 1_000_000);
+    *store.pairing.lock().unwrap() = Some(PairingCode {
+        code: code.clone(),
+        issued: Instant::now(),
+        attempts: 0,
+        address: None,
+    });
+    Ok(code)
+}
+
+#[tauri::command]
+pub fn pair_instance(app: AppHandle, host: String, port: u16, code: String) -> Result<PeerInfo, AutoShutdownError> {
+    #[cfg(desktop)]
+    let (own_port, own_token) = {
+        let api = app.state::<SettingsStore>().get().api;
+        if !api.enabled {
+            return Err("Enable the HTTP API before pairing".into());
+        }
+        (api.port, mint_token())
+    };
+    // 手机端只作为遥控器，不提供接口给对方反向控制
+    #[cfg(mobile)]
+    let (own_port, own_token) = (0, String::new());
+    let request = PairRequest {
+        code: code.trim().to_string(),
+        name: instance_name(),
+        port: own_port,
+        token: own_token.clone(),
+    };
+    let response: PairResponse = ureq::post(&format!("http://{}:{}/pair", host, port))
+        .timeout(REQUEST_TIMEOUT)
+        .send_json(&request)
+        .map_err(|e| format!("Pairing failed: {}", e))?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+    let peer = Peer {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: response.name,
+        host,
+        port,
+        token: response.token,
+        granted: own_token,
+    };
+    app.state::<PeerStore>().save(peer.clone())?;
+    tracing::info!(?peer, "paired with instance");
+    Ok(peer.into())
+}
+
+#[tauri::command]
+pub fn list_peers(store: State<'_, PeerStore>) -> Vec<PeerInfo> {
+    store.peers.get().into_iter().map(Into::into).collect()
+}
+
+// 删除记录的同时作废本机签发给对方的令牌
+#[tauri::command]
+pub fn remove_peer(store: State<'_, PeerStore>, id: String) -> Result<bool, AutoShutdownError> {
+    store.peers.update(|peers| {
+        let before = peers.len();
+        peers.retain(|p| p.id != id);
+        peers.len() != before
+    })
+    .map_err(Into::into)
+}
+
+fn find_peer(store: &PeerStore, id: &str) -> Result<Peer, AutoShutdownError> {
+    store
+        .peers
+        .get()
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| AutoShutdownError::NotFound("Peer not found".into()))
+}
+
+// 读取对方的 /status，手机端用来显示各台电脑的计时
+#[tauri::command(async)]
+pub fn peer_status(store: State<'_, PeerStore>, id: String) -> Result<Value, AutoShutdownError> {
+    let peer = find_peer(&store, &id)?;
+    ureq::get(&format!("http://{}:{}/status", peer.host, peer.port))
+        .timeout(REQUEST_TIMEOUT)
+        .set("Authorization", &format!("Bearer {}", peer.token))
+        .call()
+        .map_err(|e| format!("{} is unreachable: {}", peer.name, e))?
+        .into_json()
+        .map_err(|e| e.to_string().into())
+}
+
+#[tauri::command]
+pub fn send_peer_command(store: State<'_, PeerStore>, id: String, command: PeerCommand) -> Result<(), AutoShutdownError> {
+    let peer = find_peer(&store, &id)?;
+    let path = match command {
+        PeerCommand::Shutdown => "power/shutdown",
+        PeerCommand::Restart => "power/restart",
+        PeerCommand::Sleep => "power/sleep",
+        PeerCommand::Cancel => "cancel",
+    };
+    tracing::info!(peer = %peer.name, ?command, "sending command to peer");
+    ureq::post(&format!("http://{}:{}/{}", peer.host, peer.port, path))
+        .timeout(REQUEST_TIMEOUT)
+        .set("Authorization", &format!("Bearer {}", peer.token))
+        .send_json(json!({}))
+        .map(|_| ())
+        .map_err(|e| format!("{} did not accept the command: {}", peer.name, e).into())
+}
+
+// 使用系统自带的 DNS-SD 接口（Windows 10 1809 起），不自行监听 5353 端口
+#[cfg(windows)]
+mod imp {
+    use super::{DiscoveredInstance, SERVICE_TYPE};
+    use std::ffi::c_void;
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::DNS_REQUEST_PENDING;
+    use windows::Win32::NetworkManagement::Dns::{
+        DnsFree, DnsFreeRecordList, DnsServiceBrowse, DnsServiceBrowseCancel, DnsServiceConstructInstance,
+        DnsServiceDeRegister, DnsServiceFreeInstance, DnsServiceRegister, DNS_QUERY_REQUEST_VERSION1, DNS_RECORDW,
+        DNS_SERVICE_BROWSE_REQUEST, DNS_SERVICE_BROWSE_REQUEST_0, DNS_SERVICE_CANCEL, DNS_SERVICE_INSTANCE,
+        DNS_SERVICE_REGISTER_REQUEST, DNS_TYPE_A, DNS_TYPE_PTR, DNS_TYPE_SRV,
+    };
+
+    struct Registration(*mut DNS_SERVICE_INSTANCE);
+    // 只在持有锁时访问
+    unsafe impl Send for Registration {}
+
+    static REGISTRATION: Mutex<Option<Registration>> = Mutex::new(None);
+    static FOUND: Mutex<Vec<DiscoveredInstance>> = Mutex::new(Vec::new());
+
+    // 回调收到的实例由调用方释放
+    unsafe extern "system" fn completed(status: u32, _context: *const c_void, instance: *const DNS_SERVICE_INSTANCE) {
+        if status != 0 {
+            tracing::warn!(status, "mdns registration failed");
+        }
+        if !instance.is_null() {
+            DnsServiceFreeInstance(instance);
+        }
+    }
+
+    fn register_request(instance: *mut DNS_SERVICE_INSTANCE) -> DNS_SERVICE_REGISTER_REQUEST {
+        DNS_SERVICE_REGISTER_REQUEST {
+            Version: DNS_QUERY_REQUEST_VERSION1.0,
+            pServiceInstance: instance,
+            pRegisterCompletionCallback: Some(completed),
+            ..Default::default()
+        }
+    }
+
+    pub fn advertise(name: &str, port: u16) {
+        withdraw();
+        let instance_name = HSTRING::from(format!("{}.{}", name, SERVICE_TYPE));
+        let host_name = HSTRING::from(format!("{}.local", name));
+        let instance = unsafe {
+            DnsServiceConstructInstance(
+                &instance_name,
+                &host_name,
+                None,
+                None,
+                port,
+                0,
+                0,
+                0,
+                std::ptr::null(),
+                std::ptr::null(),
+            )
+        };
+        if instance.is_null() {
+            return;
+        }
+        let status = unsafe { DnsServiceRegister(&register_request(instance), None) };
+        if status != DNS_REQUEST_PENDING as u32 {
+            tracing::warn!(status, "failed to advertise over mdns");
+            unsafe { DnsServiceFreeInstance(instance) };
+            return;
+        }
+        tracing::info!(name, port, "advertising over mdns");
+        *REGISTRATION.lock().unwrap() = Some(Registration(instance));
+    }
+
+    pub fn withdraw() {
+        if let Some(Registration(instance)) = REGISTRATION.lock().unwrap().take() {
+            unsafe {
+                DnsServiceDeRegister(&register_request(instance), None);
+                DnsServiceFreeInstance(instance);
+            }
+        }
+    }
+
+    fn read(text: windows::core::PWSTR) -> String {
+        unsafe { text.to_string() }.unwrap_or_default()
+    }
+
+    // 每次回调是一个实例的 PTR、SRV、A 等记录组成的链表
+    unsafe extern "system" fn browsed(status: u32, _context: *const c_void, records: *const DNS_RECORDW) {
+        if status == 0 {
+            let mut found = DiscoveredInstance::default();
+            let mut record = records;
+            while let Some(r) = record.as_ref() {
+                match r.wType {
+                    t if t == DNS_TYPE_PTR.0 => {
+                        let name = read(r.Data.PTR.pNameHost);
+                        found.name = name.trim_end_matches(&format!(".{}", SERVICE_TYPE)).to_string();
+                    }
+                    t if t == DNS_TYPE_SRV.0 => {
+                        found.host = read(r.Data.SRV.pNameTarget);
+                        found.port = r.Data.SRV.wPort;
+                    }
+                    t if t == DNS_TYPE_A.0 => {
+                        found.address = Some(IpAddr::V4(Ipv4Addr::from(r.Data.A.IpAddress.to_ne_bytes())));
+                    }
+                    _ => {}
+                }
+                record = r.pNext;
+            }
+            if found.port != 0 {
+                let mut all = FOUND.lock().unwrap();
+                all.retain(|f| f.name != found.name);
+                all.push(found);
+            }
+        }
+        if !records.is_null() {
+            DnsFree(Some(records as *const c_void), DnsFreeRecordList);
+        }
+    }
+
+    pub fn browse(duration: Duration) -> Vec<DiscoveredInstance> {
+        FOUND.lock().unwrap().clear();
+        let query = HSTRING::from(SERVICE_TYPE);
+        let request = DNS_SERVICE_BROWSE_REQUEST {
+            Version: DNS_QUERY_REQUEST_VERSION1.0,
+            InterfaceIndex: 0,
+            QueryName: PCWSTR(query.as_ptr()),
+            Anonymous: DNS_SERVICE_BROWSE_REQUEST_0 {
+                pBrowseCallback: Some(browsed),
+            },
+            pQueryContext: std::ptr::null_mut(),
+        };
+        let mut cancel = DNS_SERVICE_CANCEL::default();
+        if unsafe { DnsServiceBrowse(&request, &mut cancel) } != DNS_REQUEST_PENDING {
+            return Vec::new();
+        }
+        std::thread::sleep(duration);
+        unsafe {
+            DnsServiceBrowseCancel(&cancel);
+        }
+        // 本机的广播也会被发现
+        let own = super::instance_name();
+        let mut found = FOUND.lock().unwrap().clone();
+        found.retain(|f| !f.name.eq_ignore_ascii_case(&own));
+        found
+    }
+}
+
+// 其他平台（包括手机端）没有系统 DNS-SD 接口，直接向组播地址发送 PTR 查询
+// 从临时端口发出的查询按 RFC 6762 会收到单播回应，不需要占用 5353 端口
+#[cfg(not(windows))]
+mod imp {
+    use super::{DiscoveredInstance, SERVICE_TYPE};
+    use std::collections::HashMap;
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+    use std::time::{Duration, Instant};
+
+    const MDNS_ADDR: (Ipv4Addr, u16) = (Ipv4Addr::new(224, 0, 0, 251), 5353);
+    const TYPE_A: u16 = 1;
+    const TYPE_PTR: u16 = 12;
+    const TYPE_SRV: u16 = 33;
+
+    #[cfg(desktop)]
+    pub fn advertise(_name: &str, _port: u16) {}
+
+    #[cfg(desktop)]
+    pub fn withdraw() {}
+
+    fn query() -> Vec<u8> {
+        // 报头：id 0，标志 0，一个问题
+        let mut packet = vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+        for label in SERVICE_TYPE.split('.') {
+            packet.push(label.len() as u8);
+            packet.extend_from_slice(label.as_bytes());
+        }
+        packet.push(0);
+        packet.extend_from_slice(&TYPE_PTR.to_be_bytes());
+        packet.extend_from_slice(&1u16.to_be_bytes());
+        packet
+    }
+
+    // 读取可能带压缩指针的域名，pos 移到域名之后
+    fn read_name(packet: &[u8], pos: &mut usize) -> Option<String> {
+        let mut labels = Vec::new();
+        let mut cursor = *pos;
+        let mut jumped = false;
+        // 防止指针成环
+        for _ in 0..64 {
+            let len = *packet.get(cursor)? as usize;
+            if len == 0 {
+                if !jumped {
+                    *pos = cursor + 1;
+                }
+                return Some(labels.join("."));
+            }
+            if len & 0xC0 == 0xC0 {
+                let target = ((len & 0x3F) << 8) | *packet.get(cursor + 1)? as usize;
+                if !jumped {
+                    *pos = cursor + 2;
+                }
+                jumped = true;
+                cursor = target;
+                continue;
+            }
+            let label = packet.get(cursor + 1..cursor + 1 + len)?;
+            labels.push(String::from_utf8_lossy(label).into_owned());
+            cursor += 1 + len;
+        }
+        None
+    }
+
+    fn read_u16(packet: &[u8], pos: usize) -> Option<u16> {
+        Some(u16::from_be_bytes([*packet.get(pos)?, *packet.get(pos + 1)?]))
+    }
+
+    fn parse(packet: &[u8], source: IpAddr) -> Option<Vec<DiscoveredInstance>> {
+        let questions = read_u16(packet, 4)?;
+        let records = read_u16(packet, 6)? as usize + read_u16(packet, 8)? as usize + read_u16(packet, 10)? as usize;
+        let mut pos = 12;
+        for _ in 0..questions {
+            read_name(packet, &mut pos)?;
+            pos += 4;
+        }
+
+        let mut instances = Vec::new();
+        let mut services = HashMap::new();
+        let mut addresses = HashMap::new();
+        for _ in 0..records {
+            let name = read_name(packet, &mut pos)?;
+            let kind = read_u16(packet, pos)?;
+            let length = read_u16(packet, pos + 8)? as usize;
+            let data = pos + 10;
+            packet.get(data..data + length)?;
+            match kind {
+                TYPE_PTR if name.eq_ignore_ascii_case(SERVICE_TYPE) => {
+                    let mut cursor = data;
+                    instances.push(read_name(packet, &mut cursor)?);
+                }
+                TYPE_SRV => {
+                    let port = read_u16(packet, data + 4)?;
+                    let target = read_name(packet, &mut (data + 6))?;
+                    services.insert(name.to_lowercase(), (target, port));
+                }
+                TYPE_A if length == 4 => {
+                    let ip = Ipv4Addr::new(packet[data], packet[data + 1], packet[data + 2], packet[data + 3]);
+                    addresses.insert(name.to_lowercase(), IpAddr::V4(ip));
+                }
+                _ => {}
+            }
+            pos = data + length;
+        }
+
+        let suffix = format!(".{}", SERVICE_TYPE);
+        Some(
+            instances
+                .into_iter()
+                .filter_map(|instance| {
+                    let (host, port) = services.get(&instance.to_lowercase())?.clone();
+                    Some(DiscoveredInstance {
+                        name: instance.trim_end_matches(&suffix).to_string(),
+                        address: addresses.get(&host.to_lowercase()).copied().or(Some(source)),
+                        host,
+                        port,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    pub fn browse(duration: Duration) -> Vec<DiscoveredInstance> {
+        let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)) {
+            Ok(socket) => socket,
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to open mdns socket");
+                return Vec::new();
+            }
+        };
+        if let Err(e) = socket.send_to(&query(), SocketAddr::from(MDNS_ADDR)) {
+            tracing::warn!(error = %e, "failed to send mdns query");
+            return Vec::new();
+        }
+
+        let own = super::instance_name();
+        let mut found: Vec<DiscoveredInstance> = Vec::new();
+        let deadline = Instant::now() + duration;
+        let mut buffer = [0u8; 9000];
+        while let Some(left) = deadline.checked_duration_since(Instant::now()).filter(|d| !d.is_zero()) {
+            let _ = socket.set_read_timeout(Some(left));
+            let Ok((len, from)) = socket.recv_from(&mut buffer) else {
+                break;
+            };
+            for instance in parse(&buffer[..len], from.ip()).unwrap_or_default() {
+                if instance.name.eq_ignore_ascii_case(&own) {
+                    continue;
+                }
+                found.retain(|f| f.name != instance.name);
+                found.push(instance);
+            }
+        }
+        found
+    }
+}
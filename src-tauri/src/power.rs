@@ -0,0 +1,140 @@
+use crate::error::AutoShutdownError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerAction {
+    Shutdown,
+    // 启用快速启动时普通关机实际会休眠内核会话，完全关机让下次开机是一次干净的启动
+    FullShutdown,
+    Restart,
+    Sleep,
+    Hibernate,
+    Lock,
+    LogOff,
+    DisplayOff,
+}
+
+// 关闭显示器没有现成的命令，借助 PowerShell 广播 SC_MONITORPOWER
+const DISPLAY_OFF_SCRIPT: &str = "(Add-Type -MemberDefinition '[DllImport(\"user32.dll\")] public static extern int SendMessage(int h, int m, int w, int l);' -Name Display -PassThru)::SendMessage(0xFFFF, 0x0112, 0xF170, 2)";
+
+// rundll32 调用 SetSuspendState 时参数无法正确传递，启用休眠时会变成休眠，任务计划程序改用 PowerShell 调用
+const SLEEP_SCRIPT: &str = "(Add-Type -MemberDefinition '[DllImport(\"powrprof.dll\")] public static extern bool SetSuspendState(bool h, bool f, bool w);' -Name Suspend -PassThru)::SetSuspendState($false, $false, $false)";
+
+impl PowerAction {
+    // 多个操作同时触发时，数值大的更激进，优先执行
+    pub fn severity(self) -> u8 {
+        match self {
+            PowerAction::DisplayOff => 1,
+            PowerAction::Lock => 2,
+            PowerAction::Sleep => 3,
+            PowerAction::Hibernate => 4,
+            PowerAction::LogOff => 5,
+            PowerAction::Restart => 6,
+            PowerAction::Shutdown => 7,
+            PowerAction::FullShutdown => 8,
+        }
+    }
+
+    // 执行该操作所用的程序和参数；任务计划程序镜像按 Windows 命令行规则逐个加引号后拼接
+    pub fn command(self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            PowerAction::Shutdown => ("shutdown", &["/s", "/t", "0"]),
+            PowerAction::FullShutdown => ("shutdown", &["/s", "/full", "/t", "0"]),
+            PowerAction::Restart => ("shutdown", &["/r", "/t", "0"]),
+            PowerAction::Sleep => ("powershell", &["-NoProfile", "-WindowStyle", "Hidden", "-Command", SLEEP_SCRIPT]),
+            PowerAction::Hibernate => ("shutdown", &["/h"]),
+            PowerAction::Lock => ("rundll32.exe", &["user32.dll,LockWorkStation"]),
+            PowerAction::LogOff => ("shutdown", &["/l"]),
+            PowerAction::DisplayOff => ("powershell", &["-NoProfile", "-WindowStyle", "Hidden", "-Command", DISPLAY_OFF_SCRIPT]),
+        }
+    }
+
+    pub fn execute(self) -> Result<(), AutoShutdownError> {
+        #[cfg(target_os = "windows")]
+        {
+            if let PowerAction::Sleep | PowerAction::Hibernate = self {
+                return suspend(self == PowerAction::Hibernate);
+            }
+            let (program, args) = self.command();
+            tracing::info!(action = ?self, "executing power action");
+            std::process::Command::new(program)
+                .args(args)
+                .spawn()
+                .map(|_| ())
+                .map_err(|e| {
+                    tracing::error!(action = ?self, error = %e, "power action failed");
+                    e.into()
+                })
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            Err(AutoShutdownError::Unsupported(format!("{:?} is only supported on Windows", self)))
+        }
+    }
+}
+
+// 直接调用 SetSuspendState，被拒绝时返回系统错误；成功时在唤醒后才返回
+#[cfg(target_os = "windows")]
+fn suspend(hibernate: bool) -> Result<(), AutoShutdownError> {
+    use windows::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_PRIVILEGE_NOT_HELD};
+    use windows::Win32::System::Power::SetSuspendState;
+
+    tracing::info!(hibernate, "requesting system suspend");
+    let accepted = unsafe { SetSuspendState(hibernate, false, false) };
+    if accepted.as_bool() {
+        return Ok(());
+    }
+    let e = windows::core::Error::from_win32();
+    tracing::error!(hibernate, error = %e, "suspend request rejected");
+    let message = format!("{} request was rejected: {}", if hibernate { "Hibernate" } else { "Sleep" }, e.message());
+    if e.code() == ERROR_ACCESS_DENIED.to_hresult() || e.code() == ERROR_PRIVILEGE_NOT_HELD.to_hresult() {
+        return Err(AutoShutdownError::NeedsAdmin(message));
+    }
+    Err(AutoShutdownError::Failed(message))
+}
+
+// 本机支持的电源状态，界面据此禁用无法执行的操作
+#[derive(Serialize, Clone, Copy, Debug, Default)]
+pub struct PowerCapabilities {
+    // 传统睡眠（S1-S3）
+    pub sleep: bool,
+    pub hibernate: bool,
+    // 混合睡眠需要同时支持睡眠和休眠文件
+    pub hybrid_sleep: bool,
+    pub fast_startup: bool,
+    // 现代待机（S0 低功耗空闲）的机器通常不支持传统睡眠
+    pub modern_standby: bool,
+}
+
+#[cfg(target_os = "windows")]
+pub fn capabilities() -> Result<PowerCapabilities, String> {
+    use windows::Win32::System::Power::{GetPwrCapabilities, SYSTEM_POWER_CAPABILITIES};
+
+    let mut caps = SYSTEM_POWER_CAPABILITIES::default();
+    if !unsafe { GetPwrCapabilities(&mut caps) }.as_bool() {
+        return Err(format!(
+            "Failed to query power capabilities: {}",
+            windows::core::Error::from_win32().message()
+        ));
+    }
+    let sleep = caps.SystemS1.as_bool() || caps.SystemS2.as_bool() || caps.SystemS3.as_bool();
+    let hibernate = caps.SystemS4.as_bool() && caps.HiberFilePresent.as_bool();
+    Ok(PowerCapabilities {
+        sleep,
+        hibernate,
+        hybrid_sleep: sleep && caps.HiberFilePresent.as_bool(),
+        fast_startup: caps.Hiberboot.as_bool() && caps.HiberFilePresent.as_bool(),
+        modern_standby: caps.AoAc.as_bool(),
+    })
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn capabilities() -> Result<PowerCapabilities, String> {
+    Ok(PowerCapabilities::default())
+}
+
+#[tauri::command]
+pub fn get_power_capabilities() -> Result<PowerCapabilities, AutoShutdownError> {
+    capabilities().map_err(AutoShutdownError::Failed)
+}
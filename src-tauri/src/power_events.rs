@@ -0,0 +1,70 @@
+use tauri::AppHandle;
+
+// 订阅系统睡眠和唤醒通知，转发为前端事件并在唤醒后执行恢复步骤
+#[cfg(windows)]
+mod imp {
+    use crate::audio;
+    use crate::disconnect;
+    use std::ffi::c_void;
+    use std::sync::OnceLock;
+    use tauri::{AppHandle, Emitter};
+    use windows::Win32::Foundation::{ERROR_SUCCESS, HANDLE};
+    use windows::Win32::System::Power::{PowerRegisterSuspendResumeNotification, DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS};
+    use windows::Win32::UI::WindowsAndMessaging::{DEVICE_NOTIFY_CALLBACK, PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND};
+
+    static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+    // 系统保存该指针直到取消订阅，订阅在应用运行期间一直有效
+    static mut PARAMS: DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS = DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS {
+        Callback: Some(on_power_event),
+        Context: std::ptr::null_mut(),
+    };
+
+    unsafe extern "system" fn on_power_event(_context: *const c_void, event: u32, _setting: *const c_void) -> u32 {
+        let Some(app) = APP_HANDLE.get() else {
+            return 0;
+        };
+        match event {
+            PBT_APMSUSPEND => {
+                tracing::info!("system suspending");
+                let _ = app.emit("system-suspending", ());
+            }
+            // 不论是否有用户操作唤醒都会收到
+            PBT_APMRESUMEAUTOMATIC => {
+                tracing::info!("system resumed");
+                let _ = app.emit("system-resumed", ());
+                // 回调中不宜执行耗时操作
+                let app = app.clone();
+                std::thread::spawn(move || {
+                    audio::restore_after_action(&app);
+                    disconnect::restore();
+                });
+            }
+            _ => {}
+        }
+        0
+    }
+
+    pub fn subscribe(app: AppHandle) {
+        if APP_HANDLE.set(app).is_err() {
+            return;
+        }
+        let mut handle: *mut c_void = std::ptr::null_mut();
+        let result = unsafe {
+            PowerRegisterSuspendResumeNotification(
+                DEVICE_NOTIFY_CALLBACK,
+                HANDLE(std::ptr::addr_of_mut!(PARAMS) as *mut c_void),
+                &mut handle,
+            )
+        };
+        if result != ERROR_SUCCESS {
+            tracing::warn!(code = result.0, "failed to subscribe to power notifications");
+        }
+    }
+}
+
+pub fn start(app: AppHandle) {
+    #[cfg(windows)]
+    imp::subscribe(app);
+    #[cfg(not(windows))]
+    let _ = app;
+}
@@ -0,0 +1,138 @@
+use crate::audit::{ActionSource, AuditAction};
+use crate::error::AutoShutdownError;
+use crate::executor;
+use serde::Serialize;
+use tauri::AppHandle;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PowerPlan {
+    pub guid: String,
+    pub name: String,
+    pub active: bool,
+}
+
+// 接受 powercfg /l 显示的格式，大小写和花括号均可
+fn parse_guid(guid: &str) -> Result<u128, String> {
+    let hex: String = guid.trim_matches(|c| c == '{' || c == '}').chars().filter(|c| *c != '-').collect();
+    if hex.len() != 32 {
+        return Err(format!("Invalid power plan GUID: {}", guid));
+    }
+    u128::from_str_radix(&hex, 16).map_err(|_| format!("Invalid power plan GUID: {}", guid))
+}
+
+// 使用电源管理接口而不是解析 powercfg 输出，计划名称不受控制台代码页影响
+#[cfg(windows)]
+mod imp {
+    use super::{parse_guid, PowerPlan};
+    use windows::core::GUID;
+    use windows::Win32::Foundation::{LocalFree, ERROR_NO_MORE_ITEMS, ERROR_SUCCESS, HLOCAL};
+    use windows::Win32::System::Power::{
+        PowerEnumerate, PowerGetActiveScheme, PowerReadFriendlyName, PowerSetActiveScheme, ACCESS_SCHEME,
+    };
+    use windows::Win32::System::Registry::HKEY;
+
+    fn format_guid(guid: &GUID) -> String {
+        format!("{:?}", guid).to_lowercase()
+    }
+
+    fn active() -> Option<GUID> {
+        let mut ptr: *mut GUID = std::ptr::null_mut();
+        unsafe {
+            if PowerGetActiveScheme(HKEY::default(), &mut ptr) != ERROR_SUCCESS || ptr.is_null() {
+                return None;
+            }
+            let guid = *ptr;
+            let _ = LocalFree(HLOCAL(ptr as _));
+            Some(guid)
+        }
+    }
+
+    fn friendly_name(guid: &GUID) -> String {
+        let mut size = 0u32;
+        unsafe {
+            let _ = PowerReadFriendlyName(HKEY::default(), Some(guid), None, None, None, &mut size);
+            let mut buffer = vec![0u16; (size as usize).div_ceil(2)];
+            if PowerReadFriendlyName(
+                HKEY::default(),
+                Some(guid),
+                None,
+                None,
+                Some(buffer.as_mut_ptr() as *mut u8),
+                &mut size,
+            ) != ERROR_SUCCESS
+            {
+                return format_guid(guid);
+            }
+            let end = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+            String::from_utf16_lossy(&buffer[..end])
+        }
+    }
+
+    pub fn list() -> Result<Vec<PowerPlan>, String> {
+        let active = active();
+        let mut plans = Vec::new();
+        for index in 0.. {
+            let mut guid = GUID::zeroed();
+            let mut size = std::mem::size_of::<GUID>() as u32;
+            let result = unsafe {
+                PowerEnumerate(
+                    HKEY::default(),
+                    None,
+                    None,
+                    ACCESS_SCHEME,
+                    index,
+                    Some(&mut guid as *mut GUID as *mut u8),
+                    &mut size,
+                )
+            };
+            if result == ERROR_NO_MORE_ITEMS {
+                break;
+            }
+            if result != ERROR_SUCCESS {
+                return Err(format!("Failed to enumerate power plans: {}", windows::core::Error::from(result.to_hresult()).message()));
+            }
+            plans.push(PowerPlan {
+                guid: format_guid(&guid),
+                name: friendly_name(&guid),
+                active: active == Some(guid),
+            });
+        }
+        Ok(plans)
+    }
+
+    pub fn activate(guid: &str) -> Result<(), String> {
+        let guid = GUID::from_u128(parse_guid(guid)?);
+        let result = unsafe { PowerSetActiveScheme(HKEY::default(), Some(&guid)) };
+        if result != ERROR_SUCCESS {
+            return Err(format!("Failed to switch power plan: {}", windows::core::Error::from(result.to_hresult()).message()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(windows))]
+mod imp {
+    use super::{parse_guid, PowerPlan};
+
+    pub fn list() -> Result<Vec<PowerPlan>, String> {
+        Ok(Vec::new())
+    }
+
+    pub fn activate(guid: &str) -> Result<(), String> {
+        parse_guid(guid)?;
+        Err("Power plans are only supported on Windows".into())
+    }
+}
+
+pub use imp::{activate, list};
+
+#[tauri::command]
+pub fn list_power_plans() -> Result<Vec<PowerPlan>, AutoShutdownError> {
+    Ok(list()?)
+}
+
+#[tauri::command]
+pub fn set_power_plan(app: AppHandle, guid: String) -> Result<(), AutoShutdownError> {
+    parse_guid(&guid)?;
+    executor::perform(&app, AuditAction::SetPowerPlan { guid }, ActionSource::User)
+}
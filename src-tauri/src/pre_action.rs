@@ -0,0 +1,115 @@
+use crate::audio;
+use crate::audit::{ActionSource, AuditAction};
+use crate::cleanup::CleanupOptions;
+use crate::disconnect::{self, NetworkDisconnectOptions};
+use crate::docker::{self, DockerStopOptions};
+use crate::executor;
+use crate::hooks::{self, HookScript};
+use crate::notify::{self, Notice, NoticeKind};
+use crate::services::{self, ServiceStop};
+use crate::soft_close::{self, SoftCloseOptions};
+use crate::unsaved::{self, UnsavedWorkOptions};
+use crate::users::{self, OtherUsersOptions};
+use crate::vms::{self, VmShutdownOptions};
+use crate::wsl;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+// 执行电源操作前的准备步骤，随计时器、日程和规则一起保存
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct PreActionOptions {
+    pub unsaved_work: UnsavedWorkOptions,
+    pub other_users: OtherUsersOptions,
+    pub hooks: Vec<HookScript>,
+    // 为空时不关闭应用，直接执行电源操作
+    pub soft_close: Option<SoftCloseOptions>,
+    pub cleanup: CleanupOptions,
+    // 操作前将系统静音，睡眠唤醒后自动恢复
+    pub mute_audio: bool,
+    pub disconnect: NetworkDisconnectOptions,
+    pub stop_services: Vec<ServiceStop>,
+    pub virtual_machines: VmShutdownOptions,
+    // 执行 wsl --shutdown 关闭所有发行版
+    pub shutdown_wsl: bool,
+    pub docker: DockerStopOptions,
+}
+
+impl PreActionOptions {
+    pub fn is_empty(&self) -> bool {
+        self.unsaved_work == UnsavedWorkOptions::default()
+            && self.other_users == OtherUsersOptions::default()
+            && self.hooks.is_empty()
+            && self.soft_close.is_none()
+            && self.cleanup.is_empty()
+            && !self.mute_audio
+            && self.disconnect.is_empty()
+            && self.stop_services.is_empty()
+            && !self.virtual_machines.enabled
+            && !self.shutdown_wsl
+            && !self.docker.enabled
+    }
+}
+
+// 返回 Err 表示本次操作应当取消
+pub fn run(app: &AppHandle, options: &PreActionOptions, source: &ActionSource) -> Result<(), String> {
+    unsaved::check(app, &options.unsaved_work)?;
+    users::check(app, &options.other_users)?;
+    for hook in &options.hooks {
+        let result = hooks::run(app, hook);
+        let failed = match &result {
+            Ok(result) => !result.succeeded(),
+            Err(e) => {
+                tracing::warn!(path = %hook.path, error = %e, "hook script failed to start");
+                true
+            }
+        };
+        if failed && hook.abort_on_failure {
+            return Err(format!("Aborted by hook script {}", hook.path));
+        }
+    }
+    // 在关闭应用之前处理，虚拟机运行时关闭 VirtualBox 等管理界面会弹出确认
+    let vms_running = vms::stop_all(&options.virtual_machines)?;
+    if !vms_running.is_empty() {
+        notify::dispatch(
+            app,
+            Notice::new("AutoShutdown", format!("Virtual machines did not stop: {}", vms_running.join(", ")))
+                .with_kind(NoticeKind::ActionFailed),
+        );
+    }
+    // Docker Desktop 的容器运行在 WSL 中，先于 WSL 停止
+    let containers_running = docker::stop_all(app, &options.docker, source)?;
+    if !containers_running.is_empty() {
+        notify::dispatch(
+            app,
+            Notice::new("AutoShutdown", format!("Containers did not stop: {}", containers_running.join(", ")))
+                .with_kind(NoticeKind::ActionFailed),
+        );
+    }
+    if options.shutdown_wsl {
+        if let Err(e) = wsl::shutdown() {
+            tracing::warn!(error = %e, "failed to shut down WSL");
+        }
+    }
+    if let Some(soft_close) = &options.soft_close {
+        soft_close::run(app, soft_close)?;
+    }
+    let still_running = services::stop_all(&options.stop_services)?;
+    if !still_running.is_empty() {
+        notify::dispatch(
+            app,
+            Notice::new("AutoShutdown", format!("Services did not stop: {}", still_running.join(", ")))
+                .with_kind(NoticeKind::ActionFailed),
+        );
+    }
+    // 清理失败不影响后续操作，结果记录在操作历史中
+    for task in options.cleanup.tasks() {
+        let _ = executor::perform_with(app, AuditAction::Cleanup { task }, source.clone(), false);
+    }
+    // 放在最后，前面的步骤取消操作时不会留下静音或断网
+    if options.mute_audio {
+        audio::mute_for_action(app);
+    }
+    disconnect::run(&options.disconnect);
+    Ok(())
+}
@@ -0,0 +1,51 @@
+// 本地打印机和已连接的网络打印机上排队的打印任务数
+#[cfg(windows)]
+pub fn pending_jobs() -> usize {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::Graphics::Printing::{
+        ClosePrinter, EnumJobsW, EnumPrintersW, OpenPrinterW, PRINTER_ENUM_CONNECTIONS, PRINTER_ENUM_LOCAL,
+        PRINTER_INFO_4W,
+    };
+
+    // 第一次调用取得所需缓冲区大小，第二次取得数据
+    let flags = PRINTER_ENUM_LOCAL | PRINTER_ENUM_CONNECTIONS;
+    let (mut needed, mut count) = (0u32, 0u32);
+    unsafe {
+        let _ = EnumPrintersW(flags, PCWSTR::null(), 4, None, &mut needed, &mut count);
+    }
+    if needed == 0 {
+        return 0;
+    }
+    let mut buffer = vec![0u8; needed as usize];
+    if unsafe { EnumPrintersW(flags, PCWSTR::null(), 4, Some(&mut buffer), &mut needed, &mut count) }.is_err() {
+        return 0;
+    }
+    let printers =
+        unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const PRINTER_INFO_4W, count as usize) };
+
+    printers
+        .iter()
+        .map(|printer| unsafe {
+            let mut handle = HANDLE::default();
+            if OpenPrinterW(PCWSTR(printer.pPrinterName.0), &mut handle, None).is_err() {
+                return 0;
+            }
+            let (mut needed, mut jobs) = (0u32, 0u32);
+            let _ = EnumJobsW(handle, 0, u32::MAX, 1, None, &mut needed, &mut jobs);
+            if needed > 0 {
+                let mut buffer = vec![0u8; needed as usize];
+                if EnumJobsW(handle, 0, u32::MAX, 1, Some(&mut buffer), &mut needed, &mut jobs).is_err() {
+                    jobs = 0;
+                }
+            }
+            let _ = ClosePrinter(handle);
+            jobs as usize
+        })
+        .sum()
+}
+
+#[cfg(not(windows))]
+pub fn pending_jobs() -> usize {
+    0
+}
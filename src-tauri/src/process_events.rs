@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+// 保留的最近退出记录条数，供崩溃循环规则统计
+const RECENT_EXITS: usize = 256;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProcessEvent {
+    pub pid: u32,
+    pub name: String,
+    // 只有管理员权限下的退出通知带有退出码
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<u32>,
+}
+
+// 进程启动/退出通知的状态，计时引擎据此决定何时刷新进程列表
+#[derive(Default)]
+pub struct ProcessEvents {
+    // 订阅正常工作时为 true，否则计时引擎退回每秒刷新
+    live: AtomicBool,
+    // 每收到一次退出通知加一
+    exits: AtomicU64,
+    recent_exits: Mutex<VecDeque<(Instant, ProcessEvent)>>,
+}
+
+impl ProcessEvents {
+    pub fn live(&self) -> bool {
+        self.live.load(Ordering::SeqCst)
+    }
+
+    pub fn exits(&self) -> u64 {
+        self.exits.load(Ordering::SeqCst)
+    }
+
+    // 最近 window 之内的退出通知
+    pub fn recent_exits(&self, window: Duration) -> Vec<(Instant, ProcessEvent)> {
+        let recent = self.recent_exits.lock().unwrap();
+        recent.iter().filter(|(at, _)| at.elapsed() < window).cloned().collect()
+    }
+
+    #[cfg_attr(not(windows), allow(dead_code))]
+    fn record_exit(&self, event: ProcessEvent) {
+        self.exits.fetch_add(1, Ordering::SeqCst);
+        let mut recent = self.recent_exits.lock().unwrap();
+        if recent.len() >= RECENT_EXITS {
+            recent.pop_front();
+        }
+        recent.push_back((Instant::now(), event));
+    }
+}
+
+// 通过常驻的 PowerShell 订阅 WMI 事件，每个事件输出一行 JSON
+// 管理员权限下使用即时的 Win32_Process*Trace，否则使用每秒轮询的实例事件
+#[cfg(windows)]
+mod imp {
+    use super::{ProcessEvent, ProcessEvents};
+    use crate::cmd;
+    use crate::elevation;
+    use crate::jobs::Job;
+    use serde::Deserialize;
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::sync::atomic::Ordering;
+    use std::thread;
+    use std::time::Duration;
+    use tauri::{AppHandle, Emitter, Manager};
+
+    const RESTART_DELAY: Duration = Duration::from_secs(10);
+
+    const TRACE_SCRIPT: &str = "$ErrorActionPreference = 'Stop'; Register-CimIndicationEvent -ClassName Win32_ProcessStartTrace -SourceIdentifier s; Register-CimIndicationEvent -ClassName Win32_ProcessStopTrace -SourceIdentifier e; while ($true) { $ev = Wait-Event; Remove-Event -EventIdentifier $ev.EventIdentifier; $p = $ev.SourceEventArgs.NewEvent; [Console]::Out.WriteLine((@{ kind = $ev.SourceIdentifier; pid = [uint32]$p.ProcessID; name = [string]$p.ProcessName; exit_code = $p.ExitStatus } | ConvertTo-Json -Compress)) }";
+
+    const INSTANCE_SCRIPT: &str = r#"$ErrorActionPreference = 'Stop'; Register-CimIndicationEvent -Query "SELECT * FROM __InstanceCreationEvent WITHIN 1 WHERE TargetInstance ISA 'Win32_Process'" -SourceIdentifier s; Register-CimIndicationEvent -Query "SELECT * FROM __InstanceDeletionEvent WITHIN 1 WHERE TargetInstance ISA 'Win32_Process'" -SourceIdentifier e; while ($true) { $ev = Wait-Event; Remove-Event -EventIdentifier $ev.EventIdentifier; $p = $ev.SourceEventArgs.NewEvent.TargetInstance; [Console]::Out.WriteLine((@{ kind = $ev.SourceIdentifier; pid = [uint32]$p.ProcessId; name = [string]$p.Name } | ConvertTo-Json -Compress)) }"#;
+
+    #[derive(Deserialize)]
+    struct Line {
+        kind: String,
+        #[serde(flatten)]
+        event: ProcessEvent,
+    }
+
+    // 阻塞直到 PowerShell 退出
+    fn subscribe(app: &AppHandle) -> Result<(), String> {
+        let script = if elevation::running_elevated() { TRACE_SCRIPT } else { INSTANCE_SCRIPT };
+        let mut child = cmd::hidden("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", script])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to start process watcher: {}", e))?;
+        // 本应用退出时随作业句柄关闭一起结束，不留下常驻的 PowerShell
+        let _job = Job::new(true)
+            .and_then(|job| job.assign(child.id()).map(|_| job))
+            .inspect_err(|e| tracing::warn!(error = %e, "process watcher runs without a job object"))
+            .ok();
+        let stdout = child.stdout.take().ok_or("Process watcher has no output")?;
+        let state = app.state::<ProcessEvents>();
+        state.live.store(true, Ordering::SeqCst);
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let Ok(line) = serde_json::from_str::<Line>(&line) else {
+                continue;
+            };
+            if line.kind == "e" {
+                let _ = app.emit("process-exited", &line.event);
+                state.record_exit(line.event);
+            } else {
+                let _ = app.emit("process-started", &line.event);
+            }
+        }
+        state.live.store(false, Ordering::SeqCst);
+        let status = child.wait().map_err(|e| e.to_string())?;
+        Err(format!("Process watcher exited with {}", status))
+    }
+
+    pub fn start(app: AppHandle) {
+        thread::spawn(move || loop {
+            if let Err(e) = subscribe(&app) {
+                tracing::warn!(error = %e, "process event subscription stopped, falling back to polling");
+            }
+            thread::sleep(RESTART_DELAY);
+        });
+    }
+}
+
+pub fn start(app: AppHandle) {
+    #[cfg(windows)]
+    imp::start(app);
+    #[cfg(not(windows))]
+    let _ = app;
+}
@@ -0,0 +1,179 @@
+use crate::error::AutoShutdownError;
+use crate::notify::NotificationRoutes;
+use crate::rules::{Rule, RuleStore};
+use crate::settings::SettingsStore;
+use crate::storage::JsonStore;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::{AppHandle, Emitter, State};
+
+// 导出文件的格式版本，结构不兼容时递增
+const EXPORT_VERSION: u32 = 1;
+
+// 一组可整体切换的配置，如“夜间下载”“儿童电脑”
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Profile {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    #[serde(default)]
+    pub excluded_apps: Vec<String>,
+    #[serde(default)]
+    pub notifications: NotificationRoutes,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct Profiles {
+    active: Option<String>,
+    profiles: Vec<Profile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProfileExport {
+    version: u32,
+    profile: Profile,
+}
+
+#[derive(Serialize, Clone)]
+struct ProfileChanged<'a> {
+    id: &'a str,
+    name: &'a str,
+}
+
+pub struct ProfileStore(JsonStore<Profiles>);
+
+impl ProfileStore {
+    pub fn load(app: &AppHandle) -> Self {
+        ProfileStore(JsonStore::load(app, "profiles.json"))
+    }
+
+    pub fn active(&self) -> Option<String> {
+        self.0.get().active
+    }
+
+    fn find(&self, id: &str) -> Result<Profile, AutoShutdownError> {
+        self.0
+            .get()
+            .profiles
+            .into_iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| AutoShutdownError::NotFound(format!("Profile {} not found", id)))
+    }
+}
+
+// 当前生效的规则、排除应用和通知渠道
+fn snapshot(rules: &RuleStore, settings: &SettingsStore, id: String, name: String) -> Profile {
+    let current = settings.get();
+    Profile {
+        id,
+        name,
+        rules: rules.all(),
+        excluded_apps: current.excluded_apps,
+        notifications: current.notifications,
+    }
+}
+
+#[derive(Serialize)]
+pub struct ProfileList {
+    active: Option<String>,
+    profiles: Vec<Profile>,
+}
+
+#[tauri::command]
+pub fn list_profiles(store: State<'_, ProfileStore>) -> ProfileList {
+    let Profiles { active, profiles } = store.0.get();
+    ProfileList { active, profiles }
+}
+
+// 以当前配置为内容新建配置方案
+#[tauri::command]
+pub fn create_profile(
+    store: State<'_, ProfileStore>,
+    rules: State<'_, RuleStore>,
+    settings: State<'_, SettingsStore>,
+    name: String,
+) -> Result<Profile, AutoShutdownError> {
+    let profile = snapshot(&rules, &settings, uuid::Uuid::new_v4().to_string(), name);
+    store.0.update(|p| p.profiles.push(profile.clone()))?;
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn delete_profile(store: State<'_, ProfileStore>, id: String) -> Result<(), AutoShutdownError> {
+    store.0.update(|p| {
+        p.profiles.retain(|profile| profile.id != id);
+        if p.active.as_deref() == Some(id.as_str()) {
+            p.active = None;
+        }
+    })
+    .map_err(Into::into)
+}
+
+// 切换前把当前配置存回原方案，避免切换时丢失修改
+#[tauri::command]
+pub fn switch_profile(
+    app: AppHandle,
+    store: State<'_, ProfileStore>,
+    rules: State<'_, RuleStore>,
+    settings: State<'_, SettingsStore>,
+    id: String,
+) -> Result<Profile, AutoShutdownError> {
+    let target = store.find(&id)?;
+    for rule in &target.rules {
+        rule.validate()?;
+    }
+    if let Some(active) = store.0.get().active.and_then(|a| store.find(&a).ok()) {
+        let current = snapshot(&rules, &settings, active.id.clone(), active.name);
+        store.0.update(|p| {
+            if let Some(profile) = p.profiles.iter_mut().find(|p| p.id == current.id) {
+                *profile = current;
+            }
+        })?;
+    }
+
+    rules.replace_all(&app, target.rules.clone())?;
+    settings.update(&app, |s| {
+        s.excluded_apps = target.excluded_apps.clone();
+        s.notifications = target.notifications.clone();
+    })?;
+    store.0.update(|p| p.active = Some(target.id.clone()))?;
+    tracing::info!(id = %target.id, name = %target.name, "profile activated");
+    let _ = app.emit(
+        "profile-changed",
+        ProfileChanged {
+            id: &target.id,
+            name: &target.name,
+        },
+    );
+    Ok(target)
+}
+
+#[tauri::command]
+pub fn export_profile(store: State<'_, ProfileStore>, id: String, path: String) -> Result<(), AutoShutdownError> {
+    let export = ProfileExport {
+        version: EXPORT_VERSION,
+        profile: store.find(&id)?,
+    };
+    let json = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e)).map_err(Into::into)
+}
+
+// 导入的方案总是作为新方案加入，不覆盖同名方案
+#[tauri::command]
+pub fn import_profile(store: State<'_, ProfileStore>, path: String) -> Result<Profile, AutoShutdownError> {
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let export: ProfileExport = serde_json::from_str(&json).map_err(|e| AutoShutdownError::InvalidInput(format!("Invalid profile file: {}", e)))?;
+    if export.version > EXPORT_VERSION {
+        return Err(AutoShutdownError::InvalidInput(format!("Profile file version {} is newer than this app supports", export.version)));
+    }
+    let mut profile = export.profile;
+    for rule in &profile.rules {
+        rule.validate()?;
+    }
+    profile.id = uuid::Uuid::new_v4().to_string();
+    store.0.update(|p| p.profiles.push(profile.clone()))?;
+    Ok(profile)
+}
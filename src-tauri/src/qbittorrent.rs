@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+// 下载完成后处于做种或已暂停做种的状态
+const COMPLETED_STATES: &[&str] = &[
+    "uploading",
+    "stalledUP",
+    "pausedUP",
+    "stoppedUP",
+    "queuedUP",
+    "forcedUP",
+    "checkingUP",
+];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct QbittorrentConfig {
+    #[serde(default = "default_host")]
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+}
+
+fn default_host() -> String {
+    "127.0.0.1".into()
+}
+
+fn default_port() -> u16 {
+    8080
+}
+
+// 设置会写入日志，不输出密码
+impl fmt::Debug for QbittorrentConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QbittorrentConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Torrent {
+    state: String,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TorrentSummary {
+    pub total: usize,
+    pub completed: usize,
+}
+
+impl TorrentSummary {
+    pub fn all_completed(&self) -> bool {
+        self.completed == self.total
+    }
+}
+
+struct Client {
+    base: String,
+    cookie: String,
+}
+
+impl Client {
+    // WebUI 用 SID cookie 保持登录，本机访问且关闭认证时也会返回 Ok
+    fn login(config: &QbittorrentConfig) -> Result<Client, String> {
+        let base = format!("http://{}:{}/api/v2", config.host, config.port);
+        let response = ureq::post(&format!("{}/auth/login", base))
+            .timeout(TIMEOUT)
+            .set("Referer", &format!("http://{}:{}", config.host, config.port))
+            .send_form(&[("username", &config.username), ("password", &config.password)])
+            .map_err(|e| e.to_string())?;
+        let cookie = response
+            .all("set-cookie")
+            .into_iter()
+            .find_map(|c| c.split(';').next().filter(|c| c.starts_with("SID=")))
+            .unwrap_or_default()
+            .to_string();
+        if response.into_string().map_err(|e| e.to_string())?.trim() == "Fails." {
+            return Err("qBittorrent login failed".into());
+        }
+        Ok(Client { base, cookie })
+    }
+
+    fn get(&self, path: &str) -> Result<ureq::Response, String> {
+        ureq::get(&format!("{}{}", self.base, path))
+            .timeout(TIMEOUT)
+            .set("Cookie", &self.cookie)
+            .call()
+            .map_err(|e| e.to_string())
+    }
+
+    fn post(&self, path: &str, form: &[(&str, &str)]) -> Result<(), String> {
+        ureq::post(&format!("{}{}", self.base, path))
+            .timeout(TIMEOUT)
+            .set("Cookie", &self.cookie)
+            .send_form(form)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+pub fn summary(config: &QbittorrentConfig) -> Result<TorrentSummary, String> {
+    let client = Client::login(config)?;
+    let torrents: Vec<Torrent> = client
+        .get("/torrents/info")?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+    Ok(TorrentSummary {
+        total: torrents.len(),
+        completed: torrents
+            .iter()
+            .filter(|t| COMPLETED_STATES.contains(&t.state.as_str()))
+            .count(),
+    })
+}
+
+// qBittorrent 5 将 pause 改名为 stop，先尝试新接口
+pub fn pause_all(config: &QbittorrentConfig) -> Result<(), String> {
+    let client = Client::login(config)?;
+    client
+        .post("/torrents/stop", &[("hashes", "all")])
+        .or_else(|_| client.post("/torrents/pause", &[("hashes", "all")]))
+}
@@ -0,0 +1,114 @@
+use crate::notify::{self, Notice};
+use crate::schedule::ScheduleStore;
+use crate::storage::JsonStore;
+use crate::timers::{ArmedTimer, TimerStore, TimerTrigger};
+use chrono::Local;
+use tauri::{AppHandle, Manager};
+
+const RUN_ONCE_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\RunOnce";
+const RUN_ONCE_VALUE: &str = "AutoShutdown";
+
+// 由本程序发起重启时尚未到期的计时，重启后重新启动
+pub struct PendingTimers(JsonStore<Vec<ArmedTimer>>);
+
+impl PendingTimers {
+    pub fn load(app: &AppHandle) -> Self {
+        PendingTimers(JsonStore::load(app, "pending_timers.json"))
+    }
+}
+
+// RunOnce 在用户下次登录时启动一次后自动删除，不影响是否开机自启的设置
+#[cfg(windows)]
+fn register() -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let command = format!("\"{}\"", exe.display());
+    crate::cmd::output("reg", &["add", RUN_ONCE_KEY, "/v", RUN_ONCE_VALUE, "/t", "REG_SZ", "/d", &command, "/f"]).map(|_| ())
+}
+
+#[cfg(not(windows))]
+fn register() -> Result<(), String> {
+    Err("Relaunching after restart is only supported on Windows".into())
+}
+
+#[cfg(windows)]
+fn unregister() {
+    let _ = crate::cmd::output("reg", &["delete", RUN_ONCE_KEY, "/v", RUN_ONCE_VALUE, "/f"]);
+}
+
+#[cfg(not(windows))]
+fn unregister() {}
+
+// 重启前保存计时并登记重启后启动，失败时只记录日志，不影响重启
+pub fn prepare(app: &AppHandle) {
+    let timers = app.state::<TimerStore>().all();
+    tracing::info!(timers = timers.len(), "preparing relaunch after restart");
+    if let Err(e) = app.state::<PendingTimers>().0.update(|t| *t = timers) {
+        tracing::warn!(error = %e, "failed to save pending timers");
+    }
+    if let Err(e) = register() {
+        tracing::warn!(error = %e, "failed to register relaunch after restart");
+    }
+}
+
+// 重启没有执行时撤销登记，避免下次登录时意外恢复旧的计时
+pub fn cancel(app: &AppHandle) {
+    unregister();
+    let _ = app.state::<PendingTimers>().0.update(|t| t.clear());
+}
+
+// 重启后进程 ID 已失效，只保留可按名称或命令行匹配的目标；已过期的时间不再执行
+fn revive(mut timer: ArmedTimer) -> Option<ArmedTimer> {
+    timer.spec.trigger = match timer.spec.trigger {
+        TimerTrigger::At { time } if time <= Local::now() => return None,
+        TimerTrigger::ProcessExit { name, cmdline, .. } if name.is_some() || cmdline.is_some() => {
+            TimerTrigger::ProcessExit { pid: None, name, cmdline }
+        }
+        TimerTrigger::ProcessExit { .. } => return None,
+        TimerTrigger::ProcessesExit { targets, mode } => {
+            let targets: Vec<_> = targets
+                .into_iter()
+                .map(|mut t| {
+                    t.pid = None;
+                    t
+                })
+                .filter(|t| !t.is_empty())
+                .collect();
+            if targets.is_empty() {
+                return None;
+            }
+            TimerTrigger::ProcessesExit { targets, mode }
+        }
+        other => other,
+    };
+    Some(timer)
+}
+
+// 启动时恢复重启前保存的计时，并提示用户日程和计时已重新生效
+pub fn restore(app: &AppHandle) {
+    let pending = app.state::<PendingTimers>().0.get();
+    if pending.is_empty() {
+        return;
+    }
+    let _ = app.state::<PendingTimers>().0.update(|t| t.clear());
+    let total = pending.len();
+    let store = app.state::<TimerStore>();
+    let restored = pending
+        .into_iter()
+        .filter_map(revive)
+        .filter(|timer| match store.arm(Some(timer.id.clone()), timer.spec.clone()) {
+            Ok(_) => true,
+            Err(e) => {
+                tracing::warn!(id = %timer.id, error = %e, "failed to restore timer");
+                false
+            }
+        })
+        .count();
+    tracing::info!(restored, expired = total - restored, "restored timers after restart");
+
+    let schedules = app.state::<ScheduleStore>().all().iter().filter(|s| s.enabled).count();
+    let mut body = format!("Restored {} timer(s) and {} schedule(s) after restart.", restored, schedules);
+    if restored < total {
+        body.push_str(&format!(" {} timer(s) expired during the restart and were skipped.", total - restored));
+    }
+    notify::dispatch(app, Notice::new("AutoShutdown", body));
+}
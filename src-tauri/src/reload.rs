@@ -0,0 +1,59 @@
+use crate::api;
+use crate::rules::RuleStore;
+use crate::schedule::ScheduleStore;
+use crate::settings::SettingsStore;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+fn reload(name: &'static str, changed: Result<bool, String>, reloaded: &mut Vec<&'static str>) -> bool {
+    match changed {
+        Ok(true) => {
+            tracing::info!(file = name, "config file changed on disk, reloaded");
+            reloaded.push(name);
+            true
+        }
+        Ok(false) => false,
+        Err(e) => {
+            tracing::warn!(file = name, error = %e, "failed to reload config file");
+            false
+        }
+    }
+}
+
+// 配置文件被手动编辑或由同步工具更新时重新加载
+pub fn start_watcher(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(CHECK_INTERVAL);
+        let mut reloaded = Vec::new();
+
+        let settings = app.state::<SettingsStore>();
+        if reload("settings.json", settings.reload_if_changed(), &mut reloaded) {
+            let settings = settings.get();
+            if let Err(e) = api::apply(&app, &settings.api) {
+                tracing::warn!(error = %e, "failed to apply reloaded api settings");
+            }
+            let _ = app.emit("settings-changed", &settings);
+        }
+
+        let rules = app.state::<RuleStore>();
+        if reload("rules.json", rules.reload_if_changed(), &mut reloaded) {
+            let _ = app.emit("rules-changed", rules.all());
+        }
+
+        // 计划由调度线程每次读取，只需同步任务计划程序中的镜像任务
+        let schedules = app.state::<ScheduleStore>();
+        if reload("schedules.json", schedules.reload_if_changed(), &mut reloaded) {
+            #[cfg(windows)]
+            if let Err(e) = crate::task_scheduler::sync(&schedules.all()) {
+                tracing::warn!(error = %e, "failed to sync reloaded schedules");
+            }
+        }
+
+        if !reloaded.is_empty() {
+            let _ = app.emit("settings-reloaded", &reloaded);
+        }
+    });
+}
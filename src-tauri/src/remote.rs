@@ -0,0 +1,80 @@
+use crate::audit::{ActionSource, AuditAction};
+use crate::cmd;
+use crate::error::AutoShutdownError;
+use crate::executor;
+use crate::machines::{Credentials, MachineStore};
+use crate::power::PowerAction;
+use crate::settings::SettingsStore;
+use crate::ssh;
+use tauri::{AppHandle, Manager, State};
+
+fn ipc_share(host: &str) -> String {
+    format!(r"\\{}\IPC$", host)
+}
+
+// 先以指定用户建立到目标机 IPC$ 的连接，之后的 shutdown /m 沿用该连接的身份
+fn connect(host: &str, credentials: &Credentials) -> Result<(), String> {
+    let user = format!("/user:{}", credentials.username);
+    cmd::output("net", &["use", &ipc_share(host), &credentials.password, &user]).map(|_| ())
+}
+
+fn disconnect(host: &str) {
+    let _ = cmd::output("net", &["use", &ipc_share(host), "/delete", "/y"]);
+}
+
+// 已保存且配置了 SSH 的机器走 SSH，其余按 Windows 远程关机处理
+pub fn power(app: &AppHandle, host: &str, action: PowerAction) -> Result<(), String> {
+    match app.state::<MachineStore>().find_by_host(host).and_then(|m| m.ssh) {
+        Some(config) => ssh::power(host, &config, action),
+        None => windows_power(host, action),
+    }
+}
+
+// 目标机需开启远程关机权限（本地安全策略“从远程系统强制关机”）并允许文件和打印机共享
+fn windows_power(host: &str, action: PowerAction) -> Result<(), String> {
+    let flags: &[&str] = match action {
+        PowerAction::Shutdown => &["/s"],
+        PowerAction::FullShutdown => &["/s", "/full"],
+        PowerAction::Restart => &["/r"],
+        other => return Err(format!("{:?} is not supported on remote machines", other)),
+    };
+    let target = format!(r"\\{}", host);
+    tracing::info!(host, ?action, "remote power action");
+    let mut args = flags.to_vec();
+    args.extend(["/m", &target, "/t", "0", "/f"]);
+    cmd::output("shutdown", &args).map(|_| ())
+}
+
+// 未给出凭据时使用已保存机器的凭据，仍没有则以当前用户身份执行；SSH 机器不需要建立 IPC$ 连接
+#[tauri::command]
+pub fn remote_shutdown(
+    app: AppHandle,
+    machines: State<'_, MachineStore>,
+    settings: State<'_, SettingsStore>,
+    host: String,
+    credentials: Option<Credentials>,
+    action: PowerAction,
+) -> Result<(), AutoShutdownError> {
+    let machine = machines.find_by_host(&host);
+    let credentials = credentials.or_else(|| machine.as_ref().and_then(|m| m.credentials.clone()));
+    let uses_ssh = machine.is_some_and(|m| m.ssh.is_some());
+    let connected = match &credentials {
+        Some(credentials) if !uses_ssh && !settings.get().simulate => {
+            connect(&host, credentials)?;
+            true
+        }
+        _ => false,
+    };
+    let result = executor::perform(
+        &app,
+        AuditAction::RemotePower {
+            host: host.clone(),
+            action,
+        },
+        ActionSource::User,
+    );
+    if connected {
+        disconnect(&host);
+    }
+    result
+}
@@ -0,0 +1,1281 @@
+use crate::actions::Action;
+use crate::backup::BackupTool;
+use crate::defer::DeferOptions;
+use crate::error::AutoShutdownError;
+use crate::fswatch::GrowthTracker;
+use crate::gpu;
+use crate::history::HistoryLog;
+use crate::idle::{self, ActivityAbort};
+use crate::net;
+use crate::notify::{self, Channel, Notice, NoticeKind};
+use crate::pre_action::PreActionOptions;
+use crate::process_events::ProcessEvents;
+use crate::qbittorrent;
+use crate::schedule::{default_true, Recurrence};
+use crate::settings::SettingsStore;
+use crate::steam;
+use crate::storage::JsonStore;
+use crate::timers::{
+    self, ExitMode, ProcessRefresh, ProcessTarget, TimerFired, TimerSpec, TimerStore, TimerTrigger,
+};
+use crate::titles;
+use crate::uptime;
+use crate::watchdog::{WatchdogState, WatchdogTarget};
+use crate::winupdate;
+use chrono::{DateTime, Local, Timelike};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::System;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// 两条规则的执行时间相差不超过该时长时视为冲突
+const CONFLICT_WINDOW_MINUTES: i64 = 30;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuleTrigger {
+    Schedule(Recurrence),
+    // 指定进程运行过且全部退出后触发
+    ProcessExit {
+        name: String,
+        // 命令行包含该子串的实例才计入
+        #[serde(default)]
+        cmdline: Option<String>,
+    },
+    // 无键盘鼠标输入达到指定分钟数后开始倒计时
+    Idle {
+        minutes: u64,
+        #[serde(default = "default_idle_countdown")]
+        countdown_secs: u64,
+        // 倒计时结束后再等待的秒数，期间有输入即取消，0 表示不等待
+        #[serde(default)]
+        grace_secs: u64,
+    },
+    // 目录中没有未完成的下载文件，且一段时间内没有文件增长
+    DownloadsFinished {
+        folder: String,
+        #[serde(default = "default_quiet_minutes")]
+        quiet_minutes: u64,
+    },
+    // 文件或目录的总大小开始变化后，连续指定分钟数不再变化时触发
+    SizeStable { path: String, minutes: u64 },
+    // qBittorrent 中所有种子下载完成，连接信息在设置中
+    TorrentsFinished {
+        #[serde(default)]
+        pause_seeding: bool,
+    },
+    // Steam 的游戏下载和更新全部完成
+    SteamDownloadsFinished,
+    // Windows 更新安装过程结束并保持空闲一段时间
+    WindowsUpdateFinished {
+        #[serde(default = "default_quiet_minutes")]
+        quiet_minutes: u64,
+    },
+    // 备份工具的任务进程退出，且日志目录一段时间不再变化
+    BackupFinished {
+        tool: BackupTool,
+        // 覆盖预设的日志目录
+        #[serde(default)]
+        log_dir: Option<String>,
+        #[serde(default = "default_quiet_minutes")]
+        quiet_minutes: u64,
+    },
+    // TCP 端口从可连接变为不可连接（或反之）
+    PortState {
+        #[serde(default = "default_host")]
+        host: String,
+        port: u16,
+        when: Reachability,
+        #[serde(default = "default_poll_secs")]
+        poll_secs: u64,
+    },
+    // 主机持续 N 分钟不响应 ping（或恢复响应）
+    HostReachability {
+        host: String,
+        when: Reachability,
+        minutes: u64,
+        // ICMP 被屏蔽时改为探测该 TCP 端口
+        #[serde(default)]
+        tcp_port: Option<u16>,
+    },
+    // 多个进程全部退出或任一退出，按名称和命令行匹配
+    ProcessesExit {
+        targets: Vec<ProcessTarget>,
+        #[serde(default)]
+        mode: ExitMode,
+    },
+    // 进程仍在运行，但自身 CPU 占用连续 N 分钟低于阈值（启动器、下载工具空闲下来）
+    ProcessIdle {
+        name: String,
+        #[serde(default = "default_process_cpu_threshold")]
+        cpu_below_percent: f32,
+        minutes: u64,
+    },
+    // 窗口标题匹配正则表达式（如 "100%"、"Render finished"）
+    WindowTitle {
+        pattern: String,
+        // 只看该进程的窗口
+        #[serde(default)]
+        process: Option<String>,
+    },
+    // GPU 占用曾经高于阈值，之后连续 N 分钟低于阈值（训练、渲染等任务结束）
+    GpuIdle {
+        #[serde(default = "default_gpu_threshold")]
+        threshold_percent: f64,
+        minutes: u64,
+    },
+    // 系统连续运行超过指定天数，每次开机只触发一次（如运行 14 天后重启）
+    Uptime { days: u64 },
+    // 进程意外退出后重新启动（如让下载工具一直运行到计划关机），重启次数用尽时执行规则的操作
+    Watchdog(WatchdogTarget),
+    // 进程在 minutes 分钟内以非零退出码退出 count 次（如通宵任务反复崩溃）
+    // 未以管理员身份运行时得不到退出码，每次退出都计入
+    CrashLoop { name: String, count: u32, minutes: u64 },
+}
+
+fn default_idle_countdown() -> u64 {
+    60
+}
+
+fn default_quiet_minutes() -> u64 {
+    2
+}
+
+fn default_host() -> String {
+    "127.0.0.1".into()
+}
+
+fn default_poll_secs() -> u64 {
+    15
+}
+
+fn default_process_cpu_threshold() -> f32 {
+    2.0
+}
+
+fn default_gpu_threshold() -> f64 {
+    10.0
+}
+
+// 可达性类触发条件在状态切换时触发
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Reachability {
+    Down,
+    Up,
+}
+
+impl RuleTrigger {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            RuleTrigger::Schedule(recurrence) => recurrence.validate(),
+            RuleTrigger::ProcessExit { name, .. } if name.trim().is_empty() => {
+                Err("Process name must not be empty".into())
+            }
+            RuleTrigger::ProcessExit { .. } => Ok(()),
+            RuleTrigger::Idle { minutes: 0, .. } => Err("Idle time must be at least one minute".into()),
+            RuleTrigger::Idle { .. } => Ok(()),
+            RuleTrigger::DownloadsFinished { folder, .. } if !std::path::Path::new(folder).is_dir() => {
+                Err(format!("Folder {} does not exist", folder))
+            }
+            RuleTrigger::DownloadsFinished { .. } => Ok(()),
+            RuleTrigger::SizeStable { path, .. } if !std::path::Path::new(path).exists() => {
+                Err(format!("{} does not exist", path))
+            }
+            RuleTrigger::SizeStable { minutes: 0, .. } => Err("Stable time must be at least one minute".into()),
+            RuleTrigger::SizeStable { .. } => Ok(()),
+            RuleTrigger::TorrentsFinished { .. } => Ok(()),
+            RuleTrigger::SteamDownloadsFinished => Ok(()),
+            RuleTrigger::WindowsUpdateFinished { .. } => Ok(()),
+            RuleTrigger::BackupFinished { .. } => Ok(()),
+            RuleTrigger::PortState { port: 0, .. } => Err("Port must not be zero".into()),
+            RuleTrigger::PortState { .. } => Ok(()),
+            RuleTrigger::HostReachability { host, .. } if host.trim().is_empty() => {
+                Err("Host must not be empty".into())
+            }
+            RuleTrigger::HostReachability { .. } => Ok(()),
+            RuleTrigger::ProcessesExit { targets, .. } if targets.is_empty() => {
+                Err("Add at least one process to watch".into())
+            }
+            // pid 在重启后失效，规则只按名称和命令行匹配
+            RuleTrigger::ProcessesExit { targets, .. } if targets.iter().any(|t| t.pid.is_some()) => {
+                Err("Rules match processes by name or command line, not by pid".into())
+            }
+            RuleTrigger::ProcessesExit { targets, .. } if targets.iter().any(ProcessTarget::is_empty) => {
+                Err("Each process needs a name or a command line".into())
+            }
+            RuleTrigger::ProcessesExit { .. } => Ok(()),
+            RuleTrigger::ProcessIdle { name, .. } if name.trim().is_empty() => {
+                Err("Process name must not be empty".into())
+            }
+            RuleTrigger::ProcessIdle { minutes: 0, .. } => Err("Idle time must be at least one minute".into()),
+            RuleTrigger::ProcessIdle { .. } => Ok(()),
+            RuleTrigger::WindowTitle { pattern, .. } => Regex::new(pattern)
+                .map(|_| ())
+                .map_err(|e| format!("Invalid title pattern: {}", e)),
+            RuleTrigger::GpuIdle { threshold_percent, .. } if !(0.0..100.0).contains(threshold_percent) => {
+                Err("GPU threshold must be between 0 and 100".into())
+            }
+            RuleTrigger::GpuIdle { minutes: 0, .. } => Err("Idle time must be at least one minute".into()),
+            RuleTrigger::GpuIdle { .. } => Ok(()),
+            RuleTrigger::Uptime { days: 0 } => Err("Uptime must be at least one day".into()),
+            RuleTrigger::Uptime { .. } => Ok(()),
+            RuleTrigger::Watchdog(target) => target.validate(),
+            RuleTrigger::CrashLoop { name, .. } if name.trim().is_empty() => {
+                Err("Process name must not be empty".into())
+            }
+            RuleTrigger::CrashLoop { count: 0, .. } => Err("Crash count must be at least one".into()),
+            RuleTrigger::CrashLoop { minutes: 0, .. } => Err("Time window must be at least one minute".into()),
+            RuleTrigger::CrashLoop { .. } => Ok(()),
+        }
+    }
+
+    // 需要引擎在检查前刷新进程列表
+    fn needs_processes(&self) -> bool {
+        matches!(
+            self,
+            RuleTrigger::ProcessExit { .. }
+                | RuleTrigger::ProcessesExit { .. }
+                | RuleTrigger::ProcessIdle { .. }
+                | RuleTrigger::WindowTitle { process: Some(_), .. }
+                | RuleTrigger::WindowsUpdateFinished { .. }
+                | RuleTrigger::BackupFinished { .. }
+                | RuleTrigger::Watchdog(_)
+                | RuleTrigger::CrashLoop { .. }
+        )
+    }
+
+    // CPU 占用和新窗口所属的进程不会引起进程退出事件，需要每次都刷新进程列表
+    fn polls_processes(&self) -> bool {
+        matches!(
+            self,
+            RuleTrigger::ProcessIdle { .. } | RuleTrigger::WindowTitle { process: Some(_), .. }
+        )
+    }
+
+    fn needs_cmdline(&self) -> bool {
+        match self {
+            RuleTrigger::ProcessExit { cmdline, .. } => cmdline.is_some(),
+            RuleTrigger::ProcessesExit { targets, .. } => targets.iter().any(|t| t.cmdline.is_some()),
+            _ => false,
+        }
+    }
+
+    // 触发后到执行之间的倒计时
+    fn countdown_secs(&self) -> u64 {
+        match self {
+            RuleTrigger::Idle { countdown_secs, .. } => *countdown_secs,
+            _ => 0,
+        }
+    }
+
+    // 只有空闲触发需要确认用户是否已离开
+    fn grace_secs(&self) -> Option<u64> {
+        match self {
+            RuleTrigger::Idle { grace_secs, .. } if *grace_secs > 0 => Some(*grace_secs),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Rule {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub trigger: RuleTrigger,
+    pub action: Action,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub pre_action: PreActionOptions,
+    #[serde(default)]
+    pub defer: DeferOptions,
+    #[serde(default)]
+    pub abort_on_activity: Option<ActivityAbort>,
+    // 为空时使用全局的提醒时间和通知渠道
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notifications: Option<RuleNotifications>,
+}
+
+// 规则触发后倒计时期间的提醒设置
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct RuleNotifications {
+    // 到期前多少秒提醒，可设置多次，如 [300, 60]
+    pub warning_secs: Vec<i64>,
+    // 为空时按全局设置的类别选择渠道
+    pub channels: Vec<Channel>,
+    pub sound: bool,
+    // 是否允许在提醒中推迟执行，以及每次推迟的时长
+    pub snoozable: bool,
+    pub snooze_minutes: u64,
+}
+
+impl Default for RuleNotifications {
+    fn default() -> Self {
+        RuleNotifications {
+            warning_secs: vec![60],
+            channels: Vec::new(),
+            sound: true,
+            snoozable: true,
+            snooze_minutes: 10,
+        }
+    }
+}
+
+impl RuleNotifications {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.warning_secs.iter().any(|s| *s <= 0) {
+            return Err("Warning times must be greater than zero".into());
+        }
+        if self.snoozable && self.snooze_minutes == 0 {
+            return Err("Snooze time must be at least one minute".into());
+        }
+        Ok(())
+    }
+}
+
+impl Rule {
+    pub fn validate(&self) -> Result<(), String> {
+        self.trigger.validate()?;
+        self.action.validate()?;
+        self.notifications.as_ref().map_or(Ok(()), RuleNotifications::validate)
+    }
+
+    // 最早一次提醒的提前秒数，触发后至少留出这么长时间再执行
+    fn warning_lead(&self) -> i64 {
+        self.notifications
+            .as_ref()
+            .and_then(|n| n.warning_secs.iter().max().copied())
+            .unwrap_or(0)
+            .max(0)
+    }
+}
+
+pub struct RuleStore(JsonStore<Vec<Rule>>);
+
+impl RuleStore {
+    pub fn load(app: &AppHandle) -> Self {
+        RuleStore(JsonStore::load(app, "rules.json"))
+    }
+
+    pub fn all(&self) -> Vec<Rule> {
+        self.0.get()
+    }
+
+    pub fn reload_if_changed(&self) -> Result<bool, String> {
+        self.0.reload_if_changed()
+    }
+
+    // 切换配置方案时整体替换
+    pub fn replace_all(&self, app: &AppHandle, rules: Vec<Rule>) -> Result<(), String> {
+        self.update(app, |current| {
+            *current = rules;
+            Ok(())
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    fn update<R>(
+        &self,
+        app: &AppHandle,
+        f: impl FnOnce(&mut Vec<Rule>) -> Result<R, AutoShutdownError>,
+    ) -> Result<R, AutoShutdownError> {
+        let result = self.0.update(f)??;
+        let _ = app.emit("rules-changed", self.all());
+        Ok(result)
+    }
+}
+
+fn find(rules: &mut [Rule], id: &str) -> Result<usize, AutoShutdownError> {
+    rules
+        .iter()
+        .position(|r| r.id == id)
+        .ok_or_else(|| AutoShutdownError::NotFound(format!("Rule {} not found", id)))
+}
+
+#[tauri::command]
+pub fn list_rules(store: State<'_, RuleStore>) -> Vec<Rule> {
+    store.all()
+}
+
+#[tauri::command]
+pub fn create_rule(
+    app: AppHandle,
+    store: State<'_, RuleStore>,
+    mut rule: Rule,
+) -> Result<Rule, AutoShutdownError> {
+    rule.validate()?;
+    rule.id = uuid::Uuid::new_v4().to_string();
+    let rule = store.update(&app, |rules| {
+        rules.push(rule.clone());
+        Ok(rule)
+    })?;
+    warn_conflicts(&app, &store, &rule);
+    Ok(rule)
+}
+
+#[tauri::command]
+pub fn update_rule(app: AppHandle, store: State<'_, RuleStore>, rule: Rule) -> Result<Rule, AutoShutdownError> {
+    rule.validate()?;
+    let rule = store.update(&app, |rules| {
+        let pos = find(rules, &rule.id)?;
+        rules[pos] = rule.clone();
+        Ok(rule)
+    })?;
+    warn_conflicts(&app, &store, &rule);
+    Ok(rule)
+}
+
+#[tauri::command]
+pub fn delete_rule(app: AppHandle, store: State<'_, RuleStore>, id: String) -> Result<(), AutoShutdownError> {
+    store.update(&app, |rules| {
+        let pos = find(rules, &id)?;
+        rules.remove(pos);
+        Ok(())
+    })
+}
+
+// 未指定 enabled 时切换当前状态
+#[tauri::command]
+pub fn toggle_rule(
+    app: AppHandle,
+    store: State<'_, RuleStore>,
+    id: String,
+    enabled: Option<bool>,
+) -> Result<Rule, AutoShutdownError> {
+    store.update(&app, |rules| {
+        let pos = find(rules, &id)?;
+        let rule = &mut rules[pos];
+        rule.enabled = enabled.unwrap_or(!rule.enabled);
+        Ok(rule.clone())
+    })
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RuleConflict {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub other_id: String,
+    pub other_name: String,
+    // 运行时胜出的规则，要看哪条先触发时为 None
+    pub winner_id: Option<String>,
+    pub reason: String,
+}
+
+// 一天中的分钟差，跨零点时取较短的一边
+fn minutes_apart(a: &Recurrence, b: &Recurrence) -> i64 {
+    let minutes = |r: &Recurrence| r.hour as i64 * 60 + r.minute as i64;
+    let diff = (minutes(a) - minutes(b)).abs();
+    diff.min(24 * 60 - diff)
+}
+
+// 两条不同的规则执行不同的电源操作
+fn power_conflict(a: &Rule, b: &Rule) -> bool {
+    match (a.action.power(), b.action.power()) {
+        (Some(action_a), Some(action_b)) => a.id != b.id && action_a != action_b,
+        _ => false,
+    }
+}
+
+// 基于进程的触发条件关注的进程名；按 pid 或命令行匹配的项为 None，无法判断是否同一进程
+fn watched_processes(trigger: &RuleTrigger) -> Option<Vec<Option<&str>>> {
+    match trigger {
+        RuleTrigger::ProcessExit { name, .. }
+        | RuleTrigger::ProcessIdle { name, .. }
+        | RuleTrigger::CrashLoop { name, .. }
+        | RuleTrigger::WindowTitle { process: Some(name), .. } => Some(vec![Some(name)]),
+        RuleTrigger::Watchdog(target) => Some(vec![Some(&target.name)]),
+        RuleTrigger::ProcessesExit { targets, .. } => Some(targets.iter().map(|t| t.name.as_deref()).collect()),
+        _ => None,
+    }
+}
+
+// 空闲规则从开始倒计时到执行之间的空闲秒数
+fn idle_window(trigger: &RuleTrigger) -> Option<(u64, u64)> {
+    match trigger {
+        RuleTrigger::Idle { minutes, countdown_secs, grace_secs } => {
+            Some((minutes * 60, minutes * 60 + countdown_secs + grace_secs))
+        }
+        _ => None,
+    }
+}
+
+fn same_path(a: &str, b: &str) -> bool {
+    let normalize = |p: &str| p.trim().trim_end_matches(['\\', '/']).replace('/', "\\").to_lowercase();
+    normalize(a) == normalize(b)
+}
+
+// 两个触发条件是否可能同时满足：关注同一进程、同一目录或主机，或空闲区间重叠；
+// 关注不同对象的规则各自独立，不算冲突
+fn triggers_overlap(a: &RuleTrigger, b: &RuleTrigger) -> bool {
+    if let (Some(pa), Some(pb)) = (watched_processes(a), watched_processes(b)) {
+        return pa.iter().any(|x| {
+            pb.iter().any(|y| match (x, y) {
+                (Some(x), Some(y)) => timers::name_matches(x, y),
+                _ => true,
+            })
+        });
+    }
+    if let (Some((start_a, end_a)), Some((start_b, end_b))) = (idle_window(a), idle_window(b)) {
+        return start_a <= end_b && start_b <= end_a;
+    }
+    match (a, b) {
+        (RuleTrigger::Schedule(ra), RuleTrigger::Schedule(rb)) => {
+            (0..7).any(|d| ra.runs_on(d) && rb.runs_on(d)) && minutes_apart(ra, rb) <= CONFLICT_WINDOW_MINUTES
+        }
+        (RuleTrigger::DownloadsFinished { folder: fa, .. }, RuleTrigger::DownloadsFinished { folder: fb, .. }) => {
+            same_path(fa, fb)
+        }
+        (RuleTrigger::SizeStable { path: pa, .. }, RuleTrigger::SizeStable { path: pb, .. }) => same_path(pa, pb),
+        (RuleTrigger::BackupFinished { tool: ta, .. }, RuleTrigger::BackupFinished { tool: tb, .. }) => ta == tb,
+        (
+            RuleTrigger::PortState { host: ha, port: pa, .. },
+            RuleTrigger::PortState { host: hb, port: pb, .. },
+        ) => ha.eq_ignore_ascii_case(hb) && pa == pb,
+        (RuleTrigger::HostReachability { host: ha, .. }, RuleTrigger::HostReachability { host: hb, .. }) => {
+            ha.eq_ignore_ascii_case(hb)
+        }
+        // 不限进程的标题规则可能匹配任何窗口
+        (RuleTrigger::WindowTitle { .. }, RuleTrigger::WindowTitle { .. }) => true,
+        // 其余只有一个对象的触发条件（种子、Steam、Windows 更新、GPU、运行时长），同类即重叠
+        _ => std::mem::discriminant(a) == std::mem::discriminant(b),
+    }
+}
+
+// 可能在相近时间触发的冲突规则；定时规则之间按时间判断，其余按触发条件是否关注同一对象判断
+fn conflicts(a: &Rule, b: &Rule) -> bool {
+    a.enabled && b.enabled && power_conflict(a, b) && triggers_overlap(&a.trigger, &b.trigger)
+}
+
+// 与运行时裁决相同：优先级高者胜出，相同时执行更激进的操作，再相同则先触发者胜出
+fn conflict_between(a: &Rule, b: &Rule) -> RuleConflict {
+    let severity = |r: &Rule| r.action.severity();
+    let (winner, reason) = match b.priority.cmp(&a.priority).then(severity(b).cmp(&severity(a))) {
+        Ordering::Less => (Some(a), format!("\"{}\" wins by priority or severity", a.name)),
+        Ordering::Greater => (Some(b), format!("\"{}\" wins by priority or severity", b.name)),
+        Ordering::Equal => (None, "Same priority and severity, whichever triggers first wins".to_string()),
+    };
+    RuleConflict {
+        rule_id: a.id.clone(),
+        rule_name: a.name.clone(),
+        other_id: b.id.clone(),
+        other_name: b.name.clone(),
+        winner_id: winner.map(|r| r.id.clone()),
+        reason,
+    }
+}
+
+pub fn find_conflicts(rules: &[Rule]) -> Vec<RuleConflict> {
+    let mut found = Vec::new();
+    for (i, a) in rules.iter().enumerate() {
+        for b in &rules[i + 1..] {
+            if conflicts(a, b) {
+                found.push(conflict_between(a, b));
+            }
+        }
+    }
+    found
+}
+
+// 保存后检查与其他规则的冲突，通过事件提示前端，不阻止保存
+fn warn_conflicts(app: &AppHandle, store: &RuleStore, rule: &Rule) {
+    let conflicts: Vec<RuleConflict> = store
+        .all()
+        .iter()
+        .filter(|other| conflicts(rule, other))
+        .map(|other| conflict_between(rule, other))
+        .collect();
+    if conflicts.is_empty() {
+        return;
+    }
+    tracing::warn!(rule = %rule.id, conflicts = conflicts.len(), "rule conflicts with other rules");
+    let _ = app.emit("rule-conflicts", &conflicts);
+}
+
+#[tauri::command]
+pub fn get_rule_conflicts(store: State<'_, RuleStore>) -> Vec<RuleConflict> {
+    find_conflicts(&store.all())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuleOutcome {
+    Executed,
+    // 与其他同时触发的项目裁决后未执行
+    Skipped { reason: String },
+    Failed { error: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RuleExecution {
+    pub timestamp: DateTime<Local>,
+    // 触发时的规则快照，之后修改规则不影响历史记录
+    pub rule: Rule,
+    pub action: Action,
+    pub outcome: RuleOutcome,
+}
+
+pub struct RuleHistory(pub HistoryLog<RuleExecution>);
+
+impl RuleHistory {
+    pub fn open(app: &AppHandle) -> Self {
+        RuleHistory(HistoryLog::open(app, "rule_history.jsonl"))
+    }
+}
+
+pub fn record_firing(app: &AppHandle, fired: &TimerFired, result: &Result<(), AutoShutdownError>) {
+    let history = app.state::<RuleHistory>();
+    let executed = &fired.executed;
+    if let Some(rule) = &executed.spec.rule {
+        let outcome = match result {
+            Ok(()) => RuleOutcome::Executed,
+            Err(error) => RuleOutcome::Failed { error: error.to_string() },
+        };
+        let _ = history.0.append(&RuleExecution {
+            timestamp: Local::now(),
+            rule: rule.clone(),
+            action: executed.spec.action.clone(),
+            outcome,
+        });
+    }
+    for timer in &fired.superseded {
+        let Some(rule) = &timer.spec.rule else {
+            continue;
+        };
+        let by = if executed.spec.label.is_empty() {
+            executed.spec.action.to_string()
+        } else {
+            executed.spec.label.clone()
+        };
+        let _ = history.0.append(&RuleExecution {
+            timestamp: Local::now(),
+            rule: rule.clone(),
+            action: timer.spec.action.clone(),
+            outcome: RuleOutcome::Skipped {
+                reason: format!("Superseded by {}", by),
+            },
+        });
+    }
+}
+
+fn record_skipped(app: &AppHandle, rule: &Rule, reason: String) {
+    tracing::info!(rule = %rule.id, reason, "rule skipped by conflict resolution");
+    let _ = app.state::<RuleHistory>().0.append(&RuleExecution {
+        timestamp: Local::now(),
+        rule: rule.clone(),
+        action: rule.action.clone(),
+        outcome: RuleOutcome::Skipped { reason },
+    });
+}
+
+// 与冲突窗口内已执行或正在倒计时的冲突规则裁决，返回本规则是否继续执行，结果写入规则历史
+fn arbitrate(app: &AppHandle, rule: &Rule) -> bool {
+    let since = Local::now() - chrono::Duration::minutes(CONFLICT_WINDOW_MINUTES);
+    // 已执行的规则优先级不低于本规则时让步，避免唤醒后又执行另一个操作
+    let executed = app.state::<RuleHistory>().0.recent(50).into_iter().find(|e| {
+        e.timestamp >= since
+            && matches!(e.outcome, RuleOutcome::Executed)
+            && power_conflict(rule, &e.rule)
+            && e.rule.priority >= rule.priority
+    });
+    if let Some(e) = executed {
+        let reason = format!("Yielded to \"{}\" executed at {}", e.rule.name, e.timestamp.format("%H:%M"));
+        record_skipped(app, rule, reason);
+        return false;
+    }
+
+    // 正在倒计时的冲突规则：优先级高者胜出，相同时执行更激进的操作，再相同则先触发者胜出
+    let store = app.state::<TimerStore>();
+    for timer in store.all() {
+        let Some(other) = timer.spec.rule.as_ref().filter(|other| power_conflict(rule, other)) else {
+            continue;
+        };
+        let other_wins = other
+            .priority
+            .cmp(&rule.priority)
+            .then(other.action.severity().cmp(&rule.action.severity()))
+            .is_ge();
+        if other_wins {
+            record_skipped(app, rule, format!("Yielded to \"{}\" which is already counting down", other.name));
+            return false;
+        }
+        if store.cancel(&timer.id).is_some() {
+            record_skipped(app, other, format!("Superseded by higher-priority rule \"{}\"", rule.name));
+        }
+    }
+    true
+}
+
+#[tauri::command]
+pub fn get_rule_history(history: State<'_, RuleHistory>, limit: Option<usize>) -> Vec<RuleExecution> {
+    history.0.recent(limit.unwrap_or(100))
+}
+
+#[derive(Serialize, Clone)]
+struct WatchProgress<'a> {
+    rule_id: &'a str,
+    path: &'a str,
+    total_size: u64,
+    files: usize,
+    // 大小尚未变化过时为 None
+    stable_secs: Option<u64>,
+    target_secs: u64,
+}
+
+// 每条规则在两次检查之间需要记住的状态
+#[derive(Default)]
+struct RuleRuntime {
+    last_fired_minute: Option<String>,
+    process_seen: bool,
+    // 多进程触发中每一项上次是否在运行
+    targets_seen: Vec<bool>,
+    idle_fired: bool,
+    growth: GrowthTracker,
+    // 观察到过下载活动，之后变为空闲才触发
+    activity_seen: bool,
+    last_poll: Option<Instant>,
+    last_active: Option<Instant>,
+    // 上一次探测的可达状态及其开始时间
+    reachable: Option<bool>,
+    reachable_since: Option<Instant>,
+    reachability_fired: bool,
+    // 上次检查标题时的窗口变化计数，以及当时是否已经匹配
+    title_generation: u64,
+    title_matched: bool,
+    // 编译后的标题表达式，规则修改后表达式变化时重新编译
+    title_pattern: Option<(String, Result<Regex, String>)>,
+    uptime_fired: bool,
+    watchdog: WatchdogState,
+    // 没有进程事件时轮询到的退出时间
+    polled_exits: VecDeque<Instant>,
+    crash_loop_fired: Option<Instant>,
+    // 日程规则提前触发时对应的执行时间
+    fire_at: Option<DateTime<Local>>,
+}
+
+// 访问外部服务的触发条件的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+impl RuleRuntime {
+    // 记录本次状态；从相反状态切换到目标状态并保持 hold 之后触发一次
+    fn reached(&mut self, up: bool, when: Reachability, hold: Duration) -> bool {
+        if self.reachable != Some(up) {
+            // 启动后的第一次探测不算切换
+            self.reachable_since = self.reachable.map(|_| Instant::now());
+            self.reachable = Some(up);
+            self.reachability_fired = false;
+        }
+        if up != (when == Reachability::Up) || self.reachability_fired {
+            return false;
+        }
+        self.reachability_fired = self.reachable_since.is_some_and(|t| t.elapsed() >= hold);
+        self.reachability_fired
+    }
+
+    fn should_poll(&mut self, interval: Duration) -> bool {
+        if self.last_poll.is_some_and(|t| t.elapsed() < interval) {
+            return false;
+        }
+        self.last_poll = Some(Instant::now());
+        true
+    }
+}
+
+fn check_trigger(app: &AppHandle, rule: &Rule, runtime: &mut RuleRuntime, sys: &System) -> bool {
+    match &rule.trigger {
+        // 有提醒时按最早的提醒提前触发，多看一分钟保证执行时间所在的整分钟仍在提前量之外
+        RuleTrigger::Schedule(recurrence) => {
+            let now = Local::now();
+            let lead = rule.warning_lead();
+            let target = if lead > 0 { now + chrono::Duration::seconds(lead + 60) } else { now };
+            let minute_key = target.format("%Y-%m-%d %H:%M").to_string();
+            let holidays = app.state::<SettingsStore>().get().holidays;
+            if !recurrence.matches(&target, &holidays) || runtime.last_fired_minute.as_ref() == Some(&minute_key) {
+                return false;
+            }
+            runtime.last_fired_minute = Some(minute_key);
+            runtime.fire_at = target.with_second(0).and_then(|t| t.with_nanosecond(0)).map(|t| t.max(now));
+            true
+        }
+        RuleTrigger::ProcessExit { name, cmdline } => {
+            let running = timers::process_matching(sys, Some(name), cmdline.as_deref());
+            let exited = runtime.process_seen && !running;
+            runtime.process_seen = running;
+            exited
+        }
+        RuleTrigger::ProcessesExit { targets, mode } => {
+            let running: Vec<bool> = targets.iter().map(|t| t.running(sys)).collect();
+            let seen = std::mem::replace(&mut runtime.targets_seen, running.clone());
+            if seen.len() != running.len() {
+                return false;
+            }
+            match mode {
+                ExitMode::All => seen.iter().any(|s| *s) && !running.iter().any(|r| *r),
+                ExitMode::Any => seen.iter().zip(&running).any(|(s, r)| *s && !*r),
+            }
+        }
+        // 每段空闲只触发一次，有输入后重新计算
+        RuleTrigger::Idle { minutes, .. } => {
+            let idle = idle::idle_duration() >= Duration::from_secs(minutes * 60);
+            let fire = idle && !runtime.idle_fired;
+            runtime.idle_fired = idle;
+            fire
+        }
+        RuleTrigger::DownloadsFinished { folder, quiet_minutes } => {
+            let Some(scan) = runtime.growth.sample(folder) else {
+                return false;
+            };
+            let quiet = Duration::from_secs(quiet_minutes * 60);
+            let active =
+                scan.partial_files > 0 || runtime.growth.stable_for().is_some_and(|d| d < quiet);
+            if active {
+                runtime.activity_seen = true;
+                return false;
+            }
+            let finished = runtime.activity_seen;
+            if finished {
+                runtime.activity_seen = false;
+                runtime.growth.reset();
+            }
+            finished
+        }
+        RuleTrigger::SizeStable { path, minutes } => {
+            let Some(scan) = runtime.growth.sample(path) else {
+                return false;
+            };
+            let stable = runtime.growth.stable_for();
+            let target = Duration::from_secs(minutes * 60);
+            let _ = app.emit(
+                "watch-progress",
+                WatchProgress {
+                    rule_id: &rule.id,
+                    path,
+                    total_size: scan.total_size,
+                    files: scan.files,
+                    stable_secs: stable.map(|d| d.as_secs()),
+                    target_secs: target.as_secs(),
+                },
+            );
+            let finished = stable.is_some_and(|d| d >= target);
+            if finished {
+                runtime.growth.reset();
+            }
+            finished
+        }
+        RuleTrigger::TorrentsFinished { pause_seeding } => {
+            let Some(config) = app.state::<SettingsStore>().get().qbittorrent else {
+                return false;
+            };
+            if !runtime.should_poll(POLL_INTERVAL) {
+                return false;
+            }
+            let summary = match qbittorrent::summary(&config) {
+                Ok(summary) => summary,
+                Err(e) => {
+                    tracing::warn!(rule = %rule.id, error = %e, "failed to query qBittorrent");
+                    return false;
+                }
+            };
+            // 观察到过未完成的种子之后才触发，避免启用规则时立即执行
+            if !summary.all_completed() {
+                runtime.activity_seen = true;
+                return false;
+            }
+            if !runtime.activity_seen {
+                return false;
+            }
+            runtime.activity_seen = false;
+            if *pause_seeding {
+                if let Err(e) = qbittorrent::pause_all(&config) {
+                    tracing::warn!(error = %e, "failed to pause qBittorrent seeding");
+                }
+            }
+            true
+        }
+        RuleTrigger::SteamDownloadsFinished => {
+            if !runtime.should_poll(POLL_INTERVAL) {
+                return false;
+            }
+            let Some(downloads) = steam::active_downloads() else {
+                return false;
+            };
+            if !downloads.is_empty() {
+                runtime.activity_seen = true;
+                return false;
+            }
+            std::mem::take(&mut runtime.activity_seen)
+        }
+        RuleTrigger::WindowsUpdateFinished { quiet_minutes } => {
+            if !runtime.should_poll(POLL_INTERVAL) {
+                return false;
+            }
+            if winupdate::installing(sys) {
+                runtime.activity_seen = true;
+                runtime.last_active = Some(Instant::now());
+                return false;
+            }
+            let quiet = Duration::from_secs(quiet_minutes * 60);
+            if !runtime.activity_seen || runtime.last_active.is_some_and(|t| t.elapsed() < quiet) {
+                return false;
+            }
+            runtime.activity_seen = false;
+            true
+        }
+        RuleTrigger::BackupFinished {
+            tool,
+            log_dir,
+            quiet_minutes,
+        } => {
+            // 进程退出后日志可能还在写入，从运行期间就开始采样，等目录稳定后再触发
+            let log_dir = log_dir.as_deref().or(tool.preset().log_dir);
+            if let Some(dir) = log_dir.filter(|d| std::path::Path::new(d).exists()) {
+                runtime.growth.sample(dir);
+            }
+            if tool.running(sys) {
+                runtime.activity_seen = true;
+                return false;
+            }
+            let quiet = Duration::from_secs(quiet_minutes * 60);
+            if !runtime.activity_seen || runtime.growth.stable_for().is_some_and(|d| d < quiet) {
+                return false;
+            }
+            runtime.activity_seen = false;
+            runtime.growth.reset();
+            true
+        }
+        RuleTrigger::PortState {
+            host,
+            port,
+            when,
+            poll_secs,
+        } => {
+            if !runtime.should_poll(Duration::from_secs(*poll_secs)) {
+                return false;
+            }
+            let up = net::port_open(host, *port);
+            runtime.reached(up, *when, Duration::ZERO)
+        }
+        RuleTrigger::HostReachability {
+            host,
+            when,
+            minutes,
+            tcp_port,
+        } => {
+            if !runtime.should_poll(POLL_INTERVAL) {
+                return false;
+            }
+            let up = net::host_reachable(host, *tcp_port);
+            runtime.reached(up, *when, Duration::from_secs(minutes * 60))
+        }
+        RuleTrigger::ProcessIdle {
+            name,
+            cpu_below_percent,
+            minutes,
+        } => {
+            let Some(usage) = timers::process_cpu(sys, name) else {
+                // 进程退出后重新等待下一次活动
+                runtime.activity_seen = false;
+                return false;
+            };
+            if usage >= *cpu_below_percent {
+                runtime.activity_seen = true;
+                runtime.last_active = Some(Instant::now());
+                return false;
+            }
+            let quiet = Duration::from_secs(minutes * 60);
+            if !runtime.activity_seen || runtime.last_active.is_some_and(|t| t.elapsed() < quiet) {
+                return false;
+            }
+            runtime.activity_seen = false;
+            true
+        }
+        // 窗口事件钩子报告变化时才重新枚举窗口，钩子失效时按轮询间隔兜底
+        RuleTrigger::WindowTitle { pattern, process } => {
+            if runtime.title_pattern.as_ref().is_none_or(|(cached, _)| cached != pattern) {
+                let compiled = Regex::new(pattern).map_err(|e| e.to_string());
+                // 每个表达式只报告一次
+                if let Err(e) = &compiled {
+                    tracing::warn!(rule = %rule.id, error = %e, "invalid window title pattern");
+                    notify::dispatch(
+                        app,
+                        Notice::new(
+                            "AutoShutdown",
+                            format!("Rule \"{}\" is not checking window titles: {}", rule.name, e),
+                        )
+                        .with_kind(NoticeKind::General),
+                    );
+                }
+                runtime.title_pattern = Some((pattern.clone(), compiled));
+            }
+            // Regex 内部共享，克隆开销很小
+            let regex = match &runtime.title_pattern {
+                Some((_, Ok(regex))) => regex.clone(),
+                _ => return false,
+            };
+            let generation = titles::generation();
+            if generation == runtime.title_generation && !runtime.should_poll(POLL_INTERVAL) {
+                return false;
+            }
+            runtime.title_generation = generation;
+            let matched = titles::any_matches(sys, &regex, process.as_deref());
+            let fire = matched && !runtime.title_matched;
+            runtime.title_matched = matched;
+            fire
+        }
+        RuleTrigger::GpuIdle {
+            threshold_percent,
+            minutes,
+        } => {
+            if !runtime.should_poll(POLL_INTERVAL) {
+                return false;
+            }
+            let Some(usage) = gpu::utilization() else {
+                return false;
+            };
+            if usage >= *threshold_percent {
+                runtime.activity_seen = true;
+                runtime.last_active = Some(Instant::now());
+                return false;
+            }
+            let quiet = Duration::from_secs(minutes * 60);
+            if !runtime.activity_seen || runtime.last_active.is_some_and(|t| t.elapsed() < quiet) {
+                return false;
+            }
+            runtime.activity_seen = false;
+            true
+        }
+        RuleTrigger::Uptime { days } => {
+            let exceeded = uptime::uptime_secs() >= days * 24 * 60 * 60;
+            let fire = exceeded && !runtime.uptime_fired;
+            runtime.uptime_fired = exceeded;
+            fire
+        }
+        RuleTrigger::Watchdog(target) => {
+            let running = timers::process_running(sys, &target.name);
+            runtime.watchdog.check(app, &rule.id, target, running)
+        }
+        RuleTrigger::CrashLoop { name, count, minutes } => {
+            let window = Duration::from_secs(minutes * 60);
+            let events = app.state::<ProcessEvents>();
+            let exits: Vec<Instant> = if events.live() {
+                events
+                    .recent_exits(window)
+                    .into_iter()
+                    .filter(|(_, e)| timers::name_matches(&e.name, name) && e.exit_code != Some(0))
+                    .map(|(at, _)| at)
+                    .collect()
+            } else {
+                // 退回轮询时只能看到进程消失，得不到退出码
+                let running = timers::process_running(sys, name);
+                if runtime.process_seen && !running {
+                    runtime.polled_exits.push_back(Instant::now());
+                }
+                runtime.process_seen = running;
+                runtime.polled_exits.retain(|at| at.elapsed() < window);
+                runtime.polled_exits.iter().copied().collect()
+            };
+            // 已触发过的退出不再计入下一次
+            let counted = exits.iter().filter(|at| runtime.crash_loop_fired.is_none_or(|f| **at > f)).count();
+            if counted < *count as usize {
+                return false;
+            }
+            runtime.crash_loop_fired = Some(Instant::now());
+            true
+        }
+    }
+}
+
+// 规则触发后交给计时器引擎执行，与其他同时触发的项目统一裁决
+pub fn start_rule_engine(app: AppHandle) {
+    thread::spawn(move || {
+        let mut sys = System::new();
+        let mut refresh = ProcessRefresh::default();
+        let mut runtimes: HashMap<String, RuleRuntime> = HashMap::new();
+
+        loop {
+            let rules: Vec<Rule> = app
+                .state::<RuleStore>()
+                .all()
+                .into_iter()
+                .filter(|r| r.enabled)
+                .collect();
+            runtimes.retain(|id, _| rules.iter().any(|r| &r.id == id));
+
+            // 进程退出、崩溃循环和看护规则跟随进程事件刷新；看护规则等待重启时需要确认进程是否已经启动
+            let watched = rules
+                .iter()
+                .filter(|r| r.trigger.needs_processes())
+                .map(|r| r.id.clone())
+                .collect();
+            let needed = refresh.needed(&app.state::<ProcessEvents>(), watched);
+            if needed
+                || rules.iter().any(|r| r.trigger.polls_processes())
+                || runtimes.values().any(|r| r.watchdog.waiting())
+            {
+                let with_cmdline = rules.iter().any(|r| r.trigger.needs_cmdline());
+                timers::refresh_processes(&mut sys, with_cmdline);
+            }
+
+            for rule in &rules {
+                let runtime = runtimes.entry(rule.id.clone()).or_default();
+                if !check_trigger(&app, rule, runtime, &sys) {
+                    continue;
+                }
+                // 事件触发的规则把倒计时延长到最早的提醒之前
+                let fire_at = runtime.fire_at.take().unwrap_or_else(|| {
+                    let countdown = (rule.trigger.countdown_secs() as i64).max(rule.warning_lead());
+                    Local::now() + chrono::Duration::seconds(countdown)
+                });
+                tracing::info!(rule = %rule.id, name = %rule.name, "rule triggered");
+                if !arbitrate(&app, rule) {
+                    continue;
+                }
+                notify::dispatch(
+                    &app,
+                    Notice::new(
+                        "AutoShutdown",
+                        format!("Rule \"{}\" triggered: {}", rule.name, rule.action),
+                    )
+                    .with_kind(NoticeKind::RuleFired),
+                );
+                let spec = TimerSpec {
+                    priority: rule.priority,
+                    rule_id: Some(rule.id.clone()),
+                    rule: Some(rule.clone()),
+                    pre_action: rule.pre_action.clone(),
+                    defer: rule.defer.clone(),
+                    activity_abort: rule.abort_on_activity,
+                    grace_secs: rule.trigger.grace_secs(),
+                    ..TimerSpec::new(
+                        rule.name.clone(),
+                        rule.action.clone(),
+                        TimerTrigger::At { time: fire_at },
+                    )
+                };
+                let _ = app.state::<TimerStore>().arm(None, spec);
+            }
+
+            thread::sleep(Duration::from_secs(2));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rule(id: &str, trigger: serde_json::Value, action: &str) -> Rule {
+        serde_json::from_value(json!({ "id": id, "name": id, "trigger": trigger, "action": action })).unwrap()
+    }
+
+    fn pair_conflicts(a: serde_json::Value, b: serde_json::Value) -> bool {
+        conflicts(&rule("a", a, "shutdown"), &rule("b", b, "sleep"))
+    }
+
+    #[test]
+    fn same_process_overlaps() {
+        assert!(pair_conflicts(
+            json!({ "kind": "process_exit", "name": "render.exe" }),
+            json!({ "kind": "crash_loop", "name": "Render", "count": 3, "minutes": 10 }),
+        ));
+        assert!(pair_conflicts(
+            json!({ "kind": "processes_exit", "targets": [{ "name": "a" }, { "name": "render" }] }),
+            json!({ "kind": "process_idle", "name": "render.exe", "minutes": 5 }),
+        ));
+        // 只按命令行匹配的目标无法排除是同一进程
+        assert!(pair_conflicts(
+            json!({ "kind": "processes_exit", "targets": [{ "cmdline": "--job" }] }),
+            json!({ "kind": "process_exit", "name": "render" }),
+        ));
+    }
+
+    #[test]
+    fn different_processes_do_not_overlap() {
+        assert!(!pair_conflicts(
+            json!({ "kind": "process_exit", "name": "render.exe" }),
+            json!({ "kind": "process_exit", "name": "encoder.exe" }),
+        ));
+        assert!(!pair_conflicts(
+            json!({ "kind": "watchdog", "name": "qbittorrent", "command": "qbittorrent.exe" }),
+            json!({ "kind": "window_title", "pattern": "100%", "process": "obs64" }),
+        ));
+    }
+
+    #[test]
+    fn idle_thresholds() {
+        // 10 分钟开始倒计时 60 秒，另一条在 10.5 分钟触发
+        assert!(pair_conflicts(
+            json!({ "kind": "idle", "minutes": 10, "countdown_secs": 60 }),
+            json!({ "kind": "idle", "minutes": 10 }),
+        ));
+        assert!(pair_conflicts(
+            json!({ "kind": "idle", "minutes": 10, "countdown_secs": 120 }),
+            json!({ "kind": "idle", "minutes": 11, "countdown_secs": 0 }),
+        ));
+        // 宽限期同样计入
+        assert!(pair_conflicts(
+            json!({ "kind": "idle", "minutes": 10, "countdown_secs": 0, "grace_secs": 60 }),
+            json!({ "kind": "idle", "minutes": 11, "countdown_secs": 0 }),
+        ));
+        assert!(!pair_conflicts(
+            json!({ "kind": "idle", "minutes": 10, "countdown_secs": 60 }),
+            json!({ "kind": "idle", "minutes": 30, "countdown_secs": 60 }),
+        ));
+    }
+
+    #[test]
+    fn unrelated_kinds_do_not_overlap() {
+        assert!(!pair_conflicts(
+            json!({ "kind": "idle", "minutes": 10 }),
+            json!({ "kind": "process_exit", "name": "render" }),
+        ));
+        assert!(!pair_conflicts(
+            json!({ "kind": "schedule", "hour": 23, "minute": 0 }),
+            json!({ "kind": "idle", "minutes": 10 }),
+        ));
+        assert!(!pair_conflicts(
+            json!({ "kind": "downloads_finished", "folder": "D:\\Downloads" }),
+            json!({ "kind": "downloads_finished", "folder": "E:\\Torrents" }),
+        ));
+        assert!(pair_conflicts(
+            json!({ "kind": "downloads_finished", "folder": "D:\\Downloads\\" }),
+            json!({ "kind": "downloads_finished", "folder": "d:/downloads" }),
+        ));
+        assert!(pair_conflicts(json!({ "kind": "uptime", "days": 7 }), json!({ "kind": "uptime", "days": 14 })));
+    }
+
+    #[test]
+    fn schedules_by_time() {
+        assert!(pair_conflicts(
+            json!({ "kind": "schedule", "hour": 23, "minute": 50 }),
+            json!({ "kind": "schedule", "hour": 0, "minute": 10 }),
+        ));
+        assert!(!pair_conflicts(
+            json!({ "kind": "schedule", "hour": 22, "minute": 0 }),
+            json!({ "kind": "schedule", "hour": 23, "minute": 0 }),
+        ));
+        assert!(!pair_conflicts(
+            json!({ "kind": "schedule", "hour": 22, "minute": 0, "days": [5] }),
+            json!({ "kind": "schedule", "hour": 22, "minute": 0, "days": [6] }),
+        ));
+    }
+
+    #[test]
+    fn same_action_or_disabled_is_not_a_conflict() {
+        let trigger = json!({ "kind": "process_exit", "name": "render" });
+        assert!(!conflicts(&rule("a", trigger.clone(), "shutdown"), &rule("b", trigger.clone(), "shutdown")));
+        let mut disabled = rule("b", trigger.clone(), "sleep");
+        disabled.enabled = false;
+        assert!(!conflicts(&rule("a", trigger, "shutdown"), &disabled));
+    }
+}
@@ -0,0 +1,320 @@
+use crate::actions::Action;
+use crate::calendar::{self, CalendarCache, CalendarExceptions};
+use crate::error::AutoShutdownError;
+use crate::pre_action::PreActionOptions;
+use crate::settings::SettingsStore;
+use crate::storage::JsonStore;
+use crate::timers::{TimerSpec, TimerStore, TimerTrigger};
+use chrono::{DateTime, Datelike, Local, NaiveDate, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DayFilter {
+    #[default]
+    Any,
+    // 周一至周五
+    Weekdays,
+    // 周六、周日
+    Weekends,
+}
+
+// 用户维护的节假日，设置了跳过节假日的日程当天不执行
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    #[serde(default)]
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Recurrence {
+    pub hour: u8,
+    pub minute: u8,
+    // 0 = 周一 ... 6 = 周日，为空表示每天
+    #[serde(default)]
+    pub days: Vec<u8>,
+    // 与 days 同时生效，只在两者都包含的日子执行
+    #[serde(default)]
+    pub day_filter: DayFilter,
+    #[serde(default)]
+    pub skip_holidays: bool,
+}
+
+impl Recurrence {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hour > 23 || self.minute > 59 || self.days.iter().any(|d| *d > 6) {
+            return Err("Invalid schedule time".into());
+        }
+        if self.effective_days().is_none() {
+            return Err("Invalid schedule: the selected days are excluded by the weekday filter".into());
+        }
+        Ok(())
+    }
+
+    pub fn runs_on(&self, weekday: u8) -> bool {
+        let allowed = match self.day_filter {
+            DayFilter::Any => true,
+            DayFilter::Weekdays => weekday < 5,
+            DayFilter::Weekends => weekday >= 5,
+        };
+        allowed && (self.days.is_empty() || self.days.contains(&weekday))
+    }
+
+    // 实际执行的星期，每天执行时为空，没有任何一天执行时为 None
+    pub fn effective_days(&self) -> Option<Vec<u8>> {
+        let days: Vec<u8> = (0..7).filter(|d| self.runs_on(*d)).collect();
+        match days.len() {
+            0 => None,
+            7 => Some(Vec::new()),
+            _ => Some(days),
+        }
+    }
+
+    pub fn matches(&self, now: &DateTime<Local>, holidays: &[Holiday]) -> bool {
+        self.runs_on(now.weekday().num_days_from_monday() as u8)
+            && !(self.skip_holidays && holidays.iter().any(|h| h.date == now.date_naive()))
+            && self.hour as u32 == now.hour()
+            && self.minute as u32 == now.minute()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Schedule {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub action: Action,
+    #[serde(flatten)]
+    pub recurrence: Recurrence,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    // 同时注册为 Windows 任务计划程序任务，应用未运行时也能执行
+    #[serde(default)]
+    pub mirror_to_task_scheduler: bool,
+    #[serde(default)]
+    pub pre_action: PreActionOptions,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calendar_exceptions: Option<CalendarExceptions>,
+}
+
+pub fn default_true() -> bool {
+    true
+}
+
+impl Schedule {
+    // 镜像到任务计划程序的日程由系统负责触发，应用内不再重复执行
+    // 只有电源操作有对应的命令行，其他操作即使勾选了镜像也由应用内执行
+    // 任务计划程序无法查询日历和节假日，设置了这些例外的日程同样由应用内执行
+    pub fn mirrored(&self) -> bool {
+        self.mirror_to_task_scheduler
+            && self.action.power().is_some()
+            && self.calendar_exceptions.is_none()
+            && !self.recurrence.skip_holidays
+    }
+
+    pub fn fired_by_task_scheduler(&self) -> bool {
+        cfg!(windows) && self.mirrored()
+    }
+}
+
+pub struct ScheduleStore(JsonStore<Vec<Schedule>>);
+
+impl ScheduleStore {
+    pub fn load(app: &AppHandle) -> Self {
+        ScheduleStore(JsonStore::load(app, "schedules.json"))
+    }
+
+    pub fn all(&self) -> Vec<Schedule> {
+        self.0.get()
+    }
+
+    pub fn reload_if_changed(&self) -> Result<bool, String> {
+        self.0.reload_if_changed()
+    }
+
+    pub fn replace_all(&self, schedules: Vec<Schedule>) -> Result<(), String> {
+        self.update(|current| *current = schedules)
+    }
+
+    fn update<R>(&self, f: impl FnOnce(&mut Vec<Schedule>) -> R) -> Result<R, String> {
+        self.0.update(f)
+    }
+}
+
+#[tauri::command]
+pub fn get_schedules(store: State<'_, ScheduleStore>) -> Vec<Schedule> {
+    store.all()
+}
+
+#[tauri::command]
+pub fn save_schedule(
+    app: AppHandle,
+    store: State<'_, ScheduleStore>,
+    mut schedule: Schedule,
+) -> Result<Schedule, AutoShutdownError> {
+    schedule.recurrence.validate()?;
+    schedule.action.validate()?;
+    if schedule.id.is_empty() {
+        schedule.id = uuid::Uuid::new_v4().to_string();
+    }
+    let saved = schedule.clone();
+    store.update(|schedules| {
+        if let Some(existing) = schedules.iter_mut().find(|s| s.id == schedule.id) {
+            *existing = schedule;
+        } else {
+            schedules.push(schedule);
+        }
+    })?;
+    #[cfg(windows)]
+    crate::task_scheduler::sync(&store.all())?;
+    // 立即读取新设置的日历，不必等到下一次定期刷新
+    if saved.calendar_exceptions.is_some() {
+        thread::spawn(move || calendar::refresh(&app));
+    }
+    Ok(saved)
+}
+
+#[tauri::command]
+pub fn delete_schedule(store: State<'_, ScheduleStore>, id: String) -> Result<bool, AutoShutdownError> {
+    let removed = store.update(|schedules| {
+        let before = schedules.len();
+        schedules.retain(|s| s.id != id);
+        schedules.len() != before
+    })?;
+    #[cfg(windows)]
+    crate::task_scheduler::sync(&store.all())?;
+    Ok(removed)
+}
+
+// 应用内调度：每 15 秒检查一次，同一分钟内每个日程只触发一次
+pub fn start_scheduler(app: AppHandle) {
+    thread::spawn(move || {
+        let mut last_fired: HashMap<String, String> = HashMap::new();
+        loop {
+            let now = Local::now();
+            let minute_key = now.format("%Y-%m-%d %H:%M").to_string();
+            let holidays = app.state::<SettingsStore>().get().holidays;
+
+            for schedule in app.state::<ScheduleStore>().all() {
+                if !schedule.enabled
+                    || schedule.fired_by_task_scheduler()
+                    || !schedule.recurrence.matches(&now, &holidays)
+                {
+                    continue;
+                }
+                if last_fired.get(&schedule.id) == Some(&minute_key) {
+                    continue;
+                }
+                last_fired.insert(schedule.id.clone(), minute_key.clone());
+                if let Some(exceptions) = &schedule.calendar_exceptions {
+                    if app.state::<CalendarCache>().skips(exceptions, &now) {
+                        tracing::info!(schedule = %schedule.id, "schedule skipped by calendar event");
+                        continue;
+                    }
+                }
+                tracing::info!(schedule = %schedule.id, action = ?schedule.action, "schedule due");
+                // 交给计时器引擎执行，与其他同时到期的项目统一裁决
+                let spec = TimerSpec {
+                    pre_action: schedule.pre_action.clone(),
+                    ..TimerSpec::new(
+                        schedule.name.clone(),
+                        schedule.action.clone(),
+                        TimerTrigger::At { time: now },
+                    )
+                };
+                let _ = app.state::<TimerStore>().arm(None, spec);
+            }
+
+            thread::sleep(Duration::from_secs(15));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn recurrence(hour: u8, minute: u8, days: &[u8], day_filter: DayFilter) -> Recurrence {
+        Recurrence {
+            hour,
+            minute,
+            days: days.to_vec(),
+            day_filter,
+            skip_holidays: false,
+        }
+    }
+
+    // 2026-10-12 是周一
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn day_filters_split_the_week() {
+        let weekdays = recurrence(22, 30, &[], DayFilter::Weekdays);
+        let weekends = recurrence(22, 30, &[], DayFilter::Weekends);
+        for weekday in 0..7 {
+            assert_eq!(weekdays.runs_on(weekday), weekday < 5, "weekday {weekday}");
+            assert_eq!(weekends.runs_on(weekday), weekday >= 5, "weekday {weekday}");
+        }
+        assert_eq!(weekdays.effective_days(), Some(vec![0, 1, 2, 3, 4]));
+        assert_eq!(weekends.effective_days(), Some(vec![5, 6]));
+        assert_eq!(recurrence(22, 30, &[], DayFilter::Any).effective_days(), Some(Vec::new()));
+    }
+
+    #[test]
+    fn week_boundaries() {
+        let weekdays = recurrence(22, 30, &[], DayFilter::Weekdays);
+        // 周五执行，周六、周日不执行，下一个周一重新执行
+        assert!(weekdays.matches(&at(16, 22, 30), &[]));
+        assert!(!weekdays.matches(&at(17, 22, 30), &[]));
+        assert!(!weekdays.matches(&at(18, 22, 30), &[]));
+        assert!(weekdays.matches(&at(19, 22, 30), &[]));
+        // 周日与周一之间的午夜属于周一
+        let weekends = recurrence(0, 0, &[], DayFilter::Weekends);
+        assert!(weekends.matches(&at(18, 0, 0), &[]));
+        assert!(!weekends.matches(&at(19, 0, 0), &[]));
+        assert!(weekends.matches(&at(17, 0, 0), &[]));
+    }
+
+    #[test]
+    fn days_and_filter_intersect() {
+        let friday_and_sunday = recurrence(23, 0, &[4, 6], DayFilter::Weekdays);
+        assert!(friday_and_sunday.runs_on(4));
+        assert!(!friday_and_sunday.runs_on(6));
+        assert_eq!(friday_and_sunday.effective_days(), Some(vec![4]));
+        assert!(recurrence(23, 0, &[5, 6], DayFilter::Weekdays).validate().is_err());
+        assert!(recurrence(23, 0, &[5, 6], DayFilter::Weekends).validate().is_ok());
+    }
+
+    #[test]
+    fn fires_only_on_the_anchor_minute() {
+        let nightly = recurrence(23, 59, &[], DayFilter::Any);
+        assert!(nightly.matches(&at(12, 23, 59), &[]));
+        assert!(nightly.matches(&(at(12, 23, 59) + chrono::Duration::seconds(59)), &[]));
+        assert!(!nightly.matches(&at(12, 23, 58), &[]));
+        assert!(!nightly.matches(&at(13, 0, 0), &[]));
+        assert!(recurrence(24, 0, &[], DayFilter::Any).validate().is_err());
+        assert!(recurrence(23, 60, &[], DayFilter::Any).validate().is_err());
+    }
+
+    #[test]
+    fn skips_holidays_only_when_enabled() {
+        let holidays = [Holiday {
+            date: NaiveDate::from_ymd_opt(2026, 10, 16).unwrap(),
+            name: "Movie night".into(),
+        }];
+        let mut weekdays = recurrence(22, 30, &[], DayFilter::Weekdays);
+        assert!(weekdays.matches(&at(16, 22, 30), &holidays));
+        weekdays.skip_holidays = true;
+        assert!(!weekdays.matches(&at(16, 22, 30), &holidays));
+        assert!(weekdays.matches(&at(15, 22, 30), &holidays));
+    }
+}
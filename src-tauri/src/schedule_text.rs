@@ -0,0 +1,286 @@
+use crate::error::AutoShutdownError;
+use crate::timers::TimerTrigger;
+use chrono::{DateTime, Days, Local, NaiveTime, TimeZone};
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ParsedSchedule {
+    // After 或 At，可直接作为计时的触发条件
+    pub trigger: TimerTrigger,
+    // 解析时换算出的到期时间，供界面预览
+    pub time: DateTime<Local>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Meridiem {
+    Am,
+    Pm,
+    // 中午 1 点即 13 点，中午 11 点仍是 11 点
+    Noon,
+}
+
+// 表示相对时间的前后缀，带有这些词时只按时长解析
+const RELATIVE_PREFIXES: &[&str] = &["in "];
+const RELATIVE_SUFFIXES: &[&str] = &[" from now", " later", "以后", "之后", "后"];
+
+// 日期词：相对今天的天数及隐含的上下午
+const DAY_WORDS: &[(&str, (u64, Option<Meridiem>))] = &[
+    ("tomorrow", (1, None)),
+    ("tonight", (0, Some(Meridiem::Pm))),
+    ("today", (0, None)),
+    ("明早", (1, Some(Meridiem::Am))),
+    ("明晚", (1, Some(Meridiem::Pm))),
+    ("明天", (1, None)),
+    ("后天", (2, None)),
+    ("今晚", (0, Some(Meridiem::Pm))),
+    ("今天", (0, None)),
+];
+
+const MERIDIEM_PREFIXES: &[(&str, Meridiem)] = &[
+    ("凌晨", Meridiem::Am),
+    ("早上", Meridiem::Am),
+    ("早晨", Meridiem::Am),
+    ("上午", Meridiem::Am),
+    ("中午", Meridiem::Noon),
+    ("下午", Meridiem::Pm),
+    ("傍晚", Meridiem::Pm),
+    ("晚上", Meridiem::Pm),
+];
+
+const MERIDIEM_SUFFIXES: &[(&str, Meridiem)] = &[
+    ("a.m.", Meridiem::Am),
+    ("p.m.", Meridiem::Pm),
+    ("am", Meridiem::Am),
+    ("pm", Meridiem::Pm),
+];
+
+fn unit_seconds(unit: &str) -> Option<f64> {
+    match unit {
+        "s" | "sec" | "secs" | "second" | "seconds" | "秒" | "秒钟" => Some(1.0),
+        "m" | "min" | "mins" | "minute" | "minutes" | "分" | "分钟" => Some(60.0),
+        "h" | "hr" | "hrs" | "hour" | "hours" | "小时" | "钟头" => Some(3600.0),
+        "d" | "day" | "days" | "天" => Some(86400.0),
+        _ => None,
+    }
+}
+
+// 2h30m、1.5 hours、90 min、1个半小时，纯数字按分钟
+pub fn parse_duration(text: &str) -> Option<u64> {
+    let text = text
+        .trim()
+        .to_lowercase()
+        .replace("个半小时", ".5小时")
+        .replace("半小时", "0.5小时")
+        .replace("个小时", "小时")
+        .replace("个钟头", "小时")
+        .replace(" and ", " ")
+        .replace([',', '，'], " ");
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if let Ok(minutes) = text.parse::<f64>() {
+        return (minutes > 0.0).then(|| (minutes * 60.0).round() as u64);
+    }
+
+    let mut total = 0.0;
+    let mut rest = text.as_str();
+    while !rest.is_empty() {
+        let split = rest.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(rest.len());
+        let number: f64 = rest[..split].parse().ok()?;
+        rest = &rest[split..];
+        let unit_end = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        total += number * unit_seconds(&rest[..unit_end])?;
+        rest = &rest[unit_end..];
+    }
+    let seconds = total.round() as u64;
+    (seconds > 0).then_some(seconds)
+}
+
+fn strip_any<'a, T: Copy>(text: &'a str, words: &[(&str, T)], prefix: bool) -> (&'a str, Option<T>) {
+    for (word, value) in words {
+        let stripped = if prefix { text.strip_prefix(word) } else { text.strip_suffix(word) };
+        if let Some(rest) = stripped {
+            return (rest.trim(), Some(*value));
+        }
+    }
+    (text, None)
+}
+
+// 23:45、7:05pm、7 am、11.30pm、23点45分、7点半、下午3点，未写上下午时使用 implied
+fn parse_clock(text: &str, implied: Option<Meridiem>) -> Option<NaiveTime> {
+    let (text, meridiem) = match text {
+        "noon" | "中午" => ("12", None),
+        "midnight" | "午夜" => ("0", None),
+        other => {
+            let (other, prefix) = strip_any(other, MERIDIEM_PREFIXES, true);
+            let (other, suffix) = strip_any(other, MERIDIEM_SUFFIXES, false);
+            (other, prefix.or(suffix).or(implied))
+        }
+    };
+    let clock = text
+        .replace("点半", ":30")
+        .replace("点钟", ":")
+        .replace(['点', '时', '.'], ":")
+        .trim_end_matches('分')
+        .trim_end_matches(':')
+        .to_string();
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) => (h.trim().parse::<u32>().ok()?, m.trim().parse::<u32>().ok()?),
+        None => (clock.trim().parse::<u32>().ok()?, 0),
+    };
+    let hour = match meridiem {
+        Some(_) if hour > 12 => hour,
+        Some(Meridiem::Am) if hour == 12 => 0,
+        Some(Meridiem::Pm) if hour < 12 => hour + 12,
+        Some(Meridiem::Noon) if hour < 6 => hour + 12,
+        _ => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+// at 23:45、tomorrow 7am、7am tomorrow、明天早上7点；未指定日期且时间已过时顺延到明天
+fn parse_at(text: &str, now: DateTime<Local>) -> Result<Option<DateTime<Local>>, String> {
+    let text = text.strip_prefix("at ").unwrap_or(text).trim();
+    let (mut rest, mut day) = strip_any(text, DAY_WORDS, true);
+    if day.is_none() {
+        (rest, day) = strip_any(text, DAY_WORDS, false);
+    }
+    let rest = rest.strip_prefix("at ").unwrap_or(rest).trim();
+    let rest = rest.strip_suffix(" at").unwrap_or(rest).trim();
+    let Some(clock) = parse_clock(rest, day.and_then(|(_, implied)| implied)) else {
+        return Ok(None);
+    };
+
+    let resolve = |days: u64| {
+        let date = now.date_naive().checked_add_days(Days::new(days))?;
+        Local.from_local_datetime(&date.and_time(clock)).earliest()
+    };
+    let time = resolve(day.map_or(0, |(days, _)| days)).ok_or("Invalid time: it does not exist in the local time zone")?;
+    if time > now {
+        return Ok(Some(time));
+    }
+    if day.is_some() {
+        return Err(format!("Invalid time: {} has already passed", time.format("%Y-%m-%d %H:%M")));
+    }
+    Ok(Some(resolve(1).ok_or("Invalid time: it does not exist in the local time zone")?))
+}
+
+pub fn parse(text: &str, now: DateTime<Local>) -> Result<ParsedSchedule, String> {
+    let text = text.trim().to_lowercase();
+    if text.is_empty() {
+        return Err("Invalid schedule: enter a delay such as 2h 30m or a time such as 23:45".into());
+    }
+    let after = |seconds: u64| ParsedSchedule {
+        trigger: TimerTrigger::After { seconds },
+        time: now + chrono::Duration::seconds(seconds as i64),
+    };
+
+    let mut relative = text.as_str();
+    for prefix in RELATIVE_PREFIXES {
+        relative = relative.strip_prefix(prefix).unwrap_or(relative);
+    }
+    for suffix in RELATIVE_SUFFIXES {
+        relative = relative.strip_suffix(suffix).unwrap_or(relative);
+    }
+    if relative != text {
+        return parse_duration(relative)
+            .map(after)
+            .ok_or_else(|| format!("Invalid delay \"{}\", try 2h 30m or 90m", relative.trim()));
+    }
+    if let Some(seconds) = parse_duration(&text) {
+        return Ok(after(seconds));
+    }
+    match parse_at(&text, now)? {
+        Some(time) => Ok(ParsedSchedule {
+            trigger: TimerTrigger::At { time },
+            time,
+        }),
+        None => Err(format!(
+            "Invalid schedule \"{}\", try \"in 2h 30m\", \"at 23:45\" or \"tomorrow 7am\"",
+            text
+        )),
+    }
+}
+
+// 界面的快速输入框和外部链接共用同一个解析器
+#[tauri::command]
+pub fn parse_schedule(text: String) -> Result<ParsedSchedule, AutoShutdownError> {
+    parse(&text, Local::now()).map_err(AutoShutdownError::InvalidInput)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2026-06-10 22:00，避开夏令时切换
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 6, 10, 22, 0, 0).unwrap()
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 6, day, hour, minute, 0).unwrap()
+    }
+
+    fn after_secs(text: &str) -> u64 {
+        match parse(text, now()).unwrap().trigger {
+            TimerTrigger::After { seconds } => seconds,
+            other => panic!("{text}: expected a delay, got {other:?}"),
+        }
+    }
+
+    fn at_time(text: &str) -> DateTime<Local> {
+        match parse(text, now()).unwrap().trigger {
+            TimerTrigger::At { time } => time,
+            other => panic!("{text}: expected a time, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn relative_forms() {
+        assert_eq!(after_secs("in 2h 30m"), 9000);
+        assert_eq!(after_secs("1.5 hours"), 5400);
+        assert_eq!(after_secs("90"), 5400);
+        assert_eq!(after_secs("10 minutes from now"), 600);
+        assert_eq!(after_secs("1个半小时后"), 5400);
+        assert_eq!(after_secs("30分钟以后"), 1800);
+        assert_eq!(parse("in 45s", now()).unwrap().time, now() + chrono::Duration::seconds(45));
+    }
+
+    #[test]
+    fn absolute_forms() {
+        assert_eq!(at_time("at 23:45"), at(10, 23, 45));
+        assert_eq!(at_time("tomorrow 7am"), at(11, 7, 0));
+        assert_eq!(at_time("7am tomorrow"), at(11, 7, 0));
+        assert_eq!(at_time("tomorrow at 18:30"), at(11, 18, 30));
+        assert_eq!(at_time("明天早上7点"), at(11, 7, 0));
+        assert_eq!(at_time("今晚11点半"), at(10, 23, 30));
+    }
+
+    #[test]
+    fn twelve_and_twenty_four_hour_times() {
+        assert_eq!(at_time("11:30pm"), at(10, 23, 30));
+        assert_eq!(at_time("11.30 p.m."), at(10, 23, 30));
+        assert_eq!(at_time("23:30"), at(10, 23, 30));
+        assert_eq!(at_time("23点30分"), at(10, 23, 30));
+        assert_eq!(at_time("tomorrow 12am"), at(11, 0, 0));
+        assert_eq!(at_time("tomorrow 12pm"), at(11, 12, 0));
+        assert_eq!(at_time("tomorrow noon"), at(11, 12, 0));
+        assert_eq!(at_time("明天中午1点"), at(11, 13, 0));
+        assert_eq!(at_time("明天下午3点"), at(11, 15, 0));
+    }
+
+    #[test]
+    fn rolls_over_past_midnight() {
+        assert_eq!(at_time("at 21:00"), at(11, 21, 0));
+        assert_eq!(at_time("7am"), at(11, 7, 0));
+        assert_eq!(at_time("midnight"), at(11, 0, 0));
+        assert_eq!(parse("in 3h", now()).unwrap().time, at(11, 1, 0));
+        // 明确写了今天的已过时间不顺延
+        assert!(parse("today 21:00", now()).is_err());
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        for text in ["", "   ", "banana", "at", "25:00", "12:75", "in soon", "2x", "-5", "tomorrow"] {
+            assert!(parse(text, now()).is_err(), "{text:?} should be rejected");
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::feasibility;
+use crate::notify::{self, Notice};
+use crate::process_events::ProcessEvents;
+use crate::storage;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Manager};
+
+// 计时精度检查的等待时间和允许的误差
+const TIMER_SAMPLE: Duration = Duration::from_secs(1);
+const TIMER_TOLERANCE: Duration = Duration::from_millis(250);
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    // 当前系统不适用，如非 Windows 上的窗口钩子
+    Skipped,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SelfTestCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: Option<String>,
+}
+
+impl SelfTestCheck {
+    fn new(name: &'static str, result: Result<(), String>) -> Self {
+        SelfTestCheck {
+            name,
+            status: if result.is_ok() { CheckStatus::Pass } else { CheckStatus::Fail },
+            detail: result.err(),
+        }
+    }
+
+    fn skipped(name: &'static str, detail: &str) -> Self {
+        SelfTestCheck {
+            name,
+            status: CheckStatus::Skipped,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SelfTestReport {
+    pub ran_at: DateTime<Local>,
+    // 所有检查均通过或跳过
+    pub passed: bool,
+    pub checks: Vec<SelfTestCheck>,
+}
+
+fn window_hook() -> SelfTestCheck {
+    #[cfg(windows)]
+    {
+        let result = if crate::window_watcher::is_running() {
+            Ok(())
+        } else {
+            Err("The window event hook is not installed".into())
+        };
+        SelfTestCheck::new("window_hook", result)
+    }
+    #[cfg(not(windows))]
+    {
+        SelfTestCheck::skipped("window_hook", "Window hooks are only supported on Windows")
+    }
+}
+
+// 进程事件不可用时退回定期扫描，仍能工作但反应较慢
+fn process_watcher(app: &AppHandle) -> SelfTestCheck {
+    if !cfg!(windows) {
+        return SelfTestCheck::skipped("process_watcher", "Process events are only supported on Windows");
+    }
+    let result = if app.state::<ProcessEvents>().live() {
+        Ok(())
+    } else {
+        Err("Process events are unavailable, falling back to polling every 30 seconds".into())
+    };
+    SelfTestCheck::new("process_watcher", result)
+}
+
+fn shutdown_privilege() -> SelfTestCheck {
+    if !cfg!(windows) {
+        return SelfTestCheck::skipped("shutdown_privilege", "Power actions are only supported on Windows");
+    }
+    let result = match feasibility::shutdown_privilege() {
+        Some(true) => Ok(()),
+        Some(false) => Err("This account does not have the shutdown privilege (SeShutdownPrivilege)".into()),
+        None => Err("Could not query the privileges of this account".into()),
+    };
+    SelfTestCheck::new("shutdown_privilege", result)
+}
+
+fn notification(app: &AppHandle) -> SelfTestCheck {
+    let notice = Notice::new("AutoShutdown", "Self-test notification");
+    SelfTestCheck::new("notification", notify::show_toast(app, &notice))
+}
+
+// 在应用数据目录写入、读回并删除一个测试文件
+fn config_storage(app: &AppHandle) -> SelfTestCheck {
+    let path = storage::data_path(app, "self_test.json");
+    let written = Local::now().to_rfc3339();
+    let result = storage::save(&path, &written).and_then(|_| {
+        let read: String = storage::load(&path);
+        if read == written {
+            Ok(())
+        } else {
+            Err(format!("{} could not be read back", path.display()))
+        }
+    });
+    let _ = fs::remove_file(&path);
+    SelfTestCheck::new("config_storage", result)
+}
+
+// 比较单调时钟和系统时钟，发现系统过载或时钟跳变
+fn timer_accuracy() -> SelfTestCheck {
+    let (started, wall_started) = (Instant::now(), SystemTime::now());
+    thread::sleep(TIMER_SAMPLE);
+    let elapsed = started.elapsed();
+    let wall = wall_started.elapsed().unwrap_or_default();
+    let result = if elapsed > TIMER_SAMPLE + TIMER_TOLERANCE {
+        Err(format!("Sleeping {} ms took {} ms", TIMER_SAMPLE.as_millis(), elapsed.as_millis()))
+    } else if wall.abs_diff(elapsed) > TIMER_TOLERANCE {
+        Err(format!(
+            "The system clock drifted {} ms from the monotonic clock",
+            wall.abs_diff(elapsed).as_millis()
+        ))
+    } else {
+        Ok(())
+    };
+    SelfTestCheck::new("timer_accuracy", result)
+}
+
+// 检查无人值守执行所依赖的前提条件，供界面逐项显示
+#[tauri::command(async)]
+pub fn run_self_test(app: AppHandle) -> SelfTestReport {
+    let checks = vec![
+        window_hook(),
+        process_watcher(&app),
+        shutdown_privilege(),
+        notification(&app),
+        config_storage(&app),
+        timer_accuracy(),
+    ];
+    let passed = checks.iter().all(|c| c.status != CheckStatus::Fail);
+    let failed: Vec<&str> = checks.iter().filter(|c| c.status == CheckStatus::Fail).map(|c| c.name).collect();
+    tracing::info!(passed, ?failed, "self-test finished");
+    SelfTestReport {
+        ran_at: Local::now(),
+        passed,
+        checks,
+    }
+}
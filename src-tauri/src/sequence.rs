@@ -0,0 +1,308 @@
+use crate::audit::{ActionSource, AuditAction};
+use crate::error::AutoShutdownError;
+use crate::executor;
+use crate::hooks::{self, HookScript};
+use crate::power::PowerAction;
+use crate::pre_action::PreActionOptions;
+use crate::storage::JsonStore;
+use crate::timers;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{ProcessesToUpdate, System};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// 结束进程后等待其退出的默认时长
+const DEFAULT_KILL_TIMEOUT: u64 = 10;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StepAction {
+    KillProcess { name: String },
+    Wait { seconds: u64 },
+    RunScript { script: HookScript },
+    Power { action: PowerAction },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicy {
+    #[default]
+    Abort,
+    Continue,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SequenceStep {
+    #[serde(flatten)]
+    pub action: StepAction,
+    // 覆盖该步骤的默认超时
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub on_failure: FailurePolicy,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ActionSequence {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub steps: Vec<SequenceStep>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+enum StepStatus {
+    Started,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Serialize, Clone)]
+struct StepProgress<'a> {
+    run_id: &'a str,
+    sequence_id: &'a str,
+    step: usize,
+    total: usize,
+    status: StepStatus,
+    error: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+struct SequenceFinished<'a> {
+    run_id: &'a str,
+    sequence_id: &'a str,
+    success: bool,
+    cancelled: bool,
+}
+
+pub struct SequenceStore {
+    sequences: JsonStore<Vec<ActionSequence>>,
+    // 正在执行的运行 id，只有其中的运行可以取消
+    active: Mutex<HashSet<String>>,
+    cancelled: Mutex<HashSet<String>>,
+}
+
+impl SequenceStore {
+    pub fn load(app: &AppHandle) -> Self {
+        SequenceStore {
+            sequences: JsonStore::load(app, "sequences.json"),
+            active: Mutex::new(HashSet::new()),
+            cancelled: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<ActionSequence> {
+        self.sequences.get().into_iter().find(|s| s.id == id)
+    }
+
+    fn is_cancelled(&self, run_id: &str) -> bool {
+        self.cancelled.lock().unwrap().contains(run_id)
+    }
+
+    fn cancel(&self, run_id: &str) -> bool {
+        let active = self.active.lock().unwrap();
+        if !active.contains(run_id) {
+            return false;
+        }
+        self.cancelled.lock().unwrap().insert(run_id.to_string());
+        true
+    }
+
+    // 运行结束时移出，返回期间是否被取消
+    fn finish(&self, run_id: &str) -> bool {
+        let mut active = self.active.lock().unwrap();
+        active.remove(run_id);
+        self.cancelled.lock().unwrap().remove(run_id)
+    }
+}
+
+fn kill_by_name(app: &AppHandle, name: &str, source: &ActionSource, timeout: u64) -> Result<(), String> {
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::All, true);
+    let targets: Vec<(u32, String)> = sys
+        .processes()
+        .values()
+        .filter(|p| timers::name_matches(&p.name().to_string_lossy(), name))
+        .map(|p| (p.pid().as_u32(), p.name().to_string_lossy().to_string()))
+        .collect();
+    for (pid, name) in targets {
+        let _ = executor::perform(app, AuditAction::Kill { pid, name }, source.clone());
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(timeout);
+    loop {
+        sys.refresh_processes(ProcessesToUpdate::All, true);
+        if !timers::process_running(&sys, name) {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(format!("{} is still running", name));
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+}
+
+fn run_step(
+    app: &AppHandle,
+    store: &SequenceStore,
+    run_id: &str,
+    step: &SequenceStep,
+    source: &ActionSource,
+) -> Result<(), String> {
+    match &step.action {
+        StepAction::KillProcess { name } => {
+            kill_by_name(app, name, source, step.timeout_secs.unwrap_or(DEFAULT_KILL_TIMEOUT))
+        }
+        StepAction::Wait { seconds } => {
+            let deadline = Instant::now() + Duration::from_secs(*seconds);
+            while Instant::now() < deadline {
+                if store.is_cancelled(run_id) {
+                    return Err("Cancelled".into());
+                }
+                thread::sleep(Duration::from_millis(250));
+            }
+            Ok(())
+        }
+        StepAction::RunScript { script } => {
+            let mut script = script.clone();
+            if let Some(timeout) = step.timeout_secs {
+                script.timeout_secs = timeout;
+            }
+            let result = hooks::run(app, &script)?;
+            if result.succeeded() {
+                Ok(())
+            } else if result.timed_out {
+                Err(format!("{} timed out", script.path))
+            } else {
+                Err(format!("{} exited with {:?}", script.path, result.exit_code))
+            }
+        }
+        StepAction::Power { action } => {
+            executor::perform_power(app, *action, source.clone(), &PreActionOptions::default()).map_err(|e| e.to_string())
+        }
+    }
+}
+
+pub fn run(app: &AppHandle, sequence: &ActionSequence, run_id: &str) -> bool {
+    let store = app.state::<SequenceStore>();
+    let source = ActionSource::Sequence {
+        id: sequence.id.clone(),
+        name: sequence.name.clone(),
+    };
+    let total = sequence.steps.len();
+    let progress = |step: usize, status: StepStatus, error: Option<String>| {
+        let _ = app.emit(
+            "sequence-progress",
+            StepProgress {
+                run_id,
+                sequence_id: &sequence.id,
+                step,
+                total,
+                status,
+                error,
+            },
+        );
+    };
+
+    store.active.lock().unwrap().insert(run_id.to_string());
+    tracing::info!(sequence = %sequence.id, run_id, "sequence started");
+    let mut success = true;
+    for (index, step) in sequence.steps.iter().enumerate() {
+        if store.is_cancelled(run_id) {
+            success = false;
+            break;
+        }
+        progress(index, StepStatus::Started, None);
+        match run_step(app, &store, run_id, step, &source) {
+            Ok(()) => progress(index, StepStatus::Succeeded, None),
+            Err(e) => {
+                tracing::warn!(sequence = %sequence.id, step = index, error = %e, "sequence step failed");
+                progress(index, StepStatus::Failed, Some(e));
+                if step.on_failure == FailurePolicy::Abort {
+                    success = false;
+                    break;
+                }
+            }
+        }
+    }
+
+    let cancelled = store.finish(run_id);
+    tracing::info!(sequence = %sequence.id, run_id, success, cancelled, "sequence finished");
+    let _ = app.emit(
+        "sequence-finished",
+        SequenceFinished {
+            run_id,
+            sequence_id: &sequence.id,
+            success,
+            cancelled,
+        },
+    );
+    success
+}
+
+#[tauri::command]
+pub fn list_sequences(store: State<'_, SequenceStore>) -> Vec<ActionSequence> {
+    store.sequences.get()
+}
+
+#[tauri::command]
+pub fn save_sequence(
+    store: State<'_, SequenceStore>,
+    mut sequence: ActionSequence,
+) -> Result<ActionSequence, AutoShutdownError> {
+    if sequence.steps.is_empty() {
+        return Err("Sequence must have at least one step".into());
+    }
+    if sequence.id.is_empty() {
+        sequence.id = uuid::Uuid::new_v4().to_string();
+    }
+    let saved = sequence.clone();
+    store.sequences.update(|sequences| {
+        match sequences.iter_mut().find(|s| s.id == sequence.id) {
+            Some(existing) => *existing = sequence,
+            None => sequences.push(sequence),
+        }
+    })?;
+    Ok(saved)
+}
+
+#[tauri::command]
+pub fn delete_sequence(store: State<'_, SequenceStore>, id: String) -> Result<bool, AutoShutdownError> {
+    store.sequences.update(|sequences| {
+        let before = sequences.len();
+        sequences.retain(|s| s.id != id);
+        sequences.len() != before
+    })
+    .map_err(Into::into)
+}
+
+// 在后台执行，立即返回本次运行的 id，进度通过事件推送
+#[tauri::command]
+pub fn run_sequence(app: AppHandle, store: State<'_, SequenceStore>, id: String) -> Result<String, AutoShutdownError> {
+    let sequence = store
+        .get(&id)
+        .ok_or_else(|| AutoShutdownError::NotFound(format!("Sequence {} not found", id)))?;
+    let run_id = uuid::Uuid::new_v4().to_string();
+    // 返回前登记，调用方拿到 id 后可以立即取消
+    store.active.lock().unwrap().insert(run_id.clone());
+    let handle_run_id = run_id.clone();
+    thread::spawn(move || {
+        run(&app, &sequence, &handle_run_id);
+    });
+    Ok(run_id)
+}
+
+// 已结束或不存在的运行返回错误，不留下永远不会被清除的取消标记
+#[tauri::command]
+pub fn cancel_sequence(store: State<'_, SequenceStore>, run_id: String) -> Result<(), AutoShutdownError> {
+    if store.cancel(&run_id) {
+        tracing::info!(run_id, "sequence cancel requested");
+        Ok(())
+    } else {
+        Err(AutoShutdownError::NotFound(format!("Sequence run {} is not running", run_id)))
+    }
+}
@@ -0,0 +1,263 @@
+use crate::cmd;
+use crate::error::AutoShutdownError;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceState {
+    Stopped,
+    StartPending,
+    StopPending,
+    Running,
+    Paused,
+    Unknown,
+}
+
+// sc query 输出中形如 "STATE : 4  RUNNING" 的一行
+pub fn query_state(name: &str) -> Result<ServiceState, String> {
+    let out = cmd::output("sc", &["query", name])?;
+    let state = out
+        .lines()
+        .find(|l| l.trim_start().starts_with("STATE"))
+        .and_then(|l| l.split_whitespace().nth(3))
+        .unwrap_or_default();
+    Ok(match state {
+        "STOPPED" => ServiceState::Stopped,
+        "START_PENDING" => ServiceState::StartPending,
+        "STOP_PENDING" => ServiceState::StopPending,
+        "RUNNING" => ServiceState::Running,
+        "PAUSED" => ServiceState::Paused,
+        _ => ServiceState::Unknown,
+    })
+}
+
+// 电源操作前需要正常停止的服务，如数据库或 Plex
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ServiceStop {
+    pub name: String,
+    #[serde(default = "default_stop_timeout")]
+    pub timeout_secs: u64,
+    // 未能在超时内停止时取消本次操作
+    #[serde(default)]
+    pub abort_on_failure: bool,
+}
+
+fn default_stop_timeout() -> u64 {
+    30
+}
+
+// 与窗口应用列表并列显示，很多阻止睡眠的是后台服务
+#[derive(Serialize, Clone, Debug)]
+pub struct ServiceInfo {
+    pub name: String,
+    pub display_name: String,
+    pub state: ServiceState,
+    // 未运行时为 0
+    pub pid: u32,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceControl {
+    Start,
+    Stop,
+    Restart,
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::{ServiceInfo, ServiceState};
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::{ERROR_MORE_DATA, ERROR_SERVICE_ALREADY_RUNNING, ERROR_SERVICE_NOT_ACTIVE};
+    use windows::Win32::System::Services::{
+        CloseServiceHandle, ControlService, EnumServicesStatusExW, OpenSCManagerW, OpenServiceW, QueryServiceStatus,
+        StartServiceW, ENUM_SERVICE_STATUS_PROCESSW, SC_ENUM_PROCESS_INFO, SC_HANDLE, SC_MANAGER_CONNECT,
+        SC_MANAGER_ENUMERATE_SERVICE, SERVICE_CONTROL_STOP, SERVICE_PAUSED, SERVICE_QUERY_STATUS, SERVICE_RUNNING,
+        SERVICE_START, SERVICE_START_PENDING, SERVICE_STATE_ALL, SERVICE_STATUS, SERVICE_STATUS_CURRENT_STATE,
+        SERVICE_STOP, SERVICE_STOPPED, SERVICE_STOP_PENDING, SERVICE_WIN32,
+    };
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    // 离开作用域时关闭句柄
+    struct Handle(SC_HANDLE);
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            let _ = unsafe { CloseServiceHandle(self.0) };
+        }
+    }
+
+    fn open_manager(access: u32) -> Result<Handle, String> {
+        unsafe { OpenSCManagerW(PCWSTR::null(), PCWSTR::null(), access) }
+            .map(Handle)
+            .map_err(|e| format!("Failed to open service manager: {}", e.message()))
+    }
+
+    fn open(name: &str, access: u32) -> Result<(Handle, Handle), String> {
+        unsafe {
+            let manager = open_manager(SC_MANAGER_CONNECT)?;
+            let service = Handle(
+                OpenServiceW(manager.0, &HSTRING::from(name), access)
+                    .map_err(|e| format!("Failed to open service {}: {}", name, e.message()))?,
+            );
+            Ok((manager, service))
+        }
+    }
+
+    pub fn stop(name: &str, timeout: Duration) -> Result<(), String> {
+        let (_manager, service) = open(name, SERVICE_STOP | SERVICE_QUERY_STATUS)?;
+        let mut status = SERVICE_STATUS::default();
+        unsafe {
+            if let Err(e) = ControlService(service.0, SERVICE_CONTROL_STOP, &mut status) {
+                // 未运行的服务视为已停止
+                if e.code() == ERROR_SERVICE_NOT_ACTIVE.to_hresult() {
+                    return Ok(());
+                }
+                return Err(format!("Failed to stop service {}: {}", name, e.message()));
+            }
+            let started = Instant::now();
+            while status.dwCurrentState != SERVICE_STOPPED {
+                if started.elapsed() >= timeout {
+                    return Err(format!("Service {} did not stop within {}s", name, timeout.as_secs()));
+                }
+                thread::sleep(POLL_INTERVAL);
+                QueryServiceStatus(service.0, &mut status)
+                    .map_err(|e| format!("Failed to query service {}: {}", name, e.message()))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn start(name: &str) -> Result<(), String> {
+        let (_manager, service) = open(name, SERVICE_START)?;
+        match unsafe { StartServiceW(service.0, None) } {
+            Err(e) if e.code() != ERROR_SERVICE_ALREADY_RUNNING.to_hresult() => {
+                Err(format!("Failed to start service {}: {}", name, e.message()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn to_state(state: SERVICE_STATUS_CURRENT_STATE) -> ServiceState {
+        match state {
+            SERVICE_STOPPED => ServiceState::Stopped,
+            SERVICE_START_PENDING => ServiceState::StartPending,
+            SERVICE_STOP_PENDING => ServiceState::StopPending,
+            SERVICE_RUNNING => ServiceState::Running,
+            SERVICE_PAUSED => ServiceState::Paused,
+            _ => ServiceState::Unknown,
+        }
+    }
+
+    pub fn list() -> Result<Vec<ServiceInfo>, String> {
+        let manager = open_manager(SC_MANAGER_ENUMERATE_SERVICE)?;
+        let mut services = Vec::new();
+        let mut resume = 0u32;
+        // 用 u64 分配保证结构体对齐
+        let mut buffer: Vec<u64> = vec![0; 8 * 1024];
+        loop {
+            let (mut needed, mut returned) = (0u32, 0u32);
+            let bytes = unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 8) };
+            let result = unsafe {
+                EnumServicesStatusExW(
+                    manager.0,
+                    SC_ENUM_PROCESS_INFO,
+                    SERVICE_WIN32,
+                    SERVICE_STATE_ALL,
+                    Some(bytes),
+                    &mut needed,
+                    &mut returned,
+                    Some(&mut resume),
+                    PCWSTR::null(),
+                )
+            };
+            let more = match result {
+                Ok(()) => false,
+                Err(e) if e.code() == ERROR_MORE_DATA.to_hresult() => true,
+                Err(e) => return Err(format!("Failed to enumerate services: {}", e.message())),
+            };
+            let entries = unsafe {
+                std::slice::from_raw_parts(buffer.as_ptr() as *const ENUM_SERVICE_STATUS_PROCESSW, returned as usize)
+            };
+            for entry in entries {
+                services.push(ServiceInfo {
+                    name: unsafe { entry.lpServiceName.to_string() }.unwrap_or_default(),
+                    display_name: unsafe { entry.lpDisplayName.to_string() }.unwrap_or_default(),
+                    state: to_state(entry.ServiceStatusProcess.dwCurrentState),
+                    pid: entry.ServiceStatusProcess.dwProcessId,
+                });
+            }
+            if !more {
+                break;
+            }
+            // 一项都放不下时按需要的大小扩大缓冲区
+            if returned == 0 {
+                buffer.resize((needed as usize).div_ceil(8), 0);
+            }
+        }
+        Ok(services)
+    }
+}
+
+#[cfg(not(windows))]
+mod imp {
+    use super::ServiceInfo;
+    use std::time::Duration;
+
+    pub fn stop(name: &str, _timeout: Duration) -> Result<(), String> {
+        Err(format!("Stopping service {} is only supported on Windows", name))
+    }
+
+    pub fn start(name: &str) -> Result<(), String> {
+        Err(format!("Starting service {} is only supported on Windows", name))
+    }
+
+    pub fn list() -> Result<Vec<ServiceInfo>, String> {
+        Ok(Vec::new())
+    }
+}
+
+// 依次停止，需要取消操作的失败返回 Err，其余返回未能停止的服务名
+pub fn stop_all(services: &[ServiceStop]) -> Result<Vec<String>, String> {
+    let mut failed = Vec::new();
+    for service in services {
+        match imp::stop(&service.name, Duration::from_secs(service.timeout_secs)) {
+            Ok(()) => tracing::info!(name = %service.name, "service stopped"),
+            Err(e) => {
+                tracing::warn!(name = %service.name, error = %e, "failed to stop service");
+                if service.abort_on_failure {
+                    return Err(e);
+                }
+                failed.push(service.name.clone());
+            }
+        }
+    }
+    Ok(failed)
+}
+
+#[tauri::command(async)]
+pub fn list_services() -> Result<Vec<ServiceInfo>, AutoShutdownError> {
+    let mut services = imp::list()?;
+    services.sort_by_key(|s| s.display_name.to_lowercase());
+    Ok(services)
+}
+
+// 返回操作后的状态，启动较慢的服务可能仍在启动中
+#[tauri::command(async)]
+pub fn control_service(name: String, action: ServiceControl) -> Result<ServiceState, AutoShutdownError> {
+    tracing::info!(name, ?action, "control service");
+    let timeout = Duration::from_secs(default_stop_timeout());
+    match action {
+        ServiceControl::Start => imp::start(&name)?,
+        ServiceControl::Stop => imp::stop(&name, timeout)?,
+        ServiceControl::Restart => {
+            imp::stop(&name, timeout)?;
+            imp::start(&name)?;
+        }
+    }
+    Ok(query_state(&name)?)
+}
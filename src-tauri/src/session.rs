@@ -0,0 +1,205 @@
+use crate::error::AutoShutdownError;
+use crate::storage::JsonStore;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+// 窗口的还原位置和显示状态（SW_* 值）
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Placement {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub show_cmd: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SessionApp {
+    pub name: String,
+    pub exe: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    pub title: String,
+    #[serde(default)]
+    pub placement: Option<Placement>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SessionSnapshot {
+    pub taken_at: DateTime<Local>,
+    pub apps: Vec<SessionApp>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RestoreResult {
+    pub exe: String,
+    pub error: Option<String>,
+}
+
+pub struct SessionStore(JsonStore<Option<SessionSnapshot>>);
+
+impl SessionStore {
+    pub fn load(app: &AppHandle) -> Self {
+        SessionStore(JsonStore::load(app, "session.json"))
+    }
+}
+
+#[cfg(windows)]
+fn placement_of(hwnd: isize) -> Option<Placement> {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{GetWindowPlacement, WINDOWPLACEMENT};
+
+    let mut wp = WINDOWPLACEMENT {
+        length: std::mem::size_of::<WINDOWPLACEMENT>() as u32,
+        ..Default::default()
+    };
+    unsafe { GetWindowPlacement(HWND(hwnd as _), &mut wp).ok()? };
+    let rect = wp.rcNormalPosition;
+    Some(Placement {
+        left: rect.left,
+        top: rect.top,
+        right: rect.right,
+        bottom: rect.bottom,
+        show_cmd: wp.showCmd,
+    })
+}
+
+#[cfg(windows)]
+fn running_apps() -> Vec<SessionApp> {
+    use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+    // 默认刷新不读取命令行和工作目录
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::new()
+            .with_exe(UpdateKind::OnlyIfNotSet)
+            .with_cmd(UpdateKind::OnlyIfNotSet)
+            .with_cwd(UpdateKind::OnlyIfNotSet),
+    );
+    let mut apps: Vec<SessionApp> = Vec::new();
+    for (pid, window) in crate::collect_windows() {
+        let Some(process) = sys.process(Pid::from_u32(pid)) else {
+            continue;
+        };
+        let name = process.name().to_string_lossy().to_string();
+        let Some(exe) = process.exe().map(|p| p.to_string_lossy().to_string()) else {
+            continue;
+        };
+        // 同一程序的多个进程只恢复一次
+        if !crate::is_user_app(&name) || apps.iter().any(|a| a.exe == exe) {
+            continue;
+        }
+        apps.push(SessionApp {
+            name,
+            exe,
+            args: process
+                .cmd()
+                .iter()
+                .skip(1)
+                .map(|a| a.to_string_lossy().to_string())
+                .collect(),
+            cwd: process.cwd().map(|p| p.to_string_lossy().to_string()),
+            title: window.title,
+            placement: placement_of(window.hwnd),
+        });
+    }
+    apps
+}
+
+#[cfg(not(windows))]
+fn running_apps() -> Vec<SessionApp> {
+    Vec::new()
+}
+
+// 重启前保存当前打开的应用，供重启后恢复
+pub fn snapshot(app: &AppHandle) {
+    let apps = running_apps();
+    tracing::info!(apps = apps.len(), "saving session snapshot");
+    let snapshot = SessionSnapshot {
+        taken_at: Local::now(),
+        apps,
+    };
+    if let Err(e) = app.state::<SessionStore>().0.update(|s| *s = Some(snapshot)) {
+        tracing::warn!(error = %e, "failed to save session snapshot");
+    }
+}
+
+// 等待新进程出现窗口后还原位置，程序可能通过其他进程打开窗口，超时后放弃
+#[cfg(windows)]
+fn apply_placement(pid: u32, placement: Placement) {
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use windows::Win32::Foundation::{HWND, RECT};
+    use windows::Win32::UI::WindowsAndMessaging::{SetWindowPlacement, WINDOWPLACEMENT};
+
+    thread::spawn(move || {
+        let deadline = Instant::now() + Duration::from_secs(15);
+        while Instant::now() < deadline {
+            if let Some(window) = crate::collect_windows().remove(&pid) {
+                let wp = WINDOWPLACEMENT {
+                    length: std::mem::size_of::<WINDOWPLACEMENT>() as u32,
+                    showCmd: placement.show_cmd,
+                    rcNormalPosition: RECT {
+                        left: placement.left,
+                        top: placement.top,
+                        right: placement.right,
+                        bottom: placement.bottom,
+                    },
+                    ..Default::default()
+                };
+                unsafe {
+                    let _ = SetWindowPlacement(HWND(window.hwnd as _), &wp);
+                }
+                return;
+            }
+            thread::sleep(Duration::from_millis(500));
+        }
+    });
+}
+
+#[cfg(not(windows))]
+fn apply_placement(_pid: u32, _placement: Placement) {}
+
+fn launch(app: &SessionApp) -> Result<(), String> {
+    let mut command = std::process::Command::new(&app.exe);
+    command.args(&app.args);
+    if let Some(cwd) = &app.cwd {
+        command.current_dir(cwd);
+    }
+    let child = command.spawn().map_err(|e| e.to_string())?;
+    if let Some(placement) = app.placement {
+        apply_placement(child.id(), placement);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_saved_session(store: State<'_, SessionStore>) -> Option<SessionSnapshot> {
+    store.0.get()
+}
+
+// 恢复后删除快照，避免下次启动重复打开
+#[tauri::command]
+pub fn restore_session(store: State<'_, SessionStore>) -> Result<Vec<RestoreResult>, AutoShutdownError> {
+    let snapshot = store.0.update(|s| s.take())?.ok_or("No saved session")?;
+    let results = snapshot
+        .apps
+        .iter()
+        .map(|app| {
+            let error = launch(app).err();
+            if let Some(e) = &error {
+                tracing::warn!(exe = %app.exe, error = %e, "failed to restore app");
+            }
+            RestoreResult {
+                exe: app.exe.clone(),
+                error,
+            }
+        })
+        .collect();
+    Ok(results)
+}
@@ -0,0 +1,260 @@
+use crate::api::{self, ApiSettings};
+use crate::discord::DiscordConfig;
+use crate::email::EmailConfig;
+use crate::error::AutoShutdownError;
+use crate::mqtt::MqttConfig;
+use crate::notify::NotificationRoutes;
+use crate::ntfy::NtfyConfig;
+use crate::qbittorrent::QbittorrentConfig;
+use crate::rules::{Rule, RuleStore};
+use crate::schedule::{Holiday, Schedule, ScheduleStore};
+use crate::storage::JsonStore;
+use crate::telegram::TelegramConfig;
+use crate::webhooks::Webhook;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::{AppHandle, Emitter, State};
+
+// 导出文件的格式版本，结构不兼容时递增
+const EXPORT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct Settings {
+    // 模拟模式：电源操作和结束进程只记录并发出事件，不真正执行
+    pub simulate: bool,
+    pub webhooks: Vec<Webhook>,
+    pub qbittorrent: Option<QbittorrentConfig>,
+    // 本地 HTTP 接口，默认关闭
+    pub api: ApiSettings,
+    pub mqtt: Option<MqttConfig>,
+    pub telegram: Option<TelegramConfig>,
+    pub discord: Option<DiscordConfig>,
+    pub email: Option<EmailConfig>,
+    pub ntfy: Option<NtfyConfig>,
+    // 每类通知的发送渠道
+    pub notifications: NotificationRoutes,
+    // 关闭应用时跳过的进程名，不区分大小写
+    pub excluded_apps: Vec<String>,
+    // 日程和规则可选择在这些日期不执行
+    pub holidays: Vec<Holiday>,
+}
+
+impl Settings {
+    // 导出时去掉令牌和密码，导入到另一台机器后需重新填写
+    pub fn without_secrets(mut self) -> Settings {
+        self.api.token.clear();
+        // 网络钩子地址中通常带有令牌
+        for webhook in &mut self.webhooks {
+            webhook.url.clear();
+        }
+        if let Some(c) = &mut self.qbittorrent {
+            c.password.clear();
+        }
+        if let Some(c) = &mut self.mqtt {
+            c.password.clear();
+        }
+        if let Some(c) = &mut self.telegram {
+            c.token.clear();
+        }
+        if let Some(c) = &mut self.discord {
+            c.webhook_url.clear();
+        }
+        if let Some(c) = &mut self.email {
+            c.password.clear();
+        }
+        if let Some(c) = &mut self.ntfy {
+            c.access_token.clear();
+        }
+        self
+    }
+
+    // 导入的配置中为空的密钥沿用本机已有的值
+    fn keep_secrets(&mut self, current: &Settings) {
+        fn keep(value: &mut String, current: Option<&String>) {
+            if value.is_empty() {
+                *value = current.cloned().unwrap_or_default();
+            }
+        }
+        keep(&mut self.api.token, Some(&current.api.token));
+        // 网络钩子按 id 对应本机已有的项，找不到时地址留空
+        for webhook in &mut self.webhooks {
+            let existing = current.webhooks.iter().find(|w| !webhook.id.is_empty() && w.id == webhook.id);
+            keep(&mut webhook.url, existing.map(|w| &w.url));
+        }
+        if let Some(c) = &mut self.qbittorrent {
+            keep(&mut c.password, current.qbittorrent.as_ref().map(|c| &c.password));
+        }
+        if let Some(c) = &mut self.mqtt {
+            keep(&mut c.password, current.mqtt.as_ref().map(|c| &c.password));
+        }
+        if let Some(c) = &mut self.telegram {
+            keep(&mut c.token, current.telegram.as_ref().map(|c| &c.token));
+        }
+        if let Some(c) = &mut self.discord {
+            keep(&mut c.webhook_url, current.discord.as_ref().map(|c| &c.webhook_url));
+        }
+        if let Some(c) = &mut self.email {
+            keep(&mut c.password, current.email.as_ref().map(|c| &c.password));
+        }
+        if let Some(c) = &mut self.ntfy {
+            keep(&mut c.access_token, current.ntfy.as_ref().map(|c| &c.access_token));
+        }
+    }
+}
+
+// 设置导出文件，包含规则和计划
+#[derive(Serialize, Deserialize)]
+struct SettingsBundle {
+    version: u32,
+    settings: Settings,
+    #[serde(default)]
+    rules: Vec<Rule>,
+    #[serde(default)]
+    schedules: Vec<Schedule>,
+}
+
+pub struct SettingsStore(JsonStore<Settings>);
+
+impl SettingsStore {
+    pub fn load(app: &AppHandle) -> Self {
+        SettingsStore(JsonStore::load(app, "settings.json"))
+    }
+
+    pub fn get(&self) -> Settings {
+        self.0.get()
+    }
+
+    pub fn reload_if_changed(&self) -> Result<bool, String> {
+        self.0.reload_if_changed()
+    }
+
+    // 只修改部分字段，不涉及需要重新应用的接口设置
+    pub fn update(&self, app: &AppHandle, f: impl FnOnce(&mut Settings)) -> Result<(), String> {
+        let settings = self.0.update(|s| {
+            f(s);
+            s.clone()
+        })?;
+        let _ = app.emit("settings-changed", &settings);
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub fn get_settings(store: State<'_, SettingsStore>) -> Settings {
+    store.get()
+}
+
+#[tauri::command]
+pub fn update_settings(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    settings: Settings,
+) -> Result<Settings, AutoShutdownError> {
+    apply(&app, &store, settings).map_err(Into::into)
+}
+
+fn apply(app: &AppHandle, store: &SettingsStore, mut settings: Settings) -> Result<Settings, String> {
+    if let Some(email) = &settings.email {
+        email.validate()?;
+    }
+    for webhook in settings.webhooks.iter_mut().filter(|w| w.id.is_empty()) {
+        webhook.id = uuid::Uuid::new_v4().to_string();
+    }
+    if settings.api.enabled && settings.api.token.is_empty() {
+        settings.api.token = uuid::Uuid::new_v4().simple().to_string();
+    }
+    api::apply(app, &settings.api)?;
+    store.0.update(|current| *current = settings.clone())?;
+    tracing::info!(?settings, "settings updated");
+    let _ = app.emit("settings-changed", &settings);
+    Ok(settings)
+}
+
+#[tauri::command]
+pub fn export_settings(
+    store: State<'_, SettingsStore>,
+    rules: State<'_, RuleStore>,
+    schedules: State<'_, ScheduleStore>,
+    path: String,
+) -> Result<(), AutoShutdownError> {
+    let bundle = SettingsBundle {
+        version: EXPORT_VERSION,
+        settings: store.get().without_secrets(),
+        rules: rules.all(),
+        schedules: schedules.all(),
+    };
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    tracing::info!(path, "settings exported");
+    Ok(())
+}
+
+// 整体替换设置、规则和计划，先全部校验再写入
+#[tauri::command]
+pub fn import_settings(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    rules: State<'_, RuleStore>,
+    schedules: State<'_, ScheduleStore>,
+    path: String,
+) -> Result<Settings, AutoShutdownError> {
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let mut bundle: SettingsBundle =
+        serde_json::from_str(&json).map_err(|e| AutoShutdownError::InvalidInput(format!("Invalid settings file: {}", e)))?;
+    if bundle.version > EXPORT_VERSION {
+        return Err(AutoShutdownError::InvalidInput(format!("Settings file version {} is newer than this app supports", bundle.version)));
+    }
+    for rule in &bundle.rules {
+        rule.validate()?;
+    }
+    for schedule in &bundle.schedules {
+        schedule.recurrence.validate()?;
+    }
+    bundle.settings.keep_secrets(&store.get());
+
+    let settings = apply(&app, &store, bundle.settings)?;
+    rules.replace_all(&app, bundle.rules)?;
+    schedules.replace_all(bundle.schedules)?;
+    #[cfg(windows)]
+    crate::task_scheduler::sync(&schedules.all())?;
+    tracing::info!(path, "settings imported");
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(webhooks: serde_json::Value) -> Settings {
+        serde_json::from_value(serde_json::json!({ "webhooks": webhooks })).unwrap()
+    }
+
+    fn urls(settings: &Settings) -> Vec<&str> {
+        settings.webhooks.iter().map(|w| w.url.as_str()).collect()
+    }
+
+    #[test]
+    fn keeps_webhook_urls_by_id() {
+        let current = settings(serde_json::json!([
+            { "id": "a", "url": "https://example.com/a?token=1" },
+            { "id": "b", "url": "https://example.com/b?token=2" },
+        ]));
+        let mut imported = current.clone().without_secrets();
+        imported.webhooks.reverse();
+        imported.keep_secrets(&current);
+        assert_eq!(urls(&imported), vec!["https://example.com/b?token=2", "https://example.com/a?token=1"]);
+    }
+
+    #[test]
+    fn leaves_unknown_webhook_urls_empty() {
+        let current = settings(serde_json::json!([{ "id": "a", "url": "https://example.com/a" }]));
+        let mut imported = settings(serde_json::json!([
+            { "id": "c", "url": "" },
+            { "url": "" },
+            { "id": "a", "url": "https://example.com/new" },
+        ]));
+        imported.keep_secrets(&current);
+        assert_eq!(urls(&imported), vec!["", "", "https://example.com/new"]);
+    }
+}
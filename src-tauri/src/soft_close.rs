@@ -0,0 +1,106 @@
+use crate::settings::SettingsStore;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SoftCloseOptions {
+    // 等待应用弹出保存对话框并自行退出的时长
+    #[serde(default = "default_grace")]
+    pub grace_secs: u64,
+    // 宽限期后仍有应用未关闭时取消本次操作
+    #[serde(default)]
+    pub abort_if_blocked: bool,
+}
+
+fn default_grace() -> u64 {
+    30
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct BlockingApp {
+    pub pid: u32,
+    pub name: String,
+    pub title: String,
+}
+
+#[derive(Serialize, Clone)]
+struct SoftCloseFinished<'a> {
+    closed: usize,
+    blocking: &'a [BlockingApp],
+}
+
+// 向除排除列表外所有用户应用的窗口发送 WM_CLOSE，返回宽限期结束后仍未关闭的应用
+#[cfg(windows)]
+fn close_all(grace_secs: u64, excluded: &[String]) -> (usize, Vec<BlockingApp>) {
+    use std::collections::HashMap;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use sysinfo::{Pid, ProcessesToUpdate, System};
+    use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{PostMessageW, WM_CLOSE};
+
+    let own_pid = std::process::id();
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::All, false);
+    let targets: Vec<_> = crate::enumerate_windows()
+        .into_iter()
+        .filter(|w| w.pid != own_pid)
+        .filter_map(|w| {
+            let name = sys.process(Pid::from_u32(w.pid))?.name().to_string_lossy().to_string();
+            let skip = excluded.iter().any(|e| e.eq_ignore_ascii_case(&name));
+            (crate::is_user_app(&name) && !skip).then_some((w, name))
+        })
+        .collect();
+    for (window, _) in &targets {
+        tracing::info!(pid = window.pid, title = %window.title, "sending WM_CLOSE");
+        unsafe {
+            let _ = PostMessageW(HWND(window.hwnd as _), WM_CLOSE, WPARAM(0), LPARAM(0));
+        }
+    }
+    let names: HashMap<u32, String> = targets.into_iter().map(|(w, name)| (w.pid, name)).collect();
+
+    // 保存对话框本身也是可见窗口，只要进程还有可见窗口就视为未关闭
+    let deadline = Instant::now() + Duration::from_secs(grace_secs);
+    loop {
+        let remaining: Vec<_> = crate::collect_windows()
+            .into_values()
+            .filter(|w| names.contains_key(&w.pid))
+            .collect();
+        if remaining.is_empty() || Instant::now() >= deadline {
+            let blocking: Vec<BlockingApp> = remaining
+                .into_iter()
+                .map(|w| BlockingApp {
+                    pid: w.pid,
+                    name: names.get(&w.pid).cloned().unwrap_or_default(),
+                    title: w.title,
+                })
+                .collect();
+            return (names.len() - blocking.len(), blocking);
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+}
+
+#[cfg(not(windows))]
+fn close_all(_grace_secs: u64, _excluded: &[String]) -> (usize, Vec<BlockingApp>) {
+    (0, Vec::new())
+}
+
+// 返回 Err 表示有应用阻止关闭且选项要求取消
+pub fn run(app: &AppHandle, options: &SoftCloseOptions) -> Result<(), String> {
+    let excluded = app.state::<SettingsStore>().get().excluded_apps;
+    let (closed, blocking) = close_all(options.grace_secs, &excluded);
+    tracing::info!(closed, blocking = blocking.len(), "soft close finished");
+    let _ = app.emit(
+        "soft-close-finished",
+        SoftCloseFinished {
+            closed,
+            blocking: &blocking,
+        },
+    );
+    if blocking.is_empty() || !options.abort_if_blocked {
+        return Ok(());
+    }
+    let names: Vec<&str> = blocking.iter().map(|b| b.name.as_str()).collect();
+    Err(format!("Applications still open: {}", names.join(", ")))
+}
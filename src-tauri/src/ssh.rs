@@ -0,0 +1,111 @@
+use crate::cmd;
+use crate::power::PowerAction;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::process::Stdio;
+
+// 由 askpass 脚本读取，密码不出现在命令行或磁盘上
+const PASSWORD_VAR: &str = "AUTOSHUTDOWN_SSH_PASSWORD";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SshConfig {
+    #[serde(default = "default_port")]
+    pub port: u16,
+    pub username: String,
+    // 私钥路径，为空时使用 ssh 的默认密钥
+    #[serde(default)]
+    pub key_path: Option<String>,
+    // 不为空时用密码登录
+    #[serde(default)]
+    pub password: String,
+    // 非 root 用户需要免密 sudo
+    #[serde(default)]
+    pub use_sudo: bool,
+}
+
+fn default_port() -> u16 {
+    22
+}
+
+// 不输出密码
+impl fmt::Debug for SshConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SshConfig")
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .field("key_path", &self.key_path)
+            .field("use_sudo", &self.use_sudo)
+            .finish_non_exhaustive()
+    }
+}
+
+fn systemctl_verb(action: PowerAction) -> Result<&'static str, String> {
+    match action {
+        PowerAction::Shutdown | PowerAction::FullShutdown => Ok("poweroff"),
+        PowerAction::Restart => Ok("reboot"),
+        PowerAction::Sleep => Ok("suspend"),
+        PowerAction::Hibernate => Ok("hibernate"),
+        other => Err(format!("{:?} is not supported over SSH", other)),
+    }
+}
+
+// ssh 只能从程序读取密码，写一个从环境变量输出密码的脚本供 SSH_ASKPASS 使用
+fn askpass_script() -> Result<PathBuf, String> {
+    #[cfg(windows)]
+    let (file, content) = (
+        "autoshutdown-askpass.cmd",
+        format!("@powershell -NoProfile -Command \"[Console]::Out.Write($env:{})\"\r\n", PASSWORD_VAR),
+    );
+    #[cfg(not(windows))]
+    let (file, content) = (
+        "autoshutdown-askpass.sh",
+        format!("#!/bin/sh\nprintf '%s' \"${}\"\n", PASSWORD_VAR),
+    );
+    let path = std::env::temp_dir().join(file);
+    std::fs::write(&path, content).map_err(|e| e.to_string())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+    }
+    Ok(path)
+}
+
+// 使用系统自带的 OpenSSH 客户端执行一条远程命令
+pub fn run(host: &str, config: &SshConfig, command: &str) -> Result<String, String> {
+    let mut ssh = cmd::hidden("ssh");
+    ssh.args(["-p", &config.port.to_string()])
+        .args(["-o", "ConnectTimeout=10", "-o", "StrictHostKeyChecking=accept-new"]);
+    if let Some(key) = &config.key_path {
+        ssh.args(["-i", key]);
+    }
+    if config.password.is_empty() {
+        ssh.args(["-o", "BatchMode=yes"]);
+    } else {
+        ssh.env("SSH_ASKPASS", askpass_script()?)
+            .env("SSH_ASKPASS_REQUIRE", "force")
+            .env(PASSWORD_VAR, &config.password)
+            .args(["-o", "NumberOfPasswordPrompts=1"]);
+    }
+    ssh.arg(format!("{}@{}", config.username, host)).arg(command).stdin(Stdio::null());
+
+    let output = ssh.output().map_err(|e| format!("Failed to run ssh: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+// 命令放到后台稍后执行，让 ssh 在连接被关机断开之前正常返回
+pub fn power(host: &str, config: &SshConfig, action: PowerAction) -> Result<(), String> {
+    let sudo = if config.use_sudo { "sudo -n " } else { "" };
+    let command = format!(
+        "nohup sh -c 'sleep 1; {}systemctl {}' >/dev/null 2>&1 &",
+        sudo,
+        systemctl_verb(action)?
+    );
+    tracing::info!(host, ?action, "ssh power action");
+    run(host, config, &command).map(|_| ())
+}
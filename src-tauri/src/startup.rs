@@ -0,0 +1,213 @@
+use crate::error::AutoShutdownError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupSource {
+    RunKeyUser,
+    RunKeyMachine,
+    StartupFolderUser,
+    StartupFolderCommon,
+    // 登录时触发的计划任务
+    LogonTask,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct StartupEntry {
+    // 注册表值名、快捷方式文件名或任务路径，启用/禁用时原样传回
+    pub id: String,
+    pub name: String,
+    pub command: String,
+    pub source: StartupSource,
+    pub enabled: bool,
+}
+
+// 与任务管理器一致，通过 StartupApproved 记录启用状态，不删除原有启动项
+#[cfg(windows)]
+mod imp {
+    use super::{StartupEntry, StartupSource};
+    use crate::cmd;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::PathBuf;
+
+    const RUN_USER: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+    const RUN_MACHINE: &str = r"HKLM\Software\Microsoft\Windows\CurrentVersion\Run";
+    const APPROVED_USER: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Explorer\StartupApproved";
+    const APPROVED_MACHINE: &str = r"HKLM\Software\Microsoft\Windows\CurrentVersion\Explorer\StartupApproved";
+    // 首字节为偶数表示启用，其余字节是禁用时间，写入时置零
+    const ENABLED_VALUE: &str = "020000000000000000000000";
+    const DISABLED_VALUE: &str = "030000000000000000000000";
+
+    fn approved_key(source: StartupSource) -> Option<String> {
+        match source {
+            StartupSource::RunKeyUser => Some(format!(r"{}\Run", APPROVED_USER)),
+            StartupSource::RunKeyMachine => Some(format!(r"{}\Run", APPROVED_MACHINE)),
+            StartupSource::StartupFolderUser => Some(format!(r"{}\StartupFolder", APPROVED_USER)),
+            StartupSource::StartupFolderCommon => Some(format!(r"{}\StartupFolder", APPROVED_MACHINE)),
+            StartupSource::LogonTask => None,
+        }
+    }
+
+    fn startup_folder(source: StartupSource) -> Option<PathBuf> {
+        let base = match source {
+            StartupSource::StartupFolderUser => std::env::var_os("APPDATA")?,
+            StartupSource::StartupFolderCommon => std::env::var_os("ProgramData")?,
+            _ => return None,
+        };
+        Some(PathBuf::from(base).join(r"Microsoft\Windows\Start Menu\Programs\Startup"))
+    }
+
+    // reg query 输出中形如 "    名称    REG_SZ    数据" 的行，键不存在时返回空
+    fn reg_values(key: &str) -> Vec<(String, String)> {
+        let Ok(out) = cmd::output("reg", &["query", key]) else {
+            return Vec::new();
+        };
+        out.lines()
+            .filter(|l| l.starts_with("    "))
+            .filter_map(|l| {
+                let (name, rest) = l.trim_start().split_once("    REG_")?;
+                let data = rest.split_once("    ").map(|(_, d)| d).unwrap_or_default();
+                Some((name.trim().to_string(), data.trim().to_string()))
+            })
+            .collect()
+    }
+
+    // 没有记录的项视为启用
+    fn disabled_names(source: StartupSource) -> HashMap<String, bool> {
+        let Some(key) = approved_key(source) else {
+            return HashMap::new();
+        };
+        reg_values(&key)
+            .into_iter()
+            .map(|(name, data)| {
+                let first = u8::from_str_radix(data.get(..2).unwrap_or("00"), 16).unwrap_or(0);
+                (name, first % 2 == 1)
+            })
+            .collect()
+    }
+
+    fn run_key_entries(source: StartupSource, key: &str) -> Vec<StartupEntry> {
+        let disabled = disabled_names(source);
+        reg_values(key)
+            .into_iter()
+            .map(|(name, command)| StartupEntry {
+                enabled: !disabled.get(&name).copied().unwrap_or(false),
+                id: name.clone(),
+                name,
+                command,
+                source,
+            })
+            .collect()
+    }
+
+    fn folder_entries(source: StartupSource) -> Vec<StartupEntry> {
+        let Some(dir) = startup_folder(source) else {
+            return Vec::new();
+        };
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Vec::new();
+        };
+        let disabled = disabled_names(source);
+        entries
+            .flatten()
+            .filter(|e| e.file_name() != "desktop.ini")
+            .map(|e| {
+                let file = e.file_name().to_string_lossy().to_string();
+                StartupEntry {
+                    enabled: !disabled.get(&file).copied().unwrap_or(false),
+                    name: file.rsplit_once('.').map_or(file.as_str(), |(stem, _)| stem).to_string(),
+                    command: e.path().to_string_lossy().to_string(),
+                    id: file,
+                    source,
+                }
+            })
+            .collect()
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct LogonTask {
+        path: String,
+        execute: Option<String>,
+        enabled: bool,
+    }
+
+    // schtasks 的触发器描述随系统语言变化，改用 PowerShell 按触发器类型筛选
+    const LOGON_TASKS_SCRIPT: &str = "@(Get-ScheduledTask | Where-Object { $_.Triggers | Where-Object { $_.CimClass.CimClassName -eq 'MSFT_TaskLogonTrigger' } } | ForEach-Object { [pscustomobject]@{ Path = $_.TaskPath + $_.TaskName; Execute = ($_.Actions | Select-Object -First 1).Execute; Enabled = $_.State -ne 'Disabled' } }) | ConvertTo-Json -Compress";
+
+    fn logon_tasks() -> Result<Vec<StartupEntry>, String> {
+        let out = cmd::output("powershell", &["-NoProfile", "-NonInteractive", "-Command", LOGON_TASKS_SCRIPT])?;
+        let out = out.trim();
+        if out.is_empty() {
+            return Ok(Vec::new());
+        }
+        // 只有一项时 ConvertTo-Json 输出对象而不是数组
+        let tasks: Vec<LogonTask> = if out.starts_with('[') {
+            serde_json::from_str(out).map_err(|e| e.to_string())?
+        } else {
+            vec![serde_json::from_str(out).map_err(|e| e.to_string())?]
+        };
+        Ok(tasks
+            .into_iter()
+            .map(|t| StartupEntry {
+                name: t.path.rsplit('\\').next().unwrap_or(&t.path).to_string(),
+                command: t.execute.unwrap_or_default(),
+                id: t.path,
+                source: StartupSource::LogonTask,
+                enabled: t.enabled,
+            })
+            .collect())
+    }
+
+    pub fn list() -> Result<Vec<StartupEntry>, String> {
+        let mut entries = run_key_entries(StartupSource::RunKeyUser, RUN_USER);
+        entries.extend(run_key_entries(StartupSource::RunKeyMachine, RUN_MACHINE));
+        entries.extend(folder_entries(StartupSource::StartupFolderUser));
+        entries.extend(folder_entries(StartupSource::StartupFolderCommon));
+        match logon_tasks() {
+            Ok(tasks) => entries.extend(tasks),
+            Err(e) => tracing::warn!(error = %e, "failed to list logon tasks"),
+        }
+        Ok(entries)
+    }
+
+    // 本机范围的启动项和计划任务需要管理员权限
+    pub fn set_enabled(source: StartupSource, id: &str, enabled: bool) -> Result<(), String> {
+        match approved_key(source) {
+            Some(key) => {
+                let value = if enabled { ENABLED_VALUE } else { DISABLED_VALUE };
+                cmd::output("reg", &["add", &key, "/v", id, "/t", "REG_BINARY", "/d", value, "/f"]).map(|_| ())
+            }
+            None => {
+                let flag = if enabled { "/ENABLE" } else { "/DISABLE" };
+                cmd::output("schtasks", &["/Change", "/TN", id, flag]).map(|_| ())
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod imp {
+    use super::{StartupEntry, StartupSource};
+
+    pub fn list() -> Result<Vec<StartupEntry>, String> {
+        Ok(Vec::new())
+    }
+
+    pub fn set_enabled(_source: StartupSource, _id: &str, _enabled: bool) -> Result<(), String> {
+        Err("Managing startup apps is only supported on Windows".into())
+    }
+}
+
+#[tauri::command(async)]
+pub fn list_startup_entries() -> Result<Vec<StartupEntry>, AutoShutdownError> {
+    Ok(imp::list()?)
+}
+
+#[tauri::command(async)]
+pub fn set_startup_entry_enabled(source: StartupSource, id: String, enabled: bool) -> Result<(), AutoShutdownError> {
+    tracing::info!(?source, id, enabled, "set startup entry");
+    Ok(imp::set_enabled(source, &id, enabled)?)
+}
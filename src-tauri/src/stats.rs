@@ -0,0 +1,217 @@
+use crate::audit::{ActionHistory, ActionSource, AuditAction};
+use crate::storage::JsonStore;
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::thread;
+use std::time::Duration;
+use sysinfo::System;
+use tauri::{AppHandle, Manager, State};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+// 超过该天数的统计数据会被清理
+const RETENTION_DAYS: i64 = 90;
+
+// 一次开机会话，last_seen 为最后一次采样时间
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Session {
+    boot: DateTime<Local>,
+    last_seen: DateTime<Local>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct UsageData {
+    sessions: Vec<Session>,
+    // 日期 -> 进程名 -> 有可见窗口的分钟数
+    app_minutes: BTreeMap<NaiveDate, HashMap<String, u32>>,
+}
+
+pub struct UsageStore(JsonStore<UsageData>);
+
+impl UsageStore {
+    pub fn load(app: &AppHandle) -> Self {
+        UsageStore(JsonStore::load(app, "usage.json"))
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsRange {
+    Day,
+    Week,
+    Month,
+}
+
+impl StatsRange {
+    fn days(self) -> i64 {
+        match self {
+            StatsRange::Day => 1,
+            StatsRange::Week => 7,
+            StatsRange::Month => 30,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct DailyStats {
+    date: NaiveDate,
+    uptime_minutes: i64,
+    boots: usize,
+    app_actions: usize,
+}
+
+#[derive(Serialize)]
+pub struct AppUsage {
+    name: String,
+    minutes: u32,
+}
+
+#[derive(Serialize)]
+pub struct Statistics {
+    days: Vec<DailyStats>,
+    boot_times: Vec<DateTime<Local>>,
+    top_apps: Vec<AppUsage>,
+    // 由计时器或规则发起的关机/重启/睡眠次数
+    app_actions: usize,
+}
+
+fn day_bounds(date: NaiveDate) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    let start = Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .earliest()?;
+    Some((start, start + ChronoDuration::days(1)))
+}
+
+fn sample(app: &AppHandle) {
+    let boot = Local
+        .timestamp_opt(System::boot_time() as i64, 0)
+        .single()
+        .unwrap_or_else(Local::now);
+    let now = Local::now();
+    let visible_apps = visible_app_names();
+
+    let _ = app.state::<UsageStore>().0.update(|data| {
+        // 开机时间可能有几秒抖动，一分钟内视为同一次开机
+        match data
+            .sessions
+            .iter_mut()
+            .find(|s| (s.boot - boot).num_seconds().abs() < 60)
+        {
+            Some(session) => session.last_seen = now,
+            None => data.sessions.push(Session { boot, last_seen: now }),
+        }
+
+        let today = data.app_minutes.entry(now.date_naive()).or_default();
+        for name in visible_apps {
+            *today.entry(name).or_default() += 1;
+        }
+
+        let cutoff = now - ChronoDuration::days(RETENTION_DAYS);
+        data.sessions.retain(|s| s.last_seen >= cutoff);
+        data.app_minutes.retain(|date, _| *date >= cutoff.date_naive());
+    });
+}
+
+#[cfg(windows)]
+fn visible_app_names() -> Vec<String> {
+    use sysinfo::{Pid, ProcessesToUpdate};
+
+    let windows = crate::collect_windows();
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::All, false);
+    let mut names: Vec<String> = windows
+        .keys()
+        .filter_map(|pid| sys.process(Pid::from_u32(*pid)))
+        .map(|p| p.name().to_string_lossy().to_string())
+        .filter(|name| crate::is_user_app(name))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(not(windows))]
+fn visible_app_names() -> Vec<String> {
+    Vec::new()
+}
+
+// 已用物理内存占总量的百分比
+pub fn memory_used_percent() -> f64 {
+    let mut sys = System::new();
+    sys.refresh_memory();
+    if sys.total_memory() == 0 {
+        return 0.0;
+    }
+    sys.used_memory() as f64 * 100.0 / sys.total_memory() as f64
+}
+
+pub fn start_sampler(app: AppHandle) {
+    thread::spawn(move || loop {
+        sample(&app);
+        thread::sleep(SAMPLE_INTERVAL);
+    });
+}
+
+#[tauri::command]
+pub fn get_statistics(
+    usage: State<'_, UsageStore>,
+    history: State<'_, ActionHistory>,
+    range: StatsRange,
+) -> Statistics {
+    let data = usage.0.get();
+    let today = Local::now().date_naive();
+    let first_day = today - ChronoDuration::days(range.days() - 1);
+
+    let app_actions: Vec<_> = history
+        .0
+        .recent(usize::MAX)
+        .into_iter()
+        .filter(|r| r.success && !r.simulated && r.timestamp.date_naive() >= first_day)
+        .filter(|r| matches!(r.action, AuditAction::Power { .. }))
+        .filter(|r| !matches!(r.source, ActionSource::User))
+        .collect();
+
+    let days = first_day
+        .iter_days()
+        .take_while(|d| *d <= today)
+        .filter_map(|date| {
+            let (start, end) = day_bounds(date)?;
+            let uptime_minutes = data
+                .sessions
+                .iter()
+                .map(|s| (s.last_seen.min(end) - s.boot.max(start)).num_minutes().max(0))
+                .sum();
+            Some(DailyStats {
+                date,
+                uptime_minutes,
+                boots: data.sessions.iter().filter(|s| s.boot.date_naive() == date).count(),
+                app_actions: app_actions.iter().filter(|r| r.timestamp.date_naive() == date).count(),
+            })
+        })
+        .collect();
+
+    let mut totals: HashMap<String, u32> = HashMap::new();
+    for (_, apps) in data.app_minutes.range(first_day..) {
+        for (name, minutes) in apps {
+            *totals.entry(name.clone()).or_default() += minutes;
+        }
+    }
+    let mut top_apps: Vec<AppUsage> = totals
+        .into_iter()
+        .map(|(name, minutes)| AppUsage { name, minutes })
+        .collect();
+    top_apps.sort_by(|a, b| b.minutes.cmp(&a.minutes).then(a.name.cmp(&b.name)));
+    top_apps.truncate(10);
+
+    Statistics {
+        days,
+        boot_times: data
+            .sessions
+            .iter()
+            .map(|s| s.boot)
+            .filter(|b| b.date_naive() >= first_day)
+            .collect(),
+        top_apps,
+        app_actions: app_actions.len(),
+    }
+}
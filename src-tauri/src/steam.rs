@@ -0,0 +1,52 @@
+use crate::cmd;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_STEAM_DIR: &str = r"C:\Program Files (x86)\Steam";
+
+// Steam 安装目录记录在注册表中，读取失败时使用默认位置
+fn steam_root() -> Option<PathBuf> {
+    let from_registry = cmd::output("reg", &["query", r"HKCU\Software\Valve\Steam", "/v", "SteamPath"])
+        .ok()
+        .and_then(|out| {
+            out.lines()
+                .find(|l| l.contains("SteamPath"))
+                .and_then(|l| l.split("REG_SZ").nth(1))
+                .map(|p| PathBuf::from(p.trim()))
+        });
+    from_registry
+        .into_iter()
+        .chain(std::iter::once(PathBuf::from(DEFAULT_STEAM_DIR)))
+        .find(|p| p.is_dir())
+}
+
+// 主库之外的游戏库在 libraryfolders.vdf 中以 "path" "D:\\SteamLibrary" 的形式列出
+fn library_folders(root: &Path) -> Vec<PathBuf> {
+    let mut folders = vec![root.join("steamapps")];
+    if let Ok(vdf) = fs::read_to_string(root.join("steamapps").join("libraryfolders.vdf")) {
+        for line in vdf.lines().filter(|l| l.trim_start().starts_with("\"path\"")) {
+            if let Some(path) = line.split('"').nth(3) {
+                let path = PathBuf::from(path.replace("\\\\", "\\")).join("steamapps");
+                if !folders.contains(&path) {
+                    folders.push(path);
+                }
+            }
+        }
+    }
+    folders
+}
+
+// 正在下载或更新的游戏在 steamapps/downloading 下有以 AppID 命名的目录，完成后删除
+pub fn active_downloads() -> Option<Vec<u32>> {
+    let root = steam_root()?;
+    let mut apps: Vec<u32> = library_folders(&root)
+        .iter()
+        .filter_map(|lib| fs::read_dir(lib.join("downloading")).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|e| e.file_name().to_string_lossy().parse().ok())
+        .collect();
+    apps.sort_unstable();
+    apps.dedup();
+    Some(apps)
+}
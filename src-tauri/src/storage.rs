@@ -0,0 +1,104 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, Manager};
+
+// 应用数据目录下的文件路径，目录不存在时自动创建
+pub fn data_path(app: &AppHandle, file: &str) -> PathBuf {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join("AutoShutdown"));
+    let _ = fs::create_dir_all(&dir);
+    dir.join(file)
+}
+
+// 只有文件不存在时静默使用默认值；读取或解析失败时记录日志，并把原文件改名保留后再使用默认值，
+// 避免下一次保存覆盖用户的配置
+pub fn load<T: DeserializeOwned + Default>(path: &Path) -> T {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return T::default(),
+        Err(e) => {
+            tracing::error!(path = %path.display(), error = %e, "failed to read data file");
+            set_aside(path);
+            return T::default();
+        }
+    };
+    serde_json::from_str(&text).unwrap_or_else(|e| {
+        tracing::error!(path = %path.display(), error = %e, "failed to parse data file");
+        set_aside(path);
+        T::default()
+    })
+}
+
+fn set_aside(path: &Path) {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".corrupt-{}", chrono::Local::now().format("%Y%m%d%H%M%S")));
+    match fs::rename(path, &name) {
+        Ok(()) => tracing::warn!(path = %Path::new(&name).display(), "kept unreadable data file"),
+        Err(e) => tracing::error!(path = %path.display(), error = %e, "failed to rename unreadable data file"),
+    }
+}
+
+pub fn save<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    // 先写临时文件再替换，避免写到一半时崩溃损坏配置
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, json).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+// 整体读写的 JSON 配置文件，修改后立即落盘
+pub struct JsonStore<T> {
+    path: PathBuf,
+    value: Mutex<T>,
+    // 最近一次由本程序读写后的修改时间，用于识别外部修改
+    modified: Mutex<Option<SystemTime>>,
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl<T: Serialize + DeserializeOwned + Default + Clone> JsonStore<T> {
+    pub fn load(app: &AppHandle, file: &str) -> Self {
+        let path = data_path(app, file);
+        let value = load(&path);
+        JsonStore {
+            modified: Mutex::new(modified_time(&path)),
+            path,
+            value: Mutex::new(value),
+        }
+    }
+
+    pub fn get(&self) -> T {
+        self.value.lock().unwrap().clone()
+    }
+
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, String> {
+        let mut value = self.value.lock().unwrap();
+        let result = f(&mut value);
+        save(&self.path, &*value)?;
+        *self.modified.lock().unwrap() = modified_time(&self.path);
+        Ok(result)
+    }
+
+    // 文件被外部修改时重新读取，返回是否有变化
+    // 内容无法解析时保留当前值，等下一次修改再试
+    #[cfg(desktop)]
+    pub fn reload_if_changed(&self) -> Result<bool, String> {
+        let mut value = self.value.lock().unwrap();
+        let mut modified = self.modified.lock().unwrap();
+        let current = modified_time(&self.path);
+        if current.is_none() || current == *modified {
+            return Ok(false);
+        }
+        *modified = current;
+        let json = fs::read_to_string(&self.path).map_err(|e| e.to_string())?;
+        *value = serde_json::from_str(&json).map_err(|e| format!("{}: {}", self.path.display(), e))?;
+        Ok(true)
+    }
+}
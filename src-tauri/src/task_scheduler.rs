@@ -0,0 +1,290 @@
+use crate::error::AutoShutdownError;
+use crate::power::PowerAction;
+use crate::schedule::ScheduleStore;
+use serde::Serialize;
+use tauri::State;
+
+#[derive(Serialize, Clone)]
+pub struct MirroredTask {
+    id: String,
+    task_name: String,
+    next_run: String,
+    status: String,
+}
+
+// 任务计划程序中会执行电源操作的任务，包括其他工具或组策略创建的
+#[derive(Serialize, Clone)]
+pub struct PowerTask {
+    path: String,
+    command: String,
+    action: PowerAction,
+    enabled: bool,
+    // 由本应用镜像日程创建
+    own: bool,
+}
+
+// 根据任务执行的程序和参数判断电源操作，取消关机（shutdown /a）等不算
+#[cfg(windows)]
+fn classify(execute: &str, arguments: &str) -> Option<PowerAction> {
+    let program = execute.trim_matches('"').rsplit(['\\', '/']).next()?.to_lowercase();
+    let program = program.strip_suffix(".exe").unwrap_or(&program);
+    let args = arguments.to_lowercase();
+    let has_flag = |flag: &str| {
+        args.split_whitespace()
+            .any(|a| a.len() == 2 && a[1..] == *flag && (a.starts_with('/') || a.starts_with('-')))
+    };
+    match program {
+        "shutdown" if has_flag("a") => None,
+        "shutdown" if has_flag("r") || has_flag("g") => Some(PowerAction::Restart),
+        "shutdown" if has_flag("h") => Some(PowerAction::Hibernate),
+        "shutdown" if has_flag("l") => Some(PowerAction::LogOff),
+        "shutdown" if has_flag("s") && args.split_whitespace().any(|a| a == "/full" || a == "-full") => {
+            Some(PowerAction::FullShutdown)
+        }
+        "shutdown" if has_flag("s") || has_flag("p") => Some(PowerAction::Shutdown),
+        "psshutdown" | "psshutdown64" if has_flag("r") => Some(PowerAction::Restart),
+        "psshutdown" | "psshutdown64" if has_flag("d") => Some(PowerAction::Sleep),
+        "psshutdown" | "psshutdown64" if has_flag("h") => Some(PowerAction::Hibernate),
+        "psshutdown" | "psshutdown64" if has_flag("a") => None,
+        "psshutdown" | "psshutdown64" => Some(PowerAction::Shutdown),
+        "rundll32" if args.contains("setsuspendstate") => Some(PowerAction::Sleep),
+        "rundll32" if args.contains("lockworkstation") => Some(PowerAction::Lock),
+        "powershell" | "pwsh" if args.contains("restart-computer") => Some(PowerAction::Restart),
+        "powershell" | "pwsh" if args.contains("stop-computer") => Some(PowerAction::Shutdown),
+        "powershell" | "pwsh" if args.contains("setsuspendstate") => Some(PowerAction::Sleep),
+        _ => None,
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::{classify, MirroredTask, PowerTask};
+    use crate::schedule::Schedule;
+    use serde::Deserialize;
+
+    const TASK_FOLDER: &str = "\\AutoShutdown\\";
+    const DAY_NAMES: [&str; 7] = ["MON", "TUE", "WED", "THU", "FRI", "SAT", "SUN"];
+    // schtasks /TR 允许的最长命令行
+    const MAX_TASK_RUN: usize = 261;
+
+    fn schtasks(args: &[&str]) -> Result<String, String> {
+        crate::cmd::output("schtasks", args)
+    }
+
+    // 按 CommandLineToArgvW 的规则给单个参数加引号，任务运行时拆分出的参数与原参数一致
+    fn quote_arg(arg: &str) -> String {
+        if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+            return arg.to_string();
+        }
+        let mut quoted = String::from('"');
+        let mut backslashes = 0;
+        for c in arg.chars() {
+            match c {
+                '\\' => backslashes += 1,
+                '"' => {
+                    // 引号前的反斜杠加倍，引号本身转义
+                    quoted.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+                    quoted.push('"');
+                    backslashes = 0;
+                }
+                _ => {
+                    quoted.extend(std::iter::repeat_n('\\', backslashes));
+                    quoted.push(c);
+                    backslashes = 0;
+                }
+            }
+        }
+        // 结尾的反斜杠在闭合引号前同样加倍
+        quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+        quoted.push('"');
+        quoted
+    }
+
+    fn command_line(program: &str, args: &[&str]) -> Result<String, String> {
+        let line = std::iter::once(quote_arg(program))
+            .chain(args.iter().map(|a| quote_arg(a)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if line.len() > MAX_TASK_RUN {
+            return Err(format!("Command line is too long for Task Scheduler ({} characters)", line.len()));
+        }
+        Ok(line)
+    }
+
+    fn task_name(id: &str) -> String {
+        format!("{}{}", TASK_FOLDER, id)
+    }
+
+    fn create(schedule: &Schedule) -> Result<(), String> {
+        let (program, args) = schedule
+            .action
+            .power()
+            .ok_or("Only power actions can run from Task Scheduler")?
+            .command();
+        let command_line = command_line(program, args)?;
+        let name = task_name(&schedule.id);
+        let recurrence = &schedule.recurrence;
+        let start_time = format!("{:02}:{:02}", recurrence.hour, recurrence.minute);
+        let days = recurrence
+            .effective_days()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|d| DAY_NAMES.get(*d as usize).copied())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let mut args = vec!["/Create", "/F", "/TN", &name, "/TR", &command_line, "/ST", &start_time];
+        if days.is_empty() {
+            args.extend(["/SC", "DAILY"]);
+        } else {
+            args.extend(["/SC", "WEEKLY", "/D", &days]);
+        }
+        schtasks(&args).map(|_| ())
+    }
+
+    fn delete(task_name: &str) -> Result<(), String> {
+        schtasks(&["/Delete", "/F", "/TN", task_name]).map(|_| ())
+    }
+
+    pub fn list() -> Result<Vec<MirroredTask>, String> {
+        let output = schtasks(&["/Query", "/FO", "CSV", "/NH"])?;
+        let tasks = output
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.trim().trim_matches('"').split("\",\"").collect();
+                let name = *fields.first()?;
+                let id = name.strip_prefix(TASK_FOLDER)?;
+                Some(MirroredTask {
+                    id: id.to_string(),
+                    task_name: name.to_string(),
+                    next_run: fields.get(1).unwrap_or(&"").to_string(),
+                    status: fields.get(2).unwrap_or(&"").to_string(),
+                })
+            })
+            .collect();
+        Ok(tasks)
+    }
+
+    pub fn sync(schedules: &[Schedule]) -> Result<Vec<MirroredTask>, String> {
+        let wanted: Vec<&Schedule> = schedules
+            .iter()
+            .filter(|s| s.enabled && s.mirrored())
+            .collect();
+
+        for schedule in &wanted {
+            create(schedule)?;
+        }
+        // 删除已取消镜像或已删除的日程对应的任务
+        for task in list()? {
+            if !wanted.iter().any(|s| s.id == task.id) {
+                delete(&task.task_name)?;
+            }
+        }
+        list()
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct TaskAction {
+        path: String,
+        execute: Option<String>,
+        arguments: Option<String>,
+        enabled: bool,
+    }
+
+    // schtasks 的详细输出随系统语言变化，改用 PowerShell 读取每个任务的执行操作
+    const TASK_ACTIONS_SCRIPT: &str = "@(Get-ScheduledTask | ForEach-Object { $t = $_; $t.Actions | Where-Object { $_.Execute } | ForEach-Object { [pscustomobject]@{ Path = $t.TaskPath + $t.TaskName; Execute = $_.Execute; Arguments = $_.Arguments; Enabled = $t.State -ne 'Disabled' } } }) | ConvertTo-Json -Compress";
+
+    pub fn power_tasks() -> Result<Vec<PowerTask>, String> {
+        let out = crate::cmd::output("powershell", &["-NoProfile", "-NonInteractive", "-Command", TASK_ACTIONS_SCRIPT])?;
+        let out = out.trim();
+        if out.is_empty() {
+            return Ok(Vec::new());
+        }
+        // 只有一项时 ConvertTo-Json 输出对象而不是数组
+        let actions: Vec<TaskAction> = if out.starts_with('[') {
+            serde_json::from_str(out).map_err(|e| e.to_string())?
+        } else {
+            vec![serde_json::from_str(out).map_err(|e| e.to_string())?]
+        };
+        Ok(actions
+            .into_iter()
+            .filter_map(|a| {
+                let execute = a.execute.unwrap_or_default();
+                let arguments = a.arguments.unwrap_or_default();
+                let action = classify(&execute, &arguments)?;
+                Some(PowerTask {
+                    own: a.path.starts_with(TASK_FOLDER),
+                    command: format!("{} {}", execute, arguments).trim().to_string(),
+                    path: a.path,
+                    action,
+                    enabled: a.enabled,
+                })
+            })
+            .collect())
+    }
+
+    pub fn remove(id: Option<&str>) -> Result<usize, String> {
+        let mut removed = 0;
+        for task in list()? {
+            if id.is_none_or(|id| id == task.id) {
+                delete(&task.task_name)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(windows)]
+pub use imp::sync;
+
+#[cfg(windows)]
+#[tauri::command]
+pub fn sync_task_scheduler(store: State<'_, ScheduleStore>) -> Result<Vec<MirroredTask>, AutoShutdownError> {
+    imp::sync(&store.all()).map_err(Into::into)
+}
+
+#[cfg(windows)]
+#[tauri::command]
+pub fn list_mirrored_tasks() -> Result<Vec<MirroredTask>, AutoShutdownError> {
+    imp::list().map_err(Into::into)
+}
+
+// 只移除任务计划程序中的任务，日程本身的镜像选项保持不变，下次同步时会重新创建
+#[cfg(windows)]
+#[tauri::command]
+pub fn remove_mirrored_tasks(id: Option<String>) -> Result<usize, AutoShutdownError> {
+    imp::remove(id.as_deref()).map_err(Into::into)
+}
+
+// 与本应用的规则一起显示，便于发现其他工具或 IT 策略设置的冲突计划
+#[cfg(windows)]
+#[tauri::command(async)]
+pub fn list_power_tasks() -> Result<Vec<PowerTask>, AutoShutdownError> {
+    imp::power_tasks().map_err(Into::into)
+}
+
+#[cfg(not(windows))]
+#[tauri::command]
+pub fn sync_task_scheduler(_store: State<'_, ScheduleStore>) -> Result<Vec<MirroredTask>, AutoShutdownError> {
+    Err("Task Scheduler is only available on Windows".into())
+}
+
+#[cfg(not(windows))]
+#[tauri::command]
+pub fn list_mirrored_tasks() -> Result<Vec<MirroredTask>, AutoShutdownError> {
+    Ok(Vec::new())
+}
+
+#[cfg(not(windows))]
+#[tauri::command]
+pub fn remove_mirrored_tasks(_id: Option<String>) -> Result<usize, AutoShutdownError> {
+    Ok(0)
+}
+
+#[cfg(not(windows))]
+#[tauri::command]
+pub fn list_power_tasks() -> Result<Vec<PowerTask>, AutoShutdownError> {
+    Ok(Vec::new())
+}
@@ -0,0 +1,171 @@
+use crate::notify::Notice;
+use crate::power::PowerAction;
+use crate::schedule_text;
+use crate::settings::SettingsStore;
+use crate::timers::{TimerSpec, TimerStore, TimerTrigger};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+// 长轮询等待时间，HTTP 超时需比它更长
+const POLL_SECS: u64 = 30;
+const RETRY_DELAY: Duration = Duration::from_secs(15);
+// 不带时间的电源命令留出取消的余地
+const DEFAULT_DELAY_SECS: u64 = 60;
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct TelegramConfig {
+    pub token: String,
+    // 只接受这些聊天的命令，通知也发到这些聊天
+    pub allowed_chats: Vec<i64>,
+}
+
+// 设置会写入日志，不输出令牌
+impl fmt::Debug for TelegramConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TelegramConfig")
+            .field("allowed_chats", &self.allowed_chats)
+            .finish_non_exhaustive()
+    }
+}
+
+fn call(config: &TelegramConfig, method: &str, body: Value, timeout: Duration) -> Result<Value, String> {
+    let response: Value = ureq::post(&format!("https://api.telegram.org/bot{}/{}", config.token, method))
+        .timeout(timeout)
+        .send_json(body)
+        .map_err(|e| e.to_string())?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+    if response["ok"].as_bool() != Some(true) {
+        return Err(response["description"].as_str().unwrap_or("Telegram API error").to_string());
+    }
+    Ok(response["result"].clone())
+}
+
+fn send_message(config: &TelegramConfig, chat: i64, text: &str) -> Result<(), String> {
+    call(
+        config,
+        "sendMessage",
+        json!({ "chat_id": chat, "text": text }),
+        Duration::from_secs(10),
+    )
+    .map(|_| ())
+}
+
+// 转发到所有允许的聊天，在后台线程发送
+pub fn forward(app: &AppHandle, notice: &Notice) {
+    let Some(config) = app.state::<SettingsStore>().get().telegram else {
+        return;
+    };
+    if config.token.is_empty() {
+        return;
+    }
+    let text = format!("{}\n{}", notice.title, notice.body);
+    thread::spawn(move || {
+        for chat in &config.allowed_chats {
+            if let Err(e) = send_message(&config, *chat, &text) {
+                tracing::warn!(chat, error = %e, "failed to send telegram message");
+            }
+        }
+    });
+}
+
+fn status_text(app: &AppHandle) -> String {
+    let timers = app.state::<TimerStore>().all();
+    if timers.is_empty() {
+        return "No timers armed.".into();
+    }
+    timers
+        .iter()
+        .map(|t| {
+            let label = if t.spec.label.is_empty() { "Timer" } else { &t.spec.label };
+            match t.deadline().map(|d| (d - Local::now()).num_seconds().max(0)) {
+                Some(secs) => format!("{}: {} in {}m {}s", label, t.spec.action, secs / 60, secs % 60),
+                None => format!("{}: {} (waiting for trigger)", label, t.spec.action),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn handle_command(app: &AppHandle, text: &str) -> String {
+    let mut parts = text.split_whitespace();
+    // 群组中命令可能带 @机器人名
+    let command = parts.next().unwrap_or_default().split('@').next().unwrap_or_default();
+    let argument = parts.next();
+    let action = match command {
+        "/status" => return status_text(app),
+        "/cancel" => {
+            let cancelled = app.state::<TimerStore>().cancel_all();
+            return format!("Cancelled {} timer(s).", cancelled);
+        }
+        "/shutdown" => PowerAction::Shutdown,
+        "/restart" => PowerAction::Restart,
+        "/sleep" => PowerAction::Sleep,
+        _ => return "Commands: /status, /shutdown [30m], /restart [30m], /sleep [30m], /cancel".into(),
+    };
+    let seconds = match argument {
+        Some(arg) => match schedule_text::parse_duration(arg) {
+            Some(seconds) => seconds,
+            None => return format!("Can't understand \"{}\", try 30m or 1h30m.", arg),
+        },
+        None => DEFAULT_DELAY_SECS,
+    };
+    let spec = TimerSpec::new("Telegram".into(), action, TimerTrigger::After { seconds });
+    match app.state::<TimerStore>().arm(None, spec) {
+        Ok(_) => format!("{:?} in {}m {}s. Send /cancel to stop it.", action, seconds / 60, seconds % 60),
+        Err(e) => format!("Failed: {}", e),
+    }
+}
+
+// 一次长轮询，返回下一个要请求的 update id
+fn poll(app: &AppHandle, config: &TelegramConfig, offset: i64) -> Result<i64, String> {
+    let updates = call(
+        config,
+        "getUpdates",
+        json!({ "offset": offset, "timeout": POLL_SECS, "allowed_updates": ["message"] }),
+        Duration::from_secs(POLL_SECS + 10),
+    )?;
+    let mut next = offset;
+    for update in updates.as_array().into_iter().flatten() {
+        next = next.max(update["update_id"].as_i64().unwrap_or_default() + 1);
+        let message = &update["message"];
+        let (Some(chat), Some(text)) = (message["chat"]["id"].as_i64(), message["text"].as_str()) else {
+            continue;
+        };
+        if !config.allowed_chats.contains(&chat) {
+            // 记录聊天 id，方便用户加入允许列表
+            tracing::warn!(chat, "telegram message from a chat that is not allowed");
+            continue;
+        }
+        tracing::info!(chat, text, "telegram command");
+        let reply = handle_command(app, text);
+        if let Err(e) = send_message(config, chat, &reply) {
+            tracing::warn!(chat, error = %e, "failed to reply on telegram");
+        }
+    }
+    Ok(next)
+}
+
+pub fn start(app: AppHandle) {
+    thread::spawn(move || {
+        let mut offset = 0;
+        loop {
+            match app.state::<SettingsStore>().get().telegram {
+                Some(config) if !config.token.is_empty() => match poll(&app, &config, offset) {
+                    Ok(next) => offset = next,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "telegram polling failed");
+                        thread::sleep(RETRY_DELAY);
+                    }
+                },
+                _ => thread::sleep(RETRY_DELAY),
+            }
+        }
+    });
+}
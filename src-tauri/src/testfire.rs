@@ -0,0 +1,67 @@
+use crate::audit::{ActionSource, AuditAction};
+use crate::executor;
+use crate::notify::{self, Notice};
+use crate::power::PowerAction;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TestKind {
+    Notification,
+    Sound,
+    // 默认在模拟模式下走完整的执行流程，sandboxed 为 false 时真正执行
+    Action {
+        action: PowerAction,
+        #[serde(default)]
+        sandboxed: Option<bool>,
+    },
+    All,
+}
+
+#[derive(Serialize)]
+pub struct TestStep {
+    step: &'static str,
+    ok: bool,
+    error: Option<String>,
+}
+
+impl TestStep {
+    fn new(step: &'static str, result: Result<(), String>) -> Self {
+        TestStep {
+            step,
+            ok: result.is_ok(),
+            error: result.err(),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn test_trigger(app: AppHandle, kind: TestKind) -> Vec<TestStep> {
+    tracing::info!(?kind, "test trigger");
+    let mut steps = Vec::new();
+
+    if matches!(kind, TestKind::Notification | TestKind::All) {
+        let notice = Notice::new("AutoShutdown", "Test notification");
+        steps.push(TestStep::new("notification", notify::show_toast(&app, &notice)));
+    }
+    if matches!(kind, TestKind::Sound | TestKind::All) {
+        steps.push(TestStep::new("sound", notify::play_alarm()));
+    }
+
+    let (action, sandboxed) = match kind {
+        TestKind::Action { action, sandboxed } => (Some(action), sandboxed.unwrap_or(true)),
+        TestKind::All => (Some(PowerAction::Shutdown), true),
+        _ => (None, true),
+    };
+    if let Some(action) = action {
+        let result = executor::perform_with(
+            &app,
+            AuditAction::Power { action },
+            ActionSource::Test,
+            sandboxed,
+        );
+        steps.push(TestStep::new("action", result.map_err(|e| e.to_string())));
+    }
+    steps
+}
@@ -0,0 +1,885 @@
+use crate::actions::Action;
+use crate::audit::{self, ActionSource};
+use crate::defer::{DeferOptions, DeferReason};
+use crate::error::AutoShutdownError;
+use crate::events::{self, AppEvent};
+use crate::executor;
+use crate::grace;
+use crate::idle::{ActivityAbort, ActivityResponse};
+use crate::media::MediaMonitor;
+use crate::notify::{self, Notice, NoticeKind};
+use crate::power::PowerAction;
+use crate::pre_action::PreActionOptions;
+use crate::process_events::ProcessEvents;
+use crate::rules::{self, Rule, RuleNotifications};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Mutex, TryLockError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// 多个项目在该时间窗口内先后触发时视为同时触发，只执行其中一个
+const RESOLVE_WINDOW: Duration = Duration::from_secs(3);
+// 应用内计时的项目在到期前多少秒发出提醒，规则可单独设置
+const WARNING_SECS: i64 = 60;
+// 提醒中推迟的默认时长
+const DEFAULT_SNOOZE_MINUTES: u64 = 10;
+// 有进程事件通知时，仍按该间隔完整刷新一次，防止漏掉通知
+const FALLBACK_REFRESH: Duration = Duration::from_secs(30);
+// 计时线程的名称，崩溃处理据此判断计时是否已经停止
+pub const ENGINE_THREAD: &str = "timer-engine";
+
+// 当前交给 shutdown.exe 的项目，供崩溃和退出时撤销
+static OS_TIMER: Mutex<Option<ArmedTimer>> = Mutex::new(None);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimerTrigger {
+    // 相对时间，启动时换算为 At
+    After { seconds: u64 },
+    At { time: DateTime<Local> },
+    // pid、name、cmdline 任一指定即可，匹配的进程全部退出时触发
+    ProcessExit {
+        #[serde(default)]
+        pid: Option<u32>,
+        #[serde(default)]
+        name: Option<String>,
+        // 命令行包含该子串，用于区分同一程序的多个实例
+        #[serde(default)]
+        cmdline: Option<String>,
+    },
+    // 同时监视多个进程，全部退出或任一退出时触发
+    ProcessesExit {
+        targets: Vec<ProcessTarget>,
+        #[serde(default)]
+        mode: ExitMode,
+    },
+    // 当前播放的媒体结束（从播放转为停止）后触发，暂停不算结束
+    MediaEnded,
+}
+
+impl TimerTrigger {
+    fn watches_processes(&self) -> bool {
+        matches!(self, TimerTrigger::ProcessExit { .. } | TimerTrigger::ProcessesExit { .. })
+    }
+
+    fn needs_cmdline(&self) -> bool {
+        match self {
+            TimerTrigger::ProcessExit { cmdline, .. } => cmdline.is_some(),
+            TimerTrigger::ProcessesExit { targets, .. } => targets.iter().any(|t| t.cmdline.is_some()),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitMode {
+    #[default]
+    All,
+    Any,
+}
+
+// 多进程触发中的一项，字段含义与 ProcessExit 相同
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ProcessTarget {
+    pub pid: Option<u32>,
+    pub name: Option<String>,
+    pub cmdline: Option<String>,
+}
+
+impl ProcessTarget {
+    pub fn is_empty(&self) -> bool {
+        self.pid.is_none() && self.name.is_none() && self.cmdline.is_none()
+    }
+
+    pub fn running(&self, sys: &System) -> bool {
+        !process_gone(sys, self.pid, self.name.as_deref(), self.cmdline.as_deref())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerMode {
+    // 由应用自身计时并执行，应用关闭或崩溃后失效
+    InApp,
+    // 已通过 shutdown.exe 交给系统，应用卡住时依然生效；应用退出或崩溃时撤销
+    Os,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TimerSpec {
+    #[serde(default)]
+    pub label: String,
+    pub action: Action,
+    pub trigger: TimerTrigger,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub os_fallback: bool,
+    #[serde(default, skip_serializing_if = "PreActionOptions::is_empty")]
+    pub pre_action: PreActionOptions,
+    #[serde(default, skip_serializing_if = "DeferOptions::is_empty")]
+    pub defer: DeferOptions,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity_abort: Option<ActivityAbort>,
+    // 到期前多少秒提醒，如 [1800, 600, 300, 60]，为空时按规则的设置或默认提醒一次
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warning_secs: Vec<i64>,
+    // 到期后先给出的宽限期秒数，期间无人回应才执行
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace_secs: Option<u64>,
+    // 由规则触发时记录来源规则及触发时的规则快照
+    #[serde(default, skip_deserializing)]
+    pub rule_id: Option<String>,
+    #[serde(skip)]
+    pub rule: Option<Rule>,
+}
+
+impl TimerSpec {
+    pub fn new(label: String, action: impl Into<Action>, trigger: TimerTrigger) -> Self {
+        TimerSpec {
+            label,
+            action: action.into(),
+            trigger,
+            priority: 0,
+            os_fallback: false,
+            pre_action: PreActionOptions::default(),
+            defer: DeferOptions::default(),
+            activity_abort: None,
+            warning_secs: Vec::new(),
+            grace_secs: None,
+            rule_id: None,
+            rule: None,
+        }
+    }
+
+    // 交给 shutdown.exe 后应用不再参与执行，带有应用内步骤的计时不能使用
+    fn os_eligible(&self) -> bool {
+        self.os_fallback
+            && self.pre_action.is_empty()
+            && self.defer.is_empty()
+            && self.activity_abort.is_none()
+            && self.warning_secs.is_empty()
+            && self.grace_secs.is_none()
+            && self.rule_id.is_none()
+            && self.rule.is_none()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ArmedTimer {
+    pub id: String,
+    #[serde(flatten)]
+    pub spec: TimerSpec,
+    pub mode: TimerMode,
+    pub armed_at: DateTime<Local>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct TimerStatus {
+    #[serde(flatten)]
+    timer: ArmedTimer,
+    remaining_secs: Option<i64>,
+}
+
+#[derive(Serialize, Clone)]
+struct ActionDeferred<'a> {
+    id: &'a str,
+    reason: DeferReason,
+    until: DateTime<Local>,
+}
+
+#[derive(Serialize, Clone)]
+struct CountdownInterrupted<'a> {
+    id: &'a str,
+    response: ActivityResponse,
+}
+
+// 到达提醒时间时发出，界面可用 id 取消或推迟该项目
+#[derive(Serialize, Clone)]
+struct Warning<'a> {
+    id: &'a str,
+    label: &'a str,
+    action: &'a Action,
+    // 本次到达的提醒时间
+    checkpoint_secs: i64,
+    remaining_secs: i64,
+    // 允许推迟时的推迟分钟数
+    snooze_minutes: Option<u64>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct TimerFired {
+    pub executed: ArmedTimer,
+    pub superseded: Vec<ArmedTimer>,
+}
+
+impl ArmedTimer {
+    pub fn deadline(&self) -> Option<DateTime<Local>> {
+        match &self.spec.trigger {
+            TimerTrigger::At { time } => Some(*time),
+            _ => None,
+        }
+    }
+
+    pub fn status(&self) -> TimerStatus {
+        TimerStatus {
+            timer: self.clone(),
+            remaining_secs: self
+                .deadline()
+                .map(|d| (d - Local::now()).num_seconds().max(0)),
+        }
+    }
+
+    fn is_due(&self, sys: &System, media_ended: Option<DateTime<Local>>) -> bool {
+        match &self.spec.trigger {
+            TimerTrigger::At { time } => Local::now() >= *time,
+            TimerTrigger::After { .. } => false,
+            TimerTrigger::ProcessExit { pid, name, cmdline } => {
+                process_gone(sys, *pid, name.as_deref(), cmdline.as_deref())
+            }
+            TimerTrigger::ProcessesExit { targets, mode } => match mode {
+                ExitMode::All => targets.iter().all(|t| !t.running(sys)),
+                ExitMode::Any => targets.iter().any(|t| !t.running(sys)),
+            },
+            TimerTrigger::MediaEnded => media_ended.is_some_and(|t| t > self.armed_at),
+        }
+    }
+}
+
+// 比较进程名，忽略大小写和 .exe 后缀
+pub fn name_matches(process_name: &str, name: &str) -> bool {
+    let strip = |s: &str| s.to_lowercase().trim_end_matches(".exe").to_string();
+    strip(process_name) == strip(name)
+}
+
+// 命令行子串匹配忽略大小写，需要刷新进程时读取命令行
+fn cmdline_contains(process: &Process, needle: &str) -> bool {
+    let cmdline = process
+        .cmd()
+        .iter()
+        .map(|a| a.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    cmdline.contains(&needle.to_lowercase())
+}
+
+pub fn process_matching(sys: &System, name: Option<&str>, cmdline: Option<&str>) -> bool {
+    sys.processes().values().any(|p| {
+        name.is_none_or(|name| name_matches(&p.name().to_string_lossy(), name))
+            && cmdline.is_none_or(|cmdline| cmdline_contains(p, cmdline))
+    })
+}
+
+// 指定的 pid 和匹配条件都已找不到进程
+fn process_gone(sys: &System, pid: Option<u32>, name: Option<&str>, cmdline: Option<&str>) -> bool {
+    let pid_gone = pid.is_none_or(|pid| sys.process(Pid::from_u32(pid)).is_none());
+    let matched_gone = (name.is_none() && cmdline.is_none()) || !process_matching(sys, name, cmdline);
+    pid_gone && matched_gone
+}
+
+pub fn process_running(sys: &System, name: &str) -> bool {
+    process_matching(sys, Some(name), None)
+}
+
+// 同名进程的 CPU 占用之和，换算为占全部核心的百分比；没有运行时为 None
+pub fn process_cpu(sys: &System, name: &str) -> Option<f32> {
+    let usages: Vec<f32> = sys
+        .processes()
+        .values()
+        .filter(|p| name_matches(&p.name().to_string_lossy(), name))
+        .map(|p| p.cpu_usage())
+        .collect();
+    if usages.is_empty() {
+        return None;
+    }
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    Some(usages.iter().sum::<f32>() / cores as f32)
+}
+
+// 默认刷新不读取命令行，只在需要匹配命令行时额外读取
+pub fn refresh_processes(sys: &mut System, with_cmdline: bool) {
+    if with_cmdline {
+        sys.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::new().with_cpu().with_cmd(UpdateKind::OnlyIfNotSet),
+        );
+    } else {
+        sys.refresh_processes(ProcessesToUpdate::All, true);
+    }
+}
+
+#[derive(Default)]
+pub struct TimerStore(Mutex<Vec<ArmedTimer>>);
+
+impl TimerStore {
+    pub fn get(&self, id: &str) -> Option<ArmedTimer> {
+        self.0.lock().unwrap().iter().find(|t| t.id == id).cloned()
+    }
+
+    pub fn all(&self) -> Vec<ArmedTimer> {
+        self.0.lock().unwrap().clone()
+    }
+
+    // 同 id 的旧项目会被替换
+    pub fn arm(&self, id: Option<String>, mut spec: TimerSpec) -> Result<ArmedTimer, String> {
+        spec.action.validate()?;
+        if spec.warning_secs.iter().any(|s| *s <= 0) {
+            return Err("Warning times must be greater than zero".into());
+        }
+        spec.trigger = match spec.trigger {
+            TimerTrigger::After { seconds: 0 } => {
+                return Err("Countdown must be longer than zero seconds".into())
+            }
+            TimerTrigger::After { seconds } => TimerTrigger::At {
+                time: i64::try_from(seconds)
+                    .ok()
+                    .and_then(chrono::Duration::try_seconds)
+                    .and_then(|d| Local::now().checked_add_signed(d))
+                    .ok_or("Countdown is too long")?,
+            },
+            TimerTrigger::ProcessExit { pid: None, name: None, cmdline: None } => {
+                return Err("Process trigger needs a pid, a name or a command line".into())
+            }
+            TimerTrigger::ProcessesExit { ref targets, .. } if targets.is_empty() => {
+                return Err("Process trigger needs at least one process".into())
+            }
+            TimerTrigger::ProcessesExit { ref targets, .. } if targets.iter().any(ProcessTarget::is_empty) => {
+                return Err("Each process needs a pid, a name or a command line".into())
+            }
+            other => other,
+        };
+
+        let mut timers = self.0.lock().unwrap();
+        let id = id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        if let Some(pos) = timers.iter().position(|t| t.id == id) {
+            let previous = timers.remove(pos);
+            if previous.mode == TimerMode::Os {
+                abort_os();
+            }
+        }
+
+        // Windows 同一时间只能挂起一个 shutdown.exe 计划，已被占用时退回应用内计时
+        let os_slot_free = !timers.iter().any(|t| t.mode == TimerMode::Os);
+        let mode = match &spec.trigger {
+            TimerTrigger::At { time } if spec.os_eligible() && os_slot_free => {
+                let seconds = (*time - Local::now()).num_seconds().max(1) as u64;
+                if arm_os(&spec.action, seconds) {
+                    TimerMode::Os
+                } else {
+                    TimerMode::InApp
+                }
+            }
+            _ => TimerMode::InApp,
+        };
+
+        let timer = ArmedTimer {
+            id,
+            spec,
+            mode,
+            armed_at: Local::now(),
+        };
+        tracing::info!(id = %timer.id, action = ?timer.spec.action, trigger = ?timer.spec.trigger, mode = ?timer.mode, "timer armed");
+        if timer.mode == TimerMode::Os {
+            *OS_TIMER.lock().unwrap() = Some(timer.clone());
+        }
+        timers.push(timer.clone());
+        Ok(timer)
+    }
+
+    pub fn cancel(&self, id: &str) -> Option<ArmedTimer> {
+        let mut timers = self.0.lock().unwrap();
+        let pos = timers.iter().position(|t| t.id == id)?;
+        let timer = timers.remove(pos);
+        tracing::info!(id = %timer.id, "timer cancelled");
+        if timer.mode == TimerMode::Os {
+            abort_os();
+        }
+        Some(timer)
+    }
+
+    // 取消全部计时，返回取消的数量
+    pub fn cancel_all(&self) -> usize {
+        let ids: Vec<String> = self.all().into_iter().map(|t| t.id).collect();
+        ids.iter().filter(|id| self.cancel(id).is_some()).count()
+    }
+
+    fn take_due(
+        &self,
+        sys: &mut System,
+        refresh: &mut ProcessRefresh,
+        events: &ProcessEvents,
+        media_ended: Option<DateTime<Local>>,
+    ) -> Vec<ArmedTimer> {
+        let mut timers = self.0.lock().unwrap();
+        let watched = timers
+            .iter()
+            .filter(|t| t.spec.trigger.watches_processes())
+            .map(|t| t.id.clone())
+            .collect();
+        if refresh.needed(events, watched) {
+            let with_cmdline = timers.iter().any(|t| t.spec.trigger.needs_cmdline());
+            refresh_processes(sys, with_cmdline);
+        }
+        let (due, rest): (Vec<_>, Vec<_>) = timers.drain(..).partition(|t| t.is_due(sys, media_ended));
+        *timers = rest;
+        // 系统计划到期后已由 Windows 执行，不再需要撤销
+        if due.iter().any(|t| t.mode == TimerMode::Os) {
+            *OS_TIMER.lock().unwrap() = None;
+        }
+        due
+    }
+
+    // 系统计划已被撤销，改由应用内计时；在崩溃处理中调用，锁被占用时放弃
+    pub fn release_os(&self) {
+        let mut timers = match self.0.try_lock() {
+            Ok(timers) => timers,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return,
+        };
+        for timer in timers.iter_mut().filter(|t| t.mode == TimerMode::Os) {
+            timer.mode = TimerMode::InApp;
+        }
+    }
+}
+
+// 记录上次刷新进程列表时的状态，进程事件可用时只在有进程退出、
+// 新增监视进程的计时或规则、超过兜底间隔时刷新，否则每次都刷新
+#[derive(Default)]
+pub struct ProcessRefresh {
+    exits: u64,
+    watched: HashSet<String>,
+    last: Option<Instant>,
+}
+
+impl ProcessRefresh {
+    // watched 为需要进程列表的计时或规则的 id
+    pub fn needed(&mut self, events: &ProcessEvents, watched: HashSet<String>) -> bool {
+        if watched.is_empty() {
+            self.watched.clear();
+            return false;
+        }
+        let exits = events.exits();
+        let needed = !events.live()
+            || exits != self.exits
+            || !watched.is_subset(&self.watched)
+            || self.last.is_none_or(|t| t.elapsed() >= FALLBACK_REFRESH);
+        if needed {
+            self.exits = exits;
+            self.last = Some(Instant::now());
+        }
+        self.watched = watched;
+        needed
+    }
+}
+
+// shutdown.exe 只能安排关机和重启，其他操作仍由应用内计时
+fn arm_os(action: &Action, seconds: u64) -> bool {
+    let flags: &[&str] = match action.power() {
+        Some(PowerAction::Shutdown) => &["/s"],
+        Some(PowerAction::FullShutdown) => &["/s", "/full"],
+        Some(PowerAction::Restart) => &["/r"],
+        _ => return false,
+    };
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("shutdown")
+            .args(flags)
+            .args(["/t", &seconds.to_string()])
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (flags, seconds);
+        false
+    }
+}
+
+// 崩溃处理中也会调用，不能等待可能被占用的锁
+pub fn os_timer() -> Option<ArmedTimer> {
+    match OS_TIMER.try_lock() {
+        Ok(timer) => timer.clone(),
+        Err(TryLockError::Poisoned(e)) => e.into_inner().clone(),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+pub fn abort_os() {
+    *OS_TIMER.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    cancel_os_schedule();
+}
+
+// 崩溃处理中使用：记录被占用时只撤销系统计划
+pub fn abort_os_on_panic() {
+    match OS_TIMER.try_lock() {
+        Ok(mut timer) => *timer = None,
+        Err(TryLockError::Poisoned(e)) => *e.into_inner() = None,
+        Err(TryLockError::WouldBlock) => {}
+    }
+    cancel_os_schedule();
+}
+
+fn cancel_os_schedule() {
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("shutdown").arg("/a").status().ok();
+    }
+}
+
+// 优先级高者胜出，优先级相同时执行最激进的操作，再相同则先启动者胜出，最后按 id 保证结果确定
+fn resolve(mut fired: Vec<ArmedTimer>) -> TimerFired {
+    fired.sort_by(|a, b| {
+        b.spec
+            .priority
+            .cmp(&a.spec.priority)
+            .then(b.spec.action.severity().cmp(&a.spec.action.severity()))
+            .then(a.armed_at.cmp(&b.armed_at))
+            .then(a.id.cmp(&b.id))
+    });
+    let executed = fired.remove(0);
+    TimerFired {
+        executed,
+        superseded: fired,
+    }
+}
+
+// 与上一轮对比得出新启动和被取消的项目，同 id 重新启动视为取消后再启动
+fn publish_changes(app: &AppHandle, known: &mut Vec<ArmedTimer>, current: &[ArmedTimer], due: &[ArmedTimer]) {
+    let same = |a: &ArmedTimer, b: &ArmedTimer| a.id == b.id && a.armed_at == b.armed_at;
+    for timer in known.iter() {
+        if due.iter().any(|t| same(t, timer)) {
+            events::publish(app, AppEvent::CountdownFinished { timer: timer.clone() });
+        } else if !current.iter().any(|t| same(t, timer)) {
+            events::publish(app, AppEvent::CountdownCancelled { timer: timer.clone() });
+        }
+    }
+    for timer in current.iter().chain(due) {
+        if !known.iter().any(|t| same(t, timer)) {
+            events::publish(app, AppEvent::CountdownStarted { timer: timer.clone() });
+            if due.iter().any(|t| same(t, timer)) {
+                events::publish(app, AppEvent::CountdownFinished { timer: timer.clone() });
+            }
+        }
+    }
+    *known = current.to_vec();
+}
+
+#[tauri::command]
+pub fn arm_timer(store: State<'_, TimerStore>, spec: TimerSpec) -> Result<TimerStatus, AutoShutdownError> {
+    store.arm(None, spec).map(|t| t.status()).map_err(Into::into)
+}
+
+#[tauri::command]
+pub fn cancel_timer(store: State<'_, TimerStore>, id: String) -> Result<(), AutoShutdownError> {
+    store
+        .cancel(&id)
+        .map(|_| ())
+        .ok_or_else(|| AutoShutdownError::NotFound(format!("Timer {} not found", id)))
+}
+
+// 推迟正在倒计时的项目，未指定时长时使用规则设置或默认值；规则可设置为不允许推迟
+#[tauri::command]
+pub fn snooze_timer(
+    store: State<'_, TimerStore>,
+    id: String,
+    minutes: Option<u64>,
+) -> Result<TimerStatus, AutoShutdownError> {
+    let timer = store
+        .get(&id)
+        .ok_or_else(|| AutoShutdownError::NotFound(format!("Timer {} not found", id)))?;
+    let settings = rule_notifications(&timer).cloned();
+    if settings.as_ref().is_some_and(|n| !n.snoozable) {
+        return Err(AutoShutdownError::InvalidInput("This rule does not allow snoozing".into()));
+    }
+    let Some(deadline) = timer.deadline() else {
+        return Err(AutoShutdownError::InvalidInput("Only countdowns can be snoozed".into()));
+    };
+    let minutes = minutes
+        .or(settings.map(|n| n.snooze_minutes))
+        .unwrap_or(DEFAULT_SNOOZE_MINUTES)
+        .max(1);
+    tracing::info!(id = %timer.id, minutes, "timer snoozed");
+    let spec = TimerSpec {
+        trigger: TimerTrigger::At {
+            time: deadline.max(Local::now()) + chrono::Duration::minutes(minutes as i64),
+        },
+        ..timer.spec
+    };
+    store.arm(Some(id), spec).map(|t| t.status()).map_err(Into::into)
+}
+
+#[tauri::command]
+pub fn list_timers(store: State<'_, TimerStore>) -> Vec<TimerStatus> {
+    store.all().iter().map(ArmedTimer::status).collect()
+}
+
+#[tauri::command]
+pub fn get_timer_status(store: State<'_, TimerStore>, id: String) -> Option<TimerStatus> {
+    store.get(&id).map(|t| t.status())
+}
+
+// 推迟条件成立时以相同 id 重新安排，到时再次检查
+fn defer_timer(app: &AppHandle, store: &TimerStore, timer: ArmedTimer, reason: DeferReason) {
+    let until = Local::now() + chrono::Duration::seconds(timer.spec.defer.recheck_secs.max(1) as i64);
+    tracing::info!(id = %timer.id, ?reason, %until, "timer deferred");
+    let _ = app.emit(
+        "action-deferred",
+        ActionDeferred {
+            id: &timer.id,
+            reason,
+            until,
+        },
+    );
+    let spec = TimerSpec {
+        trigger: TimerTrigger::At { time: until },
+        ..timer.spec
+    };
+    if let Err(e) = store.arm(Some(timer.id), spec) {
+        tracing::warn!(error = %e, "failed to re-arm deferred timer");
+    }
+}
+
+// 倒计时最后阶段检测到用户输入时取消或延后
+fn check_activity(app: &AppHandle, store: &TimerStore, timers: &[ArmedTimer]) {
+    for timer in timers {
+        let (Some(guard), Some(remaining)) = (timer.spec.activity_abort, timer.status().remaining_secs) else {
+            continue;
+        };
+        if timer.mode != TimerMode::InApp || !guard.input_detected(remaining) {
+            continue;
+        }
+        tracing::info!(id = %timer.id, response = ?guard.response, "user activity during countdown");
+        let _ = app.emit(
+            "countdown-interrupted",
+            CountdownInterrupted {
+                id: &timer.id,
+                response: guard.response,
+            },
+        );
+        match guard.response {
+            ActivityResponse::Cancel => {
+                store.cancel(&timer.id);
+            }
+            ActivityResponse::Postpone { seconds } => {
+                let spec = TimerSpec {
+                    trigger: TimerTrigger::After { seconds: seconds.max(1) },
+                    ..timer.spec.clone()
+                };
+                let _ = store.arm(Some(timer.id.clone()), spec);
+            }
+        }
+    }
+}
+
+// 宽限期在单独的线程中等待，无人回应时以原 id 立即重新提交
+fn start_grace(app: &AppHandle, timer: ArmedTimer, secs: u64) {
+    let app = app.clone();
+    thread::spawn(move || {
+        if grace::user_present(&app, &timer, Duration::from_secs(secs)) {
+            cancel_fired(&app, timer, "Cancelled: user is still here".into());
+            return;
+        }
+        let spec = TimerSpec {
+            trigger: TimerTrigger::At { time: Local::now() },
+            os_fallback: false,
+            grace_secs: None,
+            ..timer.spec
+        };
+        if let Err(e) = app.state::<TimerStore>().arm(Some(timer.id), spec) {
+            tracing::warn!(error = %e, "failed to re-arm timer after idle grace");
+        }
+    });
+}
+
+// 取消条件成立时不执行，按执行失败记录
+fn cancel_fired(app: &AppHandle, timer: ArmedTimer, reason: String) {
+    tracing::info!(id = %timer.id, reason, "timer cancelled by condition");
+    let result = Err(reason);
+    audit::record(
+        app,
+        timer.spec.action.audit(),
+        ActionSource::from_timer(&timer),
+        &result,
+        false,
+    );
+    let fired = TimerFired {
+        executed: timer,
+        superseded: Vec::new(),
+    };
+    rules::record_firing(app, &fired, &result.map_err(AutoShutdownError::Failed));
+    let _ = app.emit("timer-fired", fired);
+}
+
+fn rule_notifications(timer: &ArmedTimer) -> Option<&RuleNotifications> {
+    timer.spec.rule.as_ref().and_then(|r| r.notifications.as_ref())
+}
+
+// 项目自身的提醒时间优先，其次是规则的设置
+fn warning_leads(timer: &ArmedTimer) -> Vec<i64> {
+    if !timer.spec.warning_secs.is_empty() {
+        return timer.spec.warning_secs.clone();
+    }
+    rule_notifications(timer).map_or_else(|| vec![WARNING_SECS], |n| n.warning_secs.clone())
+}
+
+// 规则自带提醒设置时按规则的渠道和提示音发送，允许推迟时附带推迟时长
+fn warning_notice(timer: &ArmedTimer, remaining: i64) -> Notice {
+    let notice = Notice::new("AutoShutdown", format!("{} in {} seconds", timer.spec.action, remaining))
+        .with_kind(NoticeKind::CountdownWarning)
+        .with_timer(timer.id.clone());
+    let Some(settings) = rule_notifications(timer) else {
+        return notice.with_sound().with_snooze(timer.id.clone(), DEFAULT_SNOOZE_MINUTES);
+    };
+    let mut notice = if settings.sound { notice.with_sound() } else { notice };
+    if !settings.channels.is_empty() {
+        notice = notice.with_channels(settings.channels.clone());
+    }
+    if settings.snoozable {
+        notice = notice.with_snooze(timer.id.clone(), settings.snooze_minutes);
+    }
+    notice
+}
+
+pub fn start_engine(app: AppHandle) {
+    let engine = thread::Builder::new().name(ENGINE_THREAD.into()).spawn(move || {
+        let mut sys = System::new();
+        let mut refresh = ProcessRefresh::default();
+        let mut pending: Vec<ArmedTimer> = Vec::new();
+        let mut first_fired: Option<Instant> = None;
+        // 已发出的提醒，推迟后以新的启动时间重新计算
+        let mut warned: HashSet<(String, DateTime<Local>, i64)> = HashSet::new();
+        let mut known: Vec<ArmedTimer> = Vec::new();
+        // 准备步骤和电源操作可能持续数分钟，在单独的线程执行，计时照常推进
+        let mut running: Option<JoinHandle<()>> = None;
+
+        loop {
+            thread::sleep(Duration::from_secs(1));
+            let store = app.state::<TimerStore>();
+
+            check_activity(&app, &store, &store.all());
+            let timers = store.all();
+            warned.retain(|(id, armed_at, _)| timers.iter().any(|t| &t.id == id && &t.armed_at == armed_at));
+            for timer in &timers {
+                let Some(remaining) = timer.status().remaining_secs else {
+                    continue;
+                };
+                if timer.mode != TimerMode::InApp {
+                    continue;
+                }
+                // 同时到达多个提醒时间时只按最早的一个提醒一次
+                let reached = warning_leads(timer)
+                    .into_iter()
+                    .filter(|lead| remaining <= *lead && warned.insert((timer.id.clone(), timer.armed_at, *lead)))
+                    .min();
+                if let Some(checkpoint) = reached {
+                    let notice = warning_notice(timer, remaining);
+                    let _ = app.emit(
+                        "warning",
+                        Warning {
+                            id: &timer.id,
+                            label: &timer.spec.label,
+                            action: &timer.spec.action,
+                            checkpoint_secs: checkpoint,
+                            remaining_secs: remaining,
+                            snooze_minutes: notice.snooze.as_ref().map(|s| s.minutes),
+                        },
+                    );
+                    notify::dispatch(&app, notice);
+                }
+            }
+
+            // 系统计时的项目到期后由 Windows 执行，这里只需移除
+            let due = store.take_due(
+                &mut sys,
+                &mut refresh,
+                &app.state::<ProcessEvents>(),
+                app.state::<MediaMonitor>().ended_at(),
+            );
+            publish_changes(&app, &mut known, &store.all(), &due);
+            for timer in due {
+                if timer.mode == TimerMode::Os {
+                    let _ = app.emit("timer-fired", TimerFired { executed: timer, superseded: Vec::new() });
+                } else if let Some(reason) = timer.spec.defer.cancel_reason() {
+                    cancel_fired(&app, timer, format!("Cancelled: {:?}", reason));
+                } else if let Some(reason) = timer.spec.defer.blocking_reason(&app) {
+                    defer_timer(&app, &store, timer, reason);
+                } else if let Some(secs) = timer.spec.grace_secs.filter(|s| *s > 0) {
+                    start_grace(&app, timer, secs);
+                } else {
+                    first_fired.get_or_insert_with(Instant::now);
+                    pending.push(timer);
+                }
+            }
+
+            // 上一个操作仍在执行时，新到期的项目等它结束后再一起裁决
+            let busy = running.as_ref().is_some_and(|r| !r.is_finished());
+            if !busy && first_fired.is_some_and(|t| t.elapsed() >= RESOLVE_WINDOW) {
+                first_fired = None;
+                let fired = resolve(std::mem::take(&mut pending));
+                tracing::info!(
+                    executed = %fired.executed.id,
+                    superseded = ?fired.superseded.iter().map(|t| &t.id).collect::<Vec<_>>(),
+                    "timer fired"
+                );
+                let app = app.clone();
+                running = Some(thread::spawn(move || {
+                    let result = executor::perform_action(
+                        &app,
+                        &fired.executed.spec.action,
+                        ActionSource::from_timer(&fired.executed),
+                        &fired.executed.spec.pre_action,
+                    );
+                    rules::record_firing(&app, &fired, &result);
+                    let _ = app.emit("timer-fired", fired);
+                }));
+            }
+
+            let statuses: Vec<TimerStatus> = store.all().iter().map(ArmedTimer::status).collect();
+            let _ = app.emit("timers-tick", &statuses);
+            crate::countdown::emit_tick(&app, &store);
+        }
+    });
+    if let Err(e) = engine {
+        tracing::error!(error = %e, "failed to start timer engine");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(extra: serde_json::Value) -> TimerSpec {
+        let mut spec = serde_json::json!({
+            "action": "shutdown",
+            "trigger": { "kind": "after", "seconds": 600 },
+            "os_fallback": true,
+        });
+        spec.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(spec).unwrap()
+    }
+
+    #[test]
+    fn only_plain_timers_use_os_mode() {
+        assert!(spec(serde_json::json!({})).os_eligible());
+        assert!(!spec(serde_json::json!({ "os_fallback": false })).os_eligible());
+        assert!(!spec(serde_json::json!({ "pre_action": { "shutdown_wsl": true } })).os_eligible());
+        assert!(!spec(serde_json::json!({ "warning_secs": [300] })).os_eligible());
+        assert!(!spec(serde_json::json!({ "grace_secs": 30 })).os_eligible());
+    }
+
+    #[test]
+    fn pre_action_keeps_timer_in_app() {
+        let store = TimerStore::default();
+        let timer = store.arm(None, spec(serde_json::json!({ "pre_action": { "shutdown_wsl": true } }))).unwrap();
+        assert_eq!(timer.mode, TimerMode::InApp);
+    }
+}
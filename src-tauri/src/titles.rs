@@ -0,0 +1,33 @@
+use regex::Regex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use sysinfo::System;
+
+// 窗口标题或窗口列表变化的次数，由窗口监视器的事件钩子递增
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub fn notify_changed() {
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+// 有可见窗口的标题匹配，指定 process 时只看该进程的窗口
+#[cfg(windows)]
+pub fn any_matches(sys: &System, pattern: &Regex, process: Option<&str>) -> bool {
+    use sysinfo::Pid;
+
+    crate::enumerate_windows().iter().any(|w| {
+        let owned = process.is_none_or(|name| {
+            sys.process(Pid::from_u32(w.pid))
+                .is_some_and(|p| crate::timers::name_matches(&p.name().to_string_lossy(), name))
+        });
+        owned && pattern.is_match(&w.title)
+    })
+}
+
+#[cfg(not(windows))]
+pub fn any_matches(_sys: &System, _pattern: &Regex, _process: Option<&str>) -> bool {
+    false
+}
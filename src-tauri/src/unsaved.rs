@@ -0,0 +1,136 @@
+use crate::confirm::Confirmations;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+// 标题中常见的未保存标记
+const DIRTY_MARKERS: &[&str] = &["Unsaved", "Modified", "未保存", "已修改", "●"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsavedWorkPolicy {
+    Ignore,
+    // 只发出提醒，照常执行
+    #[default]
+    Warn,
+    // 等待用户确认，超时未确认则取消
+    Hold,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct UnsavedWorkOptions {
+    pub policy: UnsavedWorkPolicy,
+    pub hold_timeout_secs: u64,
+}
+
+impl Default for UnsavedWorkOptions {
+    fn default() -> Self {
+        UnsavedWorkOptions {
+            policy: UnsavedWorkPolicy::default(),
+            hold_timeout_secs: 300,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsavedReason {
+    DirtyTitle,
+    SaveDialog,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct UnsavedApp {
+    pub pid: u32,
+    pub name: String,
+    pub title: String,
+    pub reason: UnsavedReason,
+}
+
+#[derive(Serialize, Clone)]
+struct PossibleUnsavedWork<'a> {
+    apps: &'a [UnsavedApp],
+    // 需要确认时通过 respond_confirmation 回应
+    confirmation_id: Option<&'a str>,
+}
+
+fn is_dirty_title(title: &str) -> bool {
+    let title = title.trim();
+    title.starts_with('*') || title.ends_with('*') || DIRTY_MARKERS.iter().any(|m| title.contains(m))
+}
+
+// 保存对话框是标准对话框类 #32770，标题包含“保存”
+fn is_save_dialog(class: &str, title: &str) -> bool {
+    class == "#32770" && (title.contains("Save") || title.contains("保存"))
+}
+
+#[cfg(windows)]
+pub fn scan() -> Vec<UnsavedApp> {
+    use sysinfo::{Pid, ProcessesToUpdate, System};
+
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::All, false);
+    crate::enumerate_windows()
+        .into_iter()
+        .filter_map(|w| {
+            let reason = if is_save_dialog(&crate::window_class(w.hwnd), &w.title) {
+                UnsavedReason::SaveDialog
+            } else if is_dirty_title(&w.title) {
+                UnsavedReason::DirtyTitle
+            } else {
+                return None;
+            };
+            let name = sys.process(Pid::from_u32(w.pid))?.name().to_string_lossy().to_string();
+            crate::is_user_app(&name).then_some(UnsavedApp {
+                pid: w.pid,
+                name,
+                title: w.title,
+                reason,
+            })
+        })
+        .collect()
+}
+
+#[cfg(not(windows))]
+pub fn scan() -> Vec<UnsavedApp> {
+    Vec::new()
+}
+
+// 返回 Err 表示用户拒绝或未在时限内确认
+pub fn check(app: &AppHandle, options: &UnsavedWorkOptions) -> Result<(), String> {
+    if options.policy == UnsavedWorkPolicy::Ignore {
+        return Ok(());
+    }
+    let apps = scan();
+    if apps.is_empty() {
+        return Ok(());
+    }
+    tracing::info!(apps = ?apps.iter().map(|a| &a.name).collect::<Vec<_>>(), "possible unsaved work");
+
+    if options.policy == UnsavedWorkPolicy::Warn {
+        let _ = app.emit(
+            "possible-unsaved-work",
+            PossibleUnsavedWork {
+                apps: &apps,
+                confirmation_id: None,
+            },
+        );
+        return Ok(());
+    }
+
+    let confirmations = app.state::<Confirmations>();
+    let id = confirmations.request();
+    let _ = app.emit(
+        "possible-unsaved-work",
+        PossibleUnsavedWork {
+            apps: &apps,
+            confirmation_id: Some(&id),
+        },
+    );
+    match confirmations.wait(&id, Duration::from_secs(options.hold_timeout_secs)) {
+        Some(true) => Ok(()),
+        Some(false) => Err("Cancelled because of unsaved work".into()),
+        None => Err("Unsaved work was not confirmed in time".into()),
+    }
+}
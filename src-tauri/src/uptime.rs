@@ -0,0 +1,68 @@
+use chrono::{DateTime, Local, TimeZone};
+use serde::Serialize;
+use sysinfo::System;
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct LastWake {
+    pub sleep_time: Option<DateTime<Local>>,
+    pub wake_time: Option<DateTime<Local>>,
+    // 如 "电源按钮"、"USB 输入设备"，未知时为 None
+    pub wake_source: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct UptimeInfo {
+    pub boot_time: DateTime<Local>,
+    // 启用快速启动时，关机再开机不会重置，只有重启或完全关机才会
+    pub uptime_secs: u64,
+    pub last_wake: Option<LastWake>,
+}
+
+pub fn uptime_secs() -> u64 {
+    System::uptime()
+}
+
+// Power-Troubleshooter 事件 1 记录最近一次从睡眠或休眠恢复，按字段名读取，不受系统语言影响
+#[cfg(windows)]
+fn last_wake() -> Option<LastWake> {
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Raw {
+        sleep_time: Option<String>,
+        wake_time: Option<String>,
+        wake_source: Option<String>,
+    }
+
+    const SCRIPT: &str = "$e = Get-WinEvent -FilterHashtable @{ LogName = 'System'; ProviderName = 'Microsoft-Windows-Power-Troubleshooter'; Id = 1 } -MaxEvents 1 -ErrorAction SilentlyContinue; if ($e) { $d = @{}; ([xml]$e.ToXml()).Event.EventData.Data | ForEach-Object { $d[$_.Name] = $_.'#text' }; [pscustomobject]@{ SleepTime = $d['SleepTime']; WakeTime = $d['WakeTime']; WakeSource = $d['WakeSourceText'] } | ConvertTo-Json -Compress }";
+
+    let out = crate::cmd::output("powershell", &["-NoProfile", "-NonInteractive", "-Command", SCRIPT]).ok()?;
+    let raw: Raw = serde_json::from_str(out.trim()).ok()?;
+    // 事件中的时间为 UTC
+    let parse = |time: Option<String>| {
+        DateTime::parse_from_rfc3339(&time?).ok().map(|t| t.with_timezone(&Local))
+    };
+    Some(LastWake {
+        sleep_time: parse(raw.sleep_time),
+        wake_time: parse(raw.wake_time),
+        wake_source: raw.wake_source.filter(|s| !s.trim().is_empty()),
+    })
+}
+
+#[cfg(not(windows))]
+fn last_wake() -> Option<LastWake> {
+    None
+}
+
+#[tauri::command(async)]
+pub fn get_uptime_info() -> UptimeInfo {
+    UptimeInfo {
+        boot_time: Local
+            .timestamp_opt(System::boot_time() as i64, 0)
+            .single()
+            .unwrap_or_else(Local::now),
+        uptime_secs: uptime_secs(),
+        last_wake: last_wake(),
+    }
+}
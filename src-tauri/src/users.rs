@@ -0,0 +1,199 @@
+use crate::confirm::Confirmations;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+#[derive(Serialize, Clone, Debug)]
+pub struct UserSession {
+    pub id: u32,
+    pub user: String,
+    pub station: String,
+    // 已断开但未注销的会话为 false
+    pub active: bool,
+    // 通过远程桌面连接
+    pub remote: bool,
+    // 本应用所在的会话
+    pub current: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OtherUsersPolicy {
+    Ignore,
+    #[default]
+    Warn,
+    // 等待用户确认，超时未确认则取消
+    Confirm,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct OtherUsersOptions {
+    pub policy: OtherUsersPolicy,
+    // 设置后向其他用户的桌面发送该消息
+    pub message: Option<String>,
+    pub hold_timeout_secs: u64,
+}
+
+impl Default for OtherUsersOptions {
+    fn default() -> Self {
+        OtherUsersOptions {
+            policy: OtherUsersPolicy::default(),
+            message: None,
+            hold_timeout_secs: 300,
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct OtherUsersLoggedIn<'a> {
+    sessions: &'a [UserSession],
+    confirmation_id: Option<&'a str>,
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::UserSession;
+    use windows::core::{HSTRING, PWSTR};
+    use windows::Win32::System::RemoteDesktop::{
+        ProcessIdToSessionId, WTSActive, WTSClientProtocolType, WTSEnumerateSessionsW, WTSFreeMemory,
+        WTSQuerySessionInformationW, WTSSendMessageW, WTSUserName, WTS_CURRENT_SERVER_HANDLE, WTS_INFO_CLASS,
+        WTS_SESSION_INFOW,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{MB_ICONWARNING, MB_OK, MESSAGEBOX_RESULT};
+
+    unsafe fn query(session: u32, class: WTS_INFO_CLASS) -> Option<Vec<u8>> {
+        let mut buffer = PWSTR::null();
+        let mut bytes = 0u32;
+        WTSQuerySessionInformationW(WTS_CURRENT_SERVER_HANDLE, session, class, &mut buffer, &mut bytes).ok()?;
+        let data = std::slice::from_raw_parts(buffer.0 as *const u8, bytes as usize).to_vec();
+        WTSFreeMemory(buffer.0 as _);
+        Some(data)
+    }
+
+    pub fn list_sessions() -> Vec<UserSession> {
+        let mut current = 0u32;
+        unsafe {
+            let _ = ProcessIdToSessionId(std::process::id(), &mut current);
+        }
+
+        let mut infos: *mut WTS_SESSION_INFOW = std::ptr::null_mut();
+        let mut count = 0u32;
+        if unsafe { WTSEnumerateSessionsW(WTS_CURRENT_SERVER_HANDLE, 0, 1, &mut infos, &mut count) }.is_err() {
+            return Vec::new();
+        }
+        let sessions = unsafe { std::slice::from_raw_parts(infos, count as usize) }
+            .iter()
+            .filter_map(|info| unsafe {
+                let user = query(info.SessionId, WTSUserName).map(|data| {
+                    let wide: Vec<u16> = data.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+                    String::from_utf16_lossy(&wide).trim_end_matches('\0').to_string()
+                })?;
+                // 服务会话和登录界面没有用户名
+                if user.is_empty() {
+                    return None;
+                }
+                // 协议类型：0 本地控制台，2 远程桌面
+                let protocol = query(info.SessionId, WTSClientProtocolType)
+                    .and_then(|data| data.get(..2).map(|b| u16::from_le_bytes([b[0], b[1]])))
+                    .unwrap_or(0);
+                Some(UserSession {
+                    id: info.SessionId,
+                    user,
+                    station: info.pWinStationName.to_string().unwrap_or_default(),
+                    active: info.State == WTSActive,
+                    remote: protocol == 2,
+                    current: info.SessionId == current,
+                })
+            })
+            .collect();
+        unsafe { WTSFreeMemory(infos as _) };
+        sessions
+    }
+
+    // 不等待对方点击，消息框 5 分钟后自动关闭
+    pub fn send_message(session: u32, title: &str, message: &str) -> Result<(), String> {
+        let title = HSTRING::from(title);
+        let message = HSTRING::from(message);
+        let mut response = MESSAGEBOX_RESULT::default();
+        unsafe {
+            WTSSendMessageW(
+                WTS_CURRENT_SERVER_HANDLE,
+                session,
+                &title,
+                (title.len() * 2) as u32,
+                &message,
+                (message.len() * 2) as u32,
+                MB_OK | MB_ICONWARNING,
+                300,
+                &mut response,
+                false,
+            )
+        }
+        .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(windows)]
+pub use imp::{list_sessions, send_message};
+
+#[cfg(not(windows))]
+pub fn list_sessions() -> Vec<UserSession> {
+    Vec::new()
+}
+
+#[cfg(not(windows))]
+pub fn send_message(_session: u32, _title: &str, _message: &str) -> Result<(), String> {
+    Err("Session messages are only supported on Windows".into())
+}
+
+// 返回 Err 表示用户拒绝或未在时限内确认
+pub fn check(app: &AppHandle, options: &OtherUsersOptions) -> Result<(), String> {
+    if options.policy == OtherUsersPolicy::Ignore {
+        return Ok(());
+    }
+    let others: Vec<UserSession> = list_sessions().into_iter().filter(|s| !s.current).collect();
+    if others.is_empty() {
+        return Ok(());
+    }
+    tracing::info!(users = ?others.iter().map(|s| &s.user).collect::<Vec<_>>(), "other users logged in");
+
+    if let Some(message) = &options.message {
+        for session in &others {
+            if let Err(e) = send_message(session.id, "AutoShutdown", message) {
+                tracing::warn!(session = session.id, error = %e, "failed to send session message");
+            }
+        }
+    }
+
+    if options.policy == OtherUsersPolicy::Warn {
+        let _ = app.emit(
+            "other-users-logged-in",
+            OtherUsersLoggedIn {
+                sessions: &others,
+                confirmation_id: None,
+            },
+        );
+        return Ok(());
+    }
+
+    let confirmations = app.state::<Confirmations>();
+    let id = confirmations.request();
+    let _ = app.emit(
+        "other-users-logged-in",
+        OtherUsersLoggedIn {
+            sessions: &others,
+            confirmation_id: Some(&id),
+        },
+    );
+    match confirmations.wait(&id, Duration::from_secs(options.hold_timeout_secs)) {
+        Some(true) => Ok(()),
+        Some(false) => Err("Cancelled because other users are logged in".into()),
+        None => Err("Other logged-in users were not confirmed in time".into()),
+    }
+}
+
+#[tauri::command]
+pub fn list_user_sessions() -> Vec<UserSession> {
+    list_sessions()
+}
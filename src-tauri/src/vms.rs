@@ -0,0 +1,215 @@
+use crate::cmd;
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Hypervisor {
+    HyperV,
+    VirtualBox,
+    Vmware,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct RunningVm {
+    pub hypervisor: Hypervisor,
+    // Hyper-V 和 VirtualBox 为 GUID，VMware 为 .vmx 路径
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VmStopMode {
+    // 保存状态，主机恢复后可以继续运行
+    #[default]
+    Save,
+    // 通知客户机正常关机
+    Shutdown,
+}
+
+// 主机睡眠或关机前处理正在运行的虚拟机
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct VmShutdownOptions {
+    pub enabled: bool,
+    pub mode: VmStopMode,
+    pub timeout_secs: u64,
+    // 超时后仍有虚拟机运行时取消本次操作
+    pub abort_on_failure: bool,
+}
+
+impl Default for VmShutdownOptions {
+    fn default() -> Self {
+        VmShutdownOptions {
+            enabled: false,
+            mode: VmStopMode::default(),
+            timeout_secs: 120,
+            abort_on_failure: false,
+        }
+    }
+}
+
+// 安装目录通常不在 PATH 中，先找默认安装位置
+fn tool(env_dir: &str, default_dir: &str, exe: &str) -> String {
+    std::env::var(env_dir)
+        .ok()
+        .map(|dir| std::path::Path::new(&dir).join(default_dir).join(exe))
+        .filter(|path| path.is_file())
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|| exe.to_string())
+}
+
+fn vboxmanage() -> String {
+    // 安装程序会设置 VBOX_MSI_INSTALL_PATH
+    match std::env::var("VBOX_MSI_INSTALL_PATH") {
+        Ok(dir) => std::path::Path::new(&dir).join("VBoxManage.exe").to_string_lossy().to_string(),
+        Err(_) => tool("ProgramFiles", r"Oracle\VirtualBox", "VBoxManage"),
+    }
+}
+
+fn vmrun() -> String {
+    tool("ProgramFiles(x86)", r"VMware\VMware Workstation", "vmrun")
+}
+
+// 未安装对应的虚拟化软件时视为没有虚拟机运行
+fn hyperv_running() -> Vec<RunningVm> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Vm {
+        name: String,
+        element_name: String,
+    }
+
+    const SCRIPT: &str = r#"@(Get-CimInstance -Namespace root\virtualization\v2 -ClassName Msvm_ComputerSystem -Filter "Caption = 'Virtual Machine' AND EnabledState = 2" | Select-Object Name, ElementName) | ConvertTo-Json -Compress"#;
+
+    if !cfg!(windows) {
+        return Vec::new();
+    }
+    let Ok(out) = cmd::output("powershell", &["-NoProfile", "-NonInteractive", "-Command", SCRIPT]) else {
+        return Vec::new();
+    };
+    let out = out.trim();
+    // 只有一项时 ConvertTo-Json 输出对象而不是数组
+    let vms: Vec<Vm> = if out.starts_with('[') {
+        serde_json::from_str(out).unwrap_or_default()
+    } else {
+        serde_json::from_str(out).map(|vm| vec![vm]).unwrap_or_default()
+    };
+    vms.into_iter()
+        .map(|vm| RunningVm {
+            hypervisor: Hypervisor::HyperV,
+            id: vm.name,
+            name: vm.element_name,
+        })
+        .collect()
+}
+
+// VBoxManage list runningvms 每行形如 "名称" {uuid}
+fn virtualbox_running() -> Vec<RunningVm> {
+    let Ok(out) = cmd::output(&vboxmanage(), &["list", "runningvms"]) else {
+        return Vec::new();
+    };
+    out.lines()
+        .filter_map(|line| {
+            let (name, id) = line.rsplit_once(' ')?;
+            Some(RunningVm {
+                hypervisor: Hypervisor::VirtualBox,
+                id: id.trim_matches(['{', '}']).to_string(),
+                name: name.trim_matches('"').to_string(),
+            })
+        })
+        .collect()
+}
+
+// vmrun list 第一行为 "Total running VMs: N"，之后每行一个 .vmx 路径
+fn vmware_running() -> Vec<RunningVm> {
+    let Ok(out) = cmd::output(&vmrun(), &["list"]) else {
+        return Vec::new();
+    };
+    out.lines()
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|path| RunningVm {
+            hypervisor: Hypervisor::Vmware,
+            name: std::path::Path::new(path)
+                .file_stem()
+                .map_or(path.to_string(), |s| s.to_string_lossy().to_string()),
+            id: path.to_string(),
+        })
+        .collect()
+}
+
+pub fn running() -> Vec<RunningVm> {
+    let mut vms = hyperv_running();
+    vms.extend(virtualbox_running());
+    vms.extend(vmware_running());
+    vms
+}
+
+// Save-VM、vmrun suspend 等会等待完成；VirtualBox 的 ACPI 关机只发送信号，由调用方等待
+fn stop(vm: &RunningVm, mode: VmStopMode) -> Result<(), String> {
+    tracing::info!(hypervisor = ?vm.hypervisor, name = %vm.name, ?mode, "stopping virtual machine");
+    match vm.hypervisor {
+        Hypervisor::HyperV => {
+            let verb = match mode {
+                VmStopMode::Save => "Save-VM",
+                VmStopMode::Shutdown => "Stop-VM",
+            };
+            let script = format!("$ErrorActionPreference = 'Stop'; Get-VM -Id '{}' | {}", vm.id, verb);
+            cmd::output("powershell", &["-NoProfile", "-NonInteractive", "-Command", &script]).map(|_| ())
+        }
+        Hypervisor::VirtualBox => {
+            let verb = match mode {
+                VmStopMode::Save => "savestate",
+                VmStopMode::Shutdown => "acpipowerbutton",
+            };
+            cmd::output(&vboxmanage(), &["controlvm", &vm.id, verb]).map(|_| ())
+        }
+        Hypervisor::Vmware => {
+            let args: &[&str] = match mode {
+                VmStopMode::Save => &["suspend", &vm.id],
+                VmStopMode::Shutdown => &["stop", &vm.id, "soft"],
+            };
+            cmd::output(&vmrun(), args).map(|_| ())
+        }
+    }
+}
+
+// 依次处理所有虚拟机并等待它们停止，需要取消操作时返回 Err，其余返回仍在运行的虚拟机名称
+pub fn stop_all(options: &VmShutdownOptions) -> Result<Vec<String>, String> {
+    if !options.enabled {
+        return Ok(Vec::new());
+    }
+    let vms = running();
+    for vm in &vms {
+        if let Err(e) = stop(vm, options.mode) {
+            tracing::warn!(name = %vm.name, error = %e, "failed to stop virtual machine");
+        }
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(options.timeout_secs);
+    let still_running = loop {
+        let now_running = running();
+        let remaining: Vec<String> = vms
+            .iter()
+            .filter(|vm| now_running.iter().any(|r| r.hypervisor == vm.hypervisor && r.id == vm.id))
+            .map(|vm| vm.name.clone())
+            .collect();
+        if remaining.is_empty() || Instant::now() >= deadline {
+            break remaining;
+        }
+        thread::sleep(Duration::from_secs(2));
+    };
+    if !still_running.is_empty() && options.abort_on_failure {
+        return Err(format!("Virtual machines are still running: {}", still_running.join(", ")));
+    }
+    Ok(still_running)
+}
+
+#[tauri::command(async)]
+pub fn list_running_vms() -> Vec<RunningVm> {
+    running()
+}
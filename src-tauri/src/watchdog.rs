@@ -0,0 +1,142 @@
+use crate::executor;
+use crate::notify::{self, Notice};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+// 重启间隔翻倍的上限
+const MAX_BACKOFF: Duration = Duration::from_secs(10 * 60);
+// 重启后持续运行超过该时长，重启计数清零
+const STABLE_AFTER: Duration = Duration::from_secs(30 * 60);
+// 本应用执行操作后这段时间内的退出不算意外
+const OWN_CLOSE_WINDOW: Duration = Duration::from_secs(60);
+
+pub fn default_backoff_secs() -> u64 {
+    10
+}
+
+pub fn default_max_restarts() -> u32 {
+    5
+}
+
+// 看护的程序，name 用于判断是否在运行，command 和 args 用于重新启动
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WatchdogTarget {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    // 第一次重启前的等待，之后每次翻倍
+    #[serde(default = "default_backoff_secs")]
+    pub backoff_secs: u64,
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+}
+
+impl WatchdogTarget {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Process name must not be empty".into());
+        }
+        if self.command.trim().is_empty() {
+            return Err("Program to restart must not be empty".into());
+        }
+        if self.max_restarts == 0 {
+            return Err("Allow at least one restart".into());
+        }
+        Ok(())
+    }
+
+    fn backoff(&self, restarts: u32) -> Duration {
+        let secs = self.backoff_secs.max(1).saturating_mul(1 << restarts.min(16));
+        Duration::from_secs(secs).min(MAX_BACKOFF)
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct WatchdogRestarted<'a> {
+    rule_id: &'a str,
+    name: &'a str,
+    restarts: u32,
+    error: Option<String>,
+}
+
+// 每条看护规则在两次检查之间的状态
+#[derive(Default)]
+pub struct WatchdogState {
+    seen: bool,
+    restarts: u32,
+    restart_at: Option<Instant>,
+    last_restart: Option<Instant>,
+    gave_up: bool,
+}
+
+impl WatchdogState {
+    // 进程已退出、正在等待重启
+    pub fn waiting(&self) -> bool {
+        self.restart_at.is_some()
+    }
+
+    // 进程意外退出后按退避间隔重新启动；重启次数用尽时返回 true，由规则执行其操作
+    pub fn check(&mut self, app: &AppHandle, rule_id: &str, target: &WatchdogTarget, running: bool) -> bool {
+        if running {
+            self.seen = true;
+            self.restart_at = None;
+            // 放弃后用户手动启动的，重新开始看护
+            if self.gave_up {
+                self.gave_up = false;
+                self.restarts = 0;
+            }
+            if self.last_restart.is_some_and(|t| t.elapsed() >= STABLE_AFTER) {
+                self.restarts = 0;
+                self.last_restart = None;
+            }
+            return false;
+        }
+        // 由本应用关闭的（结束进程、关机前关闭程序）不代为启动，直到它再次运行
+        if executor::acted_within(OWN_CLOSE_WINDOW) {
+            self.seen = false;
+            self.restart_at = None;
+            return false;
+        }
+        // 启动后还没见过进程运行时不代为启动
+        if !self.seen || self.gave_up {
+            return false;
+        }
+        if self.restarts >= target.max_restarts {
+            tracing::warn!(rule = rule_id, name = %target.name, restarts = self.restarts, "watchdog gave up");
+            self.gave_up = true;
+            return true;
+        }
+        let restart_at = *self.restart_at.get_or_insert_with(|| Instant::now() + target.backoff(self.restarts));
+        if Instant::now() < restart_at {
+            return false;
+        }
+
+        self.restart_at = None;
+        self.restarts += 1;
+        self.last_restart = Some(Instant::now());
+        let result = Command::new(&target.command).args(&target.args).spawn();
+        let error = result.as_ref().err().map(|e| format!("Failed to start {}: {}", target.command, e));
+        tracing::info!(rule = rule_id, name = %target.name, restarts = self.restarts, ?error, "watchdog restart");
+        let body = match &error {
+            None => format!(
+                "{} exited unexpectedly and was restarted ({}/{})",
+                target.name, self.restarts, target.max_restarts
+            ),
+            Some(e) => e.clone(),
+        };
+        notify::dispatch(app, Notice::new("AutoShutdown", body));
+        let _ = app.emit(
+            "watchdog-restarted",
+            WatchdogRestarted {
+                rule_id,
+                name: &target.name,
+                restarts: self.restarts,
+                error,
+            },
+        );
+        false
+    }
+}
@@ -0,0 +1,88 @@
+use crate::events::Envelope;
+use crate::settings::SettingsStore;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Webhook {
+    // 保存设置时生成，导入配置时据此找回本机已有的地址
+    #[serde(default)]
+    pub id: String,
+    pub url: String,
+    // 订阅的事件名，为空表示全部
+    #[serde(default)]
+    pub events: Vec<String>,
+    // JSON 模板，可使用 {{event}} {{message}} {{timestamp}} {{payload}}；为空时直接发送事件 JSON
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default = "crate::schedule::default_true")]
+    pub enabled: bool,
+}
+
+// 地址中通常带有令牌，不写入日志
+impl fmt::Debug for Webhook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Webhook")
+            .field("id", &self.id)
+            .field("events", &self.events)
+            .field("enabled", &self.enabled)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Webhook {
+    fn wants(&self, event: &str) -> bool {
+        self.enabled && (self.events.is_empty() || self.events.iter().any(|e| e == event))
+    }
+}
+
+// 字符串占位符按 JSON 转义后填入引号内，{{payload}} 填入完整 JSON
+fn render(template: &str, envelope: &Envelope) -> String {
+    let escape = |s: &str| {
+        let quoted = serde_json::to_string(s).unwrap_or_default();
+        quoted[1..quoted.len() - 1].to_string()
+    };
+    template
+        .replace("{{event}}", &escape(envelope.event.name()))
+        .replace("{{message}}", &escape(&envelope.message))
+        .replace("{{timestamp}}", &escape(&envelope.timestamp.to_rfc3339()))
+        .replace("{{payload}}", &serde_json::to_string(envelope).unwrap_or_default())
+}
+
+pub fn send(webhook: &Webhook, envelope: &Envelope) -> Result<(), String> {
+    let body = match &webhook.template {
+        Some(template) if !template.trim().is_empty() => render(template, envelope),
+        _ => serde_json::to_string(envelope).map_err(|e| e.to_string())?,
+    };
+    ureq::post(&webhook.url)
+        .timeout(Duration::from_secs(10))
+        .set("Content-Type", "application/json")
+        .send_string(&body)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+// 在后台线程发送，避免网络请求阻塞计时器和执行流程
+pub fn dispatch(app: &AppHandle, envelope: &Envelope) {
+    let webhooks: Vec<Webhook> = app
+        .state::<SettingsStore>()
+        .get()
+        .webhooks
+        .into_iter()
+        .filter(|w| w.wants(envelope.event.name()))
+        .collect();
+    if webhooks.is_empty() {
+        return;
+    }
+    let envelope = envelope.clone();
+    thread::spawn(move || {
+        for webhook in webhooks {
+            if let Err(e) = send(&webhook, &envelope) {
+                tracing::warn!(?webhook, error = %e, "webhook failed");
+            }
+        }
+    });
+}
@@ -0,0 +1,93 @@
+use crate::error::AutoShutdownError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+static DEBOUNCE_FLAG: AtomicBool = AtomicBool::new(false);
+// 每个顶层窗口最近一次通知的标题，相同标题重复的 NAMECHANGE 不再发出事件
+static LAST_TITLES: LazyLock<Mutex<HashMap<isize, String>>> = LazyLock::new(Default::default);
+
+// 对应窗口钩子的 EVENT_OBJECT_CREATE/DESTROY/SHOW/HIDE/NAMECHANGE
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowEventKind {
+    Create,
+    Destroy,
+    Show,
+    Hide,
+    NameChange,
+}
+
+#[derive(Serialize, Clone)]
+struct TitleChanged {
+    hwnd: isize,
+    pid: u32,
+    title: String,
+}
+
+fn title_changed(app: &AppHandle, hwnd: isize, pid: u32, title: String) {
+    let mut titles = LAST_TITLES.lock().unwrap();
+    if titles.get(&hwnd) == Some(&title) {
+        return;
+    }
+    titles.insert(hwnd, title.clone());
+    drop(titles);
+    let _ = app.emit("title-changed", TitleChanged { hwnd, pid, title });
+}
+
+// 防抖：避免短时间内多次触发
+fn window_changed(app: &AppHandle, kind: WindowEventKind) {
+    if DEBOUNCE_FLAG.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+        tracing::debug!(?kind, "window changed");
+        let _ = app.emit("window-changed", ());
+
+        // 500ms 后重置标志
+        thread::spawn(|| {
+            thread::sleep(Duration::from_millis(500));
+            DEBOUNCE_FLAG.store(false, Ordering::SeqCst);
+        });
+    }
+}
+
+// 窗口钩子和注入的事件共用的处理流程；window 为标题变化的顶层窗口的 (pid, 标题)
+pub fn handle(app: &AppHandle, kind: WindowEventKind, hwnd: isize, window: Option<(u32, String)>) {
+    // 标题变化只通知标题触发规则和监听方，不刷新前端的窗口列表
+    crate::titles::notify_changed();
+    match kind {
+        WindowEventKind::NameChange => {
+            if let Some((pid, title)) = window {
+                title_changed(app, hwnd, pid, title);
+            }
+            return;
+        }
+        WindowEventKind::Destroy => {
+            LAST_TITLES.lock().unwrap().remove(&hwnd);
+        }
+        _ => {}
+    }
+    window_changed(app, kind);
+}
+
+// 仅调试构建可用：合成的窗口事件走与钩子相同的去重和防抖，便于集成测试及在没有 Windows 桌面时开发前端
+#[tauri::command]
+pub fn inject_window_event(
+    app: AppHandle,
+    kind: WindowEventKind,
+    pid: u32,
+    title: String,
+) -> Result<(), AutoShutdownError> {
+    if !cfg!(debug_assertions) {
+        return Err(AutoShutdownError::Unsupported(
+            "Window event injection is only available in debug builds".into(),
+        ));
+    }
+    // 合成事件没有真实窗口，用 pid 的相反数作为句柄，不会与真实句柄冲突
+    let hwnd = -(pid as isize);
+    tracing::debug!(?kind, pid, title, "injected window event");
+    handle(&app, kind, hwnd, Some((pid, title)));
+    Ok(())
+}
@@ -0,0 +1,89 @@
+#[cfg(windows)]
+use crate::cmd;
+use crate::services::{self, ServiceState};
+use crate::timers;
+use serde::Serialize;
+use sysinfo::System;
+
+// 下载和安装更新时占用 CPU 的进程
+const UPDATE_WORKERS: &[&str] = &["TiWorker", "TrustedInstaller", "MoUsoCoreWorker", "wuauclt", "UsoClient"];
+// 空闲时这些进程也会偶尔唤醒，低于该占用视为没有在更新
+const CPU_THRESHOLD: f32 = 2.0;
+
+// Windows Update 服务在运行，且更新相关进程有明显的 CPU 占用
+pub fn installing(sys: &System) -> bool {
+    let busy = sys.processes().values().any(|p| {
+        let name = p.name().to_string_lossy();
+        UPDATE_WORKERS.iter().any(|w| timers::name_matches(&name, w)) && p.cpu_usage() > CPU_THRESHOLD
+    });
+    busy && services::query_state("wuauserv").is_ok_and(|s| s == ServiceState::Running)
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingRebootReason {
+    // 组件更新（CBS）完成后需要重启
+    ComponentServicing,
+    WindowsUpdate,
+    // 有文件在重启时替换，常见于安装程序
+    FileRename,
+    ComputerRename,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PendingReboot {
+    pub pending: bool,
+    pub reasons: Vec<PendingRebootReason>,
+}
+
+#[cfg(windows)]
+fn pending_reasons() -> Vec<PendingRebootReason> {
+    const CBS: &str = r"HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\Component Based Servicing\RebootPending";
+    const WINDOWS_UPDATE: &str = r"HKLM\SOFTWARE\Microsoft\Windows\CurrentVersion\WindowsUpdate\Auto Update\RebootRequired";
+    const SESSION_MANAGER: &str = r"HKLM\SYSTEM\CurrentControlSet\Control\Session Manager";
+    const ACTIVE_NAME: &str = r"HKLM\SYSTEM\CurrentControlSet\Control\ComputerName\ActiveComputerName";
+    const PENDING_NAME: &str = r"HKLM\SYSTEM\CurrentControlSet\Control\ComputerName\ComputerName";
+
+    let key_exists = |key: &str| cmd::output("reg", &["query", key]).is_ok();
+    let value = |key: &str, name: &str| {
+        let out = cmd::output("reg", &["query", key, "/v", name]).ok()?;
+        let line = out.lines().find(|l| l.contains("REG_"))?;
+        line.split_once("    REG_").and_then(|(_, rest)| rest.split_once("    ")).map(|(_, data)| data.trim().to_string())
+    };
+
+    let mut reasons = Vec::new();
+    if key_exists(CBS) {
+        reasons.push(PendingRebootReason::ComponentServicing);
+    }
+    if key_exists(WINDOWS_UPDATE) {
+        reasons.push(PendingRebootReason::WindowsUpdate);
+    }
+    if value(SESSION_MANAGER, "PendingFileRenameOperations").is_some() {
+        reasons.push(PendingRebootReason::FileRename);
+    }
+    // 修改计算机名后，重启前两处的名称不一致
+    let active = value(ACTIVE_NAME, "ComputerName");
+    if active.is_some() && active != value(PENDING_NAME, "ComputerName") {
+        reasons.push(PendingRebootReason::ComputerRename);
+    }
+    reasons
+}
+
+#[cfg(not(windows))]
+fn pending_reasons() -> Vec<PendingRebootReason> {
+    Vec::new()
+}
+
+// 用于决定今晚改为重启还是关机
+pub fn pending_reboot() -> PendingReboot {
+    let reasons = pending_reasons();
+    PendingReboot {
+        pending: !reasons.is_empty(),
+        reasons,
+    }
+}
+
+#[tauri::command(async)]
+pub fn get_pending_reboot_state() -> PendingReboot {
+    pending_reboot()
+}
@@ -0,0 +1,61 @@
+use crate::error::AutoShutdownError;
+use crate::machines::MachineStore;
+use std::net::UdpSocket;
+use tauri::State;
+
+const DEFAULT_BROADCAST: &str = "255.255.255.255";
+const WOL_PORT: u16 = 9;
+
+// 接受 AA:BB:CC:DD:EE:FF、AA-BB-...、AABBCCDDEEFF 等写法
+fn parse_mac(mac: &str) -> Result<[u8; 6], String> {
+    let hex: String = mac.chars().filter(|c| c.is_ascii_hexdigit()).collect();
+    let invalid = || format!("Invalid MAC address: {}", mac);
+    if hex.len() != 12 || mac.chars().any(|c| !c.is_ascii_hexdigit() && !matches!(c, ':' | '-' | '.' | ' ')) {
+        return Err(invalid());
+    }
+    let mut bytes = [0u8; 6];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+    }
+    Ok(bytes)
+}
+
+// 魔术包：6 个 0xFF 后接 16 次 MAC 地址
+fn magic_packet(mac: [u8; 6]) -> Vec<u8> {
+    let mut packet = vec![0xFF; 6];
+    for _ in 0..16 {
+        packet.extend_from_slice(&mac);
+    }
+    packet
+}
+
+pub fn wake(mac: &str, broadcast: Option<&str>) -> Result<(), String> {
+    let packet = magic_packet(parse_mac(mac)?);
+    let target = broadcast.filter(|b| !b.trim().is_empty()).unwrap_or(DEFAULT_BROADCAST);
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| e.to_string())?;
+    socket.set_broadcast(true).map_err(|e| e.to_string())?;
+    socket
+        .send_to(&packet, (target, WOL_PORT))
+        .map_err(|e| format!("Failed to send wake packet to {}: {}", target, e))?;
+    tracing::info!(mac, target, "wake-on-lan packet sent");
+    Ok(())
+}
+
+#[tauri::command]
+pub fn send_wol(mac: String, broadcast_addr: Option<String>) -> Result<(), AutoShutdownError> {
+    wake(&mac, broadcast_addr.as_deref()).map_err(Into::into)
+}
+
+#[tauri::command]
+pub fn wake_machine(store: State<'_, MachineStore>, id: String) -> Result<(), AutoShutdownError> {
+    let machine = store
+        .all()
+        .into_iter()
+        .find(|m| m.id == id)
+        .ok_or_else(|| AutoShutdownError::NotFound("Machine not found".into()))?;
+    let mac = machine
+        .mac
+        .as_deref()
+        .ok_or_else(|| AutoShutdownError::InvalidInput("Machine has no MAC address".into()))?;
+    wake(mac, machine.broadcast.as_deref()).map_err(Into::into)
+}
@@ -0,0 +1,359 @@
+use crate::api::{self, Request};
+use base64::Engine;
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Listener, Manager};
+
+// 转发给 WebSocket 客户端的事件，与前端收到的相同；新增前端事件时需要同步加入
+const FORWARDED_EVENTS: &[&str] = &[
+    "action-deferred",
+    "action-recorded",
+    "countdown-interrupted",
+    "countdown-tick",
+    "fallback-attempt",
+    "hook-finished",
+    "hook-output",
+    "idle-grace",
+    "media-ended",
+    "media-state",
+    "notification",
+    "other-users-logged-in",
+    "possible-unsaved-work",
+    "process-exited",
+    "process-started",
+    "profile-changed",
+    "rule-conflicts",
+    "rules-changed",
+    "sequence-finished",
+    "sequence-progress",
+    "settings-changed",
+    "settings-reloaded",
+    "soft-close-finished",
+    "system-resumed",
+    "system-suspending",
+    "timer-fired",
+    "timers-tick",
+    "title-changed",
+    "warning",
+    "watchdog-restarted",
+    "watch-progress",
+    "window-changed",
+    "would-have-executed",
+];
+
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+// 每个客户端最多积压的事件数，超出时断开该客户端
+const QUEUE_LEN: usize = 256;
+// WebSocket 连接不占用 HTTP 接口的连接名额，单独限制
+const MAX_CLIENTS: usize = 8;
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+// 没有事件时隔多久检查一次客户端是否已关闭
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+// 客户端只需要发送控制帧，更大的帧直接断开
+const MAX_CLIENT_FRAME: u64 = 4 * 1024;
+
+const OP_TEXT: u8 = 0x1;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+#[derive(Default)]
+pub struct EventStream {
+    clients: Mutex<Vec<SyncSender<String>>>,
+    connected: AtomicUsize,
+}
+
+// 连接结束时释放名额
+struct Subscription<'a> {
+    stream: &'a EventStream,
+    messages: Receiver<String>,
+}
+
+impl Drop for Subscription<'_> {
+    fn drop(&mut self) {
+        self.stream.connected.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl EventStream {
+    fn subscribe(&self) -> Option<Subscription<'_>> {
+        if self.connected.fetch_add(1, Ordering::SeqCst) >= MAX_CLIENTS {
+            self.connected.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        let (tx, rx) = mpsc::sync_channel(QUEUE_LEN);
+        self.clients.lock().unwrap().push(tx);
+        Some(Subscription {
+            stream: self,
+            messages: rx,
+        })
+    }
+
+    // 客户端断开后其接收端被丢弃，发送失败时一并移除；积压过多的客户端也移除，连接随之关闭
+    fn broadcast(&self, message: String) {
+        self.clients.lock().unwrap().retain(|tx| match tx.try_send(message.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                tracing::info!("websocket client is not keeping up, disconnecting");
+                false
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+    }
+}
+
+pub fn start_forwarding(app: AppHandle) {
+    for name in FORWARDED_EVENTS {
+        let handle = app.clone();
+        app.listen_any(*name, move |event| {
+            let stream = handle.state::<EventStream>();
+            if stream.clients.lock().unwrap().is_empty() {
+                return;
+            }
+            let payload: Value = serde_json::from_str(event.payload()).unwrap_or(Value::Null);
+            stream.broadcast(json!({ "event": name, "payload": payload }).to_string());
+        });
+    }
+}
+
+pub fn is_upgrade(request: &Request) -> bool {
+    request
+        .header("Upgrade")
+        .is_some_and(|v| v.eq_ignore_ascii_case("websocket"))
+}
+
+fn accept_key(key: &str) -> String {
+    base64::engine::general_purpose::STANDARD.encode(sha1(format!("{}{}", key, HANDSHAKE_GUID).as_bytes()))
+}
+
+// 完成握手后在当前线程上持续推送事件，另起线程读取客户端的控制帧，直到任一方断开
+pub fn serve(app: &AppHandle, mut stream: TcpStream, request: &Request) -> Result<(), String> {
+    let Some(key) = request.header("Sec-WebSocket-Key") else {
+        return Err("Missing Sec-WebSocket-Key".into());
+    };
+    let events = app.state::<EventStream>();
+    let Some(subscription) = events.subscribe() else {
+        return api::write_response(stream, 503, &json!({ "error": "Too many WebSocket clients" }));
+    };
+    let head = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    );
+    stream.write_all(head.as_bytes()).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT)).map_err(|e| e.to_string())?;
+    // 客户端可以长时间不发送任何帧
+    stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+    tracing::info!(peer = ?stream.peer_addr().ok(), "websocket client connected");
+
+    let writer = Arc::new(Mutex::new(stream.try_clone().map_err(|e| e.to_string())?));
+    let closed = Arc::new(AtomicBool::new(false));
+    let reader = {
+        let stream = stream.try_clone().map_err(|e| e.to_string())?;
+        let (writer, closed) = (writer.clone(), closed.clone());
+        thread::spawn(move || {
+            read_frames(stream, &writer);
+            closed.store(true, Ordering::SeqCst);
+        })
+    };
+
+    loop {
+        match subscription.messages.recv_timeout(POLL_INTERVAL) {
+            Ok(message) => {
+                if writer.lock().unwrap().write_all(&frame(OP_TEXT, message.as_bytes())).is_err() {
+                    break;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) if !closed.load(Ordering::SeqCst) => {}
+            Err(_) => break,
+        }
+    }
+    // 关闭连接让读取线程退出
+    let _ = stream.shutdown(Shutdown::Both);
+    let _ = reader.join();
+    tracing::info!("websocket client disconnected");
+    Ok(())
+}
+
+// 回应 ping；收到 close 时回送 close 后结束，连接出错或帧不合法时直接结束
+fn read_frames(mut stream: TcpStream, writer: &Mutex<TcpStream>) {
+    while let Ok((opcode, payload)) = read_frame(&mut stream) {
+        let reply = match opcode {
+            OP_PING => frame(OP_PONG, &payload),
+            // 只回送状态码
+            OP_CLOSE => frame(OP_CLOSE, &payload[..payload.len().min(2)]),
+            // 不处理客户端发来的数据帧
+            _ => continue,
+        };
+        if writer.lock().unwrap().write_all(&reply).is_err() || opcode == OP_CLOSE {
+            return;
+        }
+    }
+}
+
+// 客户端发出的帧必须加掩码
+fn read_frame(reader: &mut impl Read) -> Result<(u8, Vec<u8>), String> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head).map_err(|e| e.to_string())?;
+    let opcode = head[0] & 0x0F;
+    if head[1] & 0x80 == 0 {
+        return Err("Unmasked client frame".into());
+    }
+    let length = match head[1] & 0x7F {
+        126 => {
+            let mut bytes = [0u8; 2];
+            reader.read_exact(&mut bytes).map_err(|e| e.to_string())?;
+            u16::from_be_bytes(bytes) as u64
+        }
+        127 => {
+            let mut bytes = [0u8; 8];
+            reader.read_exact(&mut bytes).map_err(|e| e.to_string())?;
+            u64::from_be_bytes(bytes)
+        }
+        len => len as u64,
+    };
+    if length > MAX_CLIENT_FRAME {
+        return Err("Client frame too large".into());
+    }
+    let mut mask = [0u8; 4];
+    reader.read_exact(&mut mask).map_err(|e| e.to_string())?;
+    let mut payload = vec![0u8; length as usize];
+    reader.read_exact(&mut payload).map_err(|e| e.to_string())?;
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+    Ok((opcode, payload))
+}
+
+// 服务端发出的帧不加掩码，也不分片
+fn frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+// 握手只需要对短字符串做一次 SHA-1，不为此引入依赖
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (i, state) in h.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&state.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn masked(opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x37, 0xFA, 0x21, 0x3D];
+        let mut frame = vec![0x80 | opcode, 0x80 | payload.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        frame
+    }
+
+    #[test]
+    fn reads_masked_control_frames() {
+        let mut input = masked(OP_PING, b"hi");
+        input.extend(masked(OP_CLOSE, &[0x03, 0xE8]));
+        let mut reader = input.as_slice();
+        assert_eq!(read_frame(&mut reader).unwrap(), (OP_PING, b"hi".to_vec()));
+        assert_eq!(read_frame(&mut reader).unwrap(), (OP_CLOSE, vec![0x03, 0xE8]));
+        assert!(read_frame(&mut reader).is_err());
+    }
+
+    #[test]
+    fn rejects_unmasked_and_oversized_frames() {
+        assert!(read_frame(&mut [0x89u8, 0x00].as_slice()).is_err());
+        let mut oversized = vec![0x82, 0x80 | 127];
+        oversized.extend_from_slice(&u64::MAX.to_be_bytes());
+        assert!(read_frame(&mut oversized.as_slice()).is_err());
+    }
+
+    #[test]
+    fn frames_use_extended_lengths() {
+        assert_eq!(frame(OP_PONG, b"hi"), vec![0x8A, 2, b'h', b'i']);
+        let long = frame(OP_TEXT, &[b'a'; 300]);
+        assert_eq!(&long[..4], &[0x81, 126, 0x01, 0x2C]);
+        assert_eq!(long.len(), 304);
+    }
+
+    fn hex(digest: [u8; 20]) -> String {
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn sha1_matches_known_digests() {
+        assert_eq!(hex(sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(hex(sha1(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        // 跨越两个分块
+        assert_eq!(
+            hex(sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+    }
+
+    #[test]
+    fn accept_key_matches_rfc_example() {
+        // RFC 6455 §1.3
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+}
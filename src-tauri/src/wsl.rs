@@ -0,0 +1,33 @@
+use crate::cmd;
+
+// 旧版本的 wsl.exe 输出 UTF-16，设置 WSL_UTF8 后输出 UTF-8，两种情况都去掉空字符
+fn wsl(args: &[&str]) -> Result<String, String> {
+    let output = cmd::hidden("wsl")
+        .args(args)
+        .env("WSL_UTF8", "1")
+        .output()
+        .map_err(|e| format!("Failed to run wsl: {}", e))?;
+    let text = String::from_utf8_lossy(&output.stdout).replace('\0', "");
+    if output.status.success() {
+        Ok(text)
+    } else {
+        Err(text.trim().to_string())
+    }
+}
+
+// 没有运行的发行版或未安装 WSL 时返回空
+pub fn running_distros() -> Vec<String> {
+    wsl(&["--list", "--running", "--quiet"])
+        .map(|out| out.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect())
+        .unwrap_or_default()
+}
+
+// 关机前正常卸载发行版中的 Linux 文件系统，避免 ext4.vhdx 损坏
+pub fn shutdown() -> Result<(), String> {
+    let distros = running_distros();
+    if distros.is_empty() {
+        return Ok(());
+    }
+    tracing::info!(?distros, "shutting down WSL");
+    wsl(&["--shutdown"]).map(|_| ())
+}
@@ -7,6 +7,64 @@ interface ProcessInfo {
   name: string;
   title: string;
   icon: string | null;
+  elevated: boolean;
+}
+
+interface TitleChanged {
+  hwnd: number;
+  pid: number;
+  title: string;
+}
+
+// 与后端 actions::Action 对应，电源操作序列化为字符串
+type PowerAction = 'shutdown' | 'full_shutdown' | 'restart' | 'sleep' | 'hibernate' | 'lock' | 'log_off' | 'display_off';
+type Action =
+  | PowerAction
+  | { kill_processes: { names: string[]; tree?: boolean } }
+  | { run_script: { script: { path: string; args?: string[]; timeout_secs?: number } } }
+  | { set_power_plan: { guid: string } }
+  | { set_brightness: { percent: number } }
+  | { fallback: { steps: { action: PowerAction; wake_within_secs?: number }[] } };
+
+interface CountdownStatus {
+  action: Action;
+  mode: 'in_app' | 'os';
+  deadline: string;
+  remaining_secs: number;
+}
+
+// 与后端 power::PowerCapabilities 对应
+interface PowerCapabilities {
+  sleep: boolean;
+  hibernate: boolean;
+  hybrid_sleep: boolean;
+  fast_startup: boolean;
+  modern_standby: boolean;
+}
+
+// 与后端 winupdate::PendingReboot 对应
+interface PendingReboot {
+  pending: boolean;
+  reasons: ('component_servicing' | 'windows_update' | 'file_rename' | 'computer_rename')[];
+}
+
+// 与后端 uptime::UptimeInfo 对应
+interface UptimeInfo {
+  boot_time: string;
+  uptime_secs: number;
+  last_wake: { sleep_time: string | null; wake_time: string | null; wake_source: string | null } | null;
+}
+
+// 与后端 schedule_text::ParsedSchedule 对应
+interface ParsedSchedule {
+  trigger: { kind: 'after'; seconds: number } | { kind: 'at'; time: string };
+  time: string;
+}
+
+// 与后端 error::AutoShutdownError 对应
+interface AppError {
+  kind: 'needs_admin' | 'unsupported' | 'process_gone' | 'not_found' | 'invalid_input' | 'failed';
+  message: string;
 }
 
 // 翻译字典
@@ -32,14 +90,39 @@ const translations = {
     sysTimerDesc: "设置系统关机、重启或休眠的定时任务",
     action: "执行操作",
     shutdown: "关机",
+    full_shutdown: "完全关机（跳过快速启动）",
     restart: "重启",
     sleep: "休眠",
+    hibernate: "深度休眠",
+    lock: "锁定",
+    log_off: "注销",
+    display_off: "关闭显示器",
     pleaseSelect: "请先选择一个应用！",
     invalidTime: "请输入有效的时间！",
     switchToLight: "切换浅色",
     switchToDark: "切换深色",
     refresh: "刷新",
-    appExited: "应用已退出，任务取消"
+    appExited: "应用已退出，任务取消",
+    osFallback: "交由系统计时（应用关闭后仍会执行）",
+    osMode: "系统计时",
+    needs_admin: "需要以管理员身份运行",
+    unsupported: "当前系统不支持此操作",
+    process_gone: "应用已退出",
+    not_found: "目标不存在",
+    invalid_input: "输入无效",
+    failed: "操作失败",
+    relaunchAsAdmin: "此操作需要管理员权限，是否以管理员身份重新启动？",
+    killAsAdmin: "该应用以管理员身份运行，是否通过管理员权限关闭？",
+    admin: "管理员",
+    rebootPending: "有更新或安装等待重启完成",
+    restartInstead: "改为重启",
+    uptime: "已运行",
+    day: "天",
+    lastWake: "上次唤醒",
+    quickSchedule: "快速输入",
+    quickPlaceholder: "如 2小时30分钟后、23:45、明天早上7点",
+    warningCheckpoints: "提醒时间（分钟）",
+    warningPlaceholder: "如 30, 10, 5, 1"
   },
   en: {
     runningApps: "Running Apps",
@@ -62,14 +145,39 @@ const translations = {
     sysTimerDesc: "Schedule system shutdown, restart, or sleep",
     action: "Action",
     shutdown: "Shutdown",
+    full_shutdown: "Full shutdown (skip fast startup)",
     restart: "Restart",
     sleep: "Sleep",
+    hibernate: "Hibernate",
+    lock: "Lock",
+    log_off: "Log off",
+    display_off: "Turn off display",
     pleaseSelect: "Please select an app first!",
     invalidTime: "Please enter a valid time!",
     switchToLight: "Switch to Light Mode",
     switchToDark: "Switch to Dark Mode",
     refresh: "Refresh",
-    appExited: "App exited, task cancelled"
+    appExited: "App exited, task cancelled",
+    osFallback: "Let Windows keep the timer (survives closing the app)",
+    osMode: "OS timer",
+    needs_admin: "Run AutoShutdown as administrator to do this",
+    unsupported: "Not supported on this system",
+    process_gone: "The app has already exited",
+    not_found: "Not found",
+    invalid_input: "Invalid input",
+    failed: "Operation failed",
+    relaunchAsAdmin: "This needs administrator rights. Restart AutoShutdown as administrator?",
+    killAsAdmin: "This app runs as administrator. Close it with administrator rights?",
+    admin: "Admin",
+    rebootPending: "Updates or installs are waiting for a restart",
+    restartInstead: "Restart instead",
+    uptime: "Up",
+    day: "days",
+    lastWake: "Last wake",
+    quickSchedule: "Quick entry",
+    quickPlaceholder: "e.g. in 2h 30m, at 23:45, tomorrow 7am",
+    warningCheckpoints: "Warnings (minutes)",
+    warningPlaceholder: "e.g. 30, 10, 5, 1"
   }
 };
 
@@ -82,18 +190,24 @@ function App() {
   const [appHours, setAppHours] = useState("0");
   const [sysMinutes, setSysMinutes] = useState("0");
   const [sysHours, setSysHours] = useState("1");
-  const [sysAction, setSysAction] = useState("shutdown"); // 存储英文 key
+  const [quickText, setQuickText] = useState("");
+  const [sysWarnings, setSysWarnings] = useState("1");
+  const [sysAction, setSysAction] = useState<PowerAction>("shutdown"); // 存储英文 key
   const [shutdownAfter, setShutdownAfter] = useState(false);
+  const [osFallback, setOsFallback] = useState(false);
+  const [sysMode, setSysMode] = useState<CountdownStatus['mode'] | null>(null);
   const [appTimer, setAppTimer] = useState(0);
   const [sysTimer, setSysTimer] = useState(0);
   const [appStatus, setAppStatus] = useState("");
   const [sysStatus, setSysStatus] = useState("");
   const [loading, setLoading] = useState(true);
+  const [capabilities, setCapabilities] = useState<PowerCapabilities | null>(null);
+  const [pendingReboot, setPendingReboot] = useState(false);
+  const [uptime, setUptime] = useState<UptimeInfo | null>(null);
   const [isDark, setIsDark] = useState(false);
   const [lang, setLang] = useState<Language>('zh');
 
   const appIntervalRef = useRef<number | null>(null);
-  const sysIntervalRef = useRef<number | null>(null);
 
   // 初始化语言
   useEffect(() => {
@@ -114,10 +228,26 @@ function App() {
     document.documentElement.setAttribute('data-theme', isDark ? 'dark' : 'light');
   }, [isDark]);
 
-  const loadApps = async () => {
+  // 按错误类型显示可操作的提示，附带后端的详细信息
+  const describeError = (e: unknown) => {
+    const err = e as Partial<AppError>;
+    if (err && err.kind && err.kind in translations.zh) {
+      return `${t(err.kind)}: ${err.message}`;
+    }
+    return String(e);
+  };
+
+  // 需要管理员权限时提示通过 UAC 重新启动
+  const offerElevation = (e: unknown) => {
+    if ((e as Partial<AppError>)?.kind === 'needs_admin' && window.confirm(t('relaunchAsAdmin'))) {
+      invoke("relaunch_elevated").catch(err => alert(describeError(err)));
+    }
+  };
+
+  const loadApps = async (forceRefresh = false) => {
     try {
       setLoading(true);
-      const result = await invoke<ProcessInfo[]>("get_running_apps");
+      const result = await invoke<ProcessInfo[]>("get_running_apps", { forceRefresh });
       setApps(result);
     } catch (e) {
       console.error(e);
@@ -128,17 +258,50 @@ function App() {
 
   useEffect(() => {
     loadApps();
-    let unlisten: (() => void) | undefined;
+    const unlisteners: (() => void)[] = [];
     import('@tauri-apps/api/event').then(({ listen }) => {
       listen('window-changed', () => {
         loadApps();
       }).then(fn => {
-        unlisten = fn;
+        unlisteners.push(fn);
+      });
+      // 标题变化直接更新列表中的标题，不重新枚举进程
+      listen<TitleChanged>('title-changed', ({ payload }) => {
+        setApps(prev => prev.map(app => app.pid === payload.pid ? { ...app, title: payload.title } : app));
+      }).then(fn => {
+        unlisteners.push(fn);
       });
     });
-    const interval = setInterval(loadApps, 30000);
+    const interval = setInterval(() => loadApps(), 30000);
     return () => {
       clearInterval(interval);
+      unlisteners.forEach(fn => fn());
+    };
+  }, []);
+
+  // 查询失败时不限制选项，由后端在执行时报错
+  useEffect(() => {
+    invoke<PowerCapabilities>("get_power_capabilities").then(setCapabilities).catch(console.error);
+    invoke<PendingReboot>("get_pending_reboot_state").then(state => setPendingReboot(state.pending)).catch(console.error);
+    invoke<UptimeInfo>("get_uptime_info").then(setUptime).catch(console.error);
+  }, []);
+
+  // 系统倒计时由后端负责，这里只同步显示
+  useEffect(() => {
+    const applyStatus = (status: CountdownStatus | null) => {
+      setSysTimer(status ? status.remaining_secs : 0);
+      setSysMode(status ? status.mode : null);
+    };
+    invoke<CountdownStatus | null>("get_countdown_status").then(applyStatus);
+    let unlisten: (() => void) | undefined;
+    import('@tauri-apps/api/event').then(({ listen }) => {
+      listen<CountdownStatus | null>('countdown-tick', e => {
+        applyStatus(e.payload);
+      }).then(fn => {
+        unlisten = fn;
+      });
+    });
+    return () => {
       if (unlisten) unlisten();
     };
   }, []);
@@ -184,12 +347,25 @@ function App() {
 
   const executeAppShutdown = async () => {
     if (selectedApp) {
-      await invoke("kill_process", { pid: selectedApp.pid });
-      setAppStatus(t('closed'));
-      if (shutdownAfter) {
-        await invoke("system_shutdown");
+      try {
+        try {
+          await invoke("kill_process", { pid: selectedApp.pid });
+        } catch (e) {
+          // 提升的进程可以单独通过 UAC 结束，不必重启整个应用
+          if ((e as Partial<AppError>)?.kind !== 'needs_admin' || !window.confirm(t('killAsAdmin'))) {
+            throw e;
+          }
+          await invoke("kill_process", { pid: selectedApp.pid, useElevation: true });
+        }
+        setAppStatus(t('closed'));
+        if (shutdownAfter) {
+          await invoke("system_shutdown");
+        }
+      } catch (e) {
+        setAppStatus(describeError(e));
+        offerElevation(e);
       }
-      loadApps();
+      loadApps(true);
       setSelectedApp(null);
     }
   };
@@ -200,7 +376,21 @@ function App() {
     setAppStatus(t('cancelled'));
   };
 
-  const startSysTimer = () => {
+  // 由后端解析输入的时长或时间，换算成倒计时的小时和分钟
+  const applyQuickSchedule = async () => {
+    if (!quickText.trim()) return;
+    try {
+      const parsed = await invoke<ParsedSchedule>("parse_schedule", { text: quickText });
+      const totalMinutes = Math.max(1, Math.ceil((new Date(parsed.time).getTime() - Date.now()) / 60000));
+      setSysHours(Math.floor(totalMinutes / 60).toString());
+      setSysMinutes((totalMinutes % 60).toString());
+      setSysStatus("");
+    } catch (e) {
+      setSysStatus(describeError(e));
+    }
+  };
+
+  const startSysTimer = async () => {
     const mins = parseFloat(sysMinutes) || 0;
     const hours = parseFloat(sysHours) || 0;
     const totalMinutes = hours * 60 + mins;
@@ -209,33 +399,47 @@ function App() {
       alert(t('invalidTime'));
       return;
     }
-    setSysTimer(Math.floor(totalMinutes * 60));
     setSysStatus("");
-    
-    sysIntervalRef.current = setInterval(() => {
-      setSysTimer(prev => {
-        if (prev <= 1) {
-          clearInterval(sysIntervalRef.current!);
-          executeSysAction();
-          return 0;
-        }
-        return prev - 1;
+    // 逗号分隔的分钟数，无效的项忽略
+    const warningSecs = sysWarnings
+      .split(/[,，\s]+/)
+      .map(v => Math.round((parseFloat(v) || 0) * 60))
+      .filter(v => v > 0);
+    try {
+      const status = await invoke<CountdownStatus>("start_countdown", {
+        action: sysAction,
+        seconds: Math.floor(totalMinutes * 60),
+        osFallback,
+        warningSecs,
       });
-    }, 1000);
-  };
-
-  const executeSysAction = async () => {
-    if (sysAction === "shutdown") await invoke("system_shutdown");
-    else if (sysAction === "restart") await invoke("system_restart");
-    else if (sysAction === "sleep") await invoke("system_sleep");
+      setSysTimer(status.remaining_secs);
+      setSysMode(status.mode);
+    } catch (e) {
+      setSysStatus(describeError(e));
+      offerElevation(e);
+    }
   };
 
-  const cancelSysTimer = () => {
-    if (sysIntervalRef.current) clearInterval(sysIntervalRef.current);
+  const cancelSysTimer = async () => {
+    await invoke("cancel_countdown");
     setSysTimer(0);
+    setSysMode(null);
     setSysStatus(t('cancelled'));
   };
 
+  // 运行时间精确到分钟，超过一天时显示天数
+  const formatUptime = (info: UptimeInfo) => {
+    const days = Math.floor(info.uptime_secs / 86400);
+    const hours = Math.floor((info.uptime_secs % 86400) / 3600);
+    const minutes = Math.floor((info.uptime_secs % 3600) / 60);
+    const dayText = days > 0 ? `${days} ${t('day')} ` : '';
+    const uptimeText = `${t('uptime')} ${dayText}${hours} ${t('hour')} ${minutes} ${t('minute')}`;
+    const wake = info.last_wake?.wake_time;
+    if (!wake) return uptimeText;
+    const source = info.last_wake?.wake_source ? ` (${info.last_wake.wake_source})` : '';
+    return `${uptimeText} · ${t('lastWake')} ${new Date(wake).toLocaleString()}${source}`;
+  };
+
   const formatTime = (seconds: number) => {
     const h = Math.floor(seconds / 3600);
     const m = Math.floor((seconds % 3600) / 60);
@@ -320,6 +524,7 @@ function App() {
                   </div>
                 )}
                 <span className="app-name">{getAppDisplayName(app)}</span>
+                {app.elevated && <span className="app-badge">{t('admin')}</span>}
               </button>
             ))
           )}
@@ -330,6 +535,7 @@ function App() {
         <div className="header-section">
           <h1>{selectedApp ? getAppDisplayName(selectedApp) : t('appName')}</h1>
           <p className="header-subtitle">{selectedApp ? selectedApp.title : t('selectAppHint')}</p>
+          {uptime && <p className="header-uptime">{formatUptime(uptime)}</p>}
         </div>
         
         <div className="card">
@@ -425,11 +631,16 @@ function App() {
                 <label>{t('action')}</label>
                 <select 
                   value={sysAction}
-                  onChange={e => setSysAction(e.target.value)}
+                  onChange={e => setSysAction(e.target.value as PowerAction)}
                 >
                   <option value="shutdown">{t('shutdown')}</option>
+                  <option value="full_shutdown">{t('full_shutdown')}</option>
                   <option value="restart">{t('restart')}</option>
-                  <option value="sleep">{t('sleep')}</option>
+                  <option value="sleep" disabled={capabilities?.sleep === false}>{t('sleep')}</option>
+                  <option value="hibernate" disabled={capabilities?.hibernate === false}>{t('hibernate')}</option>
+                  <option value="lock">{t('lock')}</option>
+                  <option value="log_off">{t('log_off')}</option>
+                  <option value="display_off">{t('display_off')}</option>
                 </select>
               </div>
               
@@ -456,7 +667,48 @@ function App() {
                   </div>
                 </div>
               </div>
+
+              <div className="form-group">
+                <label>{t('quickSchedule')}</label>
+                <input
+                  type="text"
+                  value={quickText}
+                  placeholder={t('quickPlaceholder')}
+                  onChange={e => setQuickText(e.target.value)}
+                  onKeyDown={e => e.key === 'Enter' && applyQuickSchedule()}
+                  onBlur={applyQuickSchedule}
+                />
+              </div>
+
+              <div className="form-group">
+                <label>{t('warningCheckpoints')}</label>
+                <input
+                  type="text"
+                  value={sysWarnings}
+                  placeholder={t('warningPlaceholder')}
+                  onChange={e => setSysWarnings(e.target.value)}
+                />
+              </div>
+
+              <label className="checkbox-label" style={{ marginTop: '24px' }}>
+                <input 
+                  type="checkbox"
+                  checked={osFallback}
+                  onChange={e => setOsFallback(e.target.checked)}
+                  disabled={sysAction !== "shutdown" && sysAction !== "full_shutdown" && sysAction !== "restart"}
+                />
+                <span>{t('osFallback')}</span>
+              </label>
             </div>
+
+            {(sysAction === "shutdown" || sysAction === "full_shutdown") && pendingReboot && (
+              <div className="form-hint">
+                <span>{t('rebootPending')}</span>
+                <button className="link-button" onClick={() => setSysAction("restart")}>
+                  {t('restartInstead')}
+                </button>
+              </div>
+            )}
             
             <div className="card-actions">
               <button 
@@ -472,7 +724,9 @@ function App() {
               
               <div className="timer-display">
                 {sysTimer > 0 ? (
-                  <span className="timer red">{formatTime(sysTimer)}</span>
+                  <span className="timer red">
+                    {formatTime(sysTimer)}{sysMode === 'os' ? ` · ${t('osMode')}` : ''}
+                  </span>
                 ) : sysStatus ? (
                   <span className="status">{sysStatus}</span>
                 ) : null}